@@ -0,0 +1,173 @@
+// zip 归档辅助函数：供壁纸库导出 / 导入与壁纸包共用
+//
+// 解压时只接受归档内的相对路径（拒绝 `..` 与绝对路径），并限制单个条目与总解压大小。
+
+use std::fs;
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+pub fn create_archive(path: &Path) -> Result<ZipWriter<fs::File>, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let file = fs::File::create(path).map_err(|e| format!("Failed to create archive: {}", e))?;
+    Ok(ZipWriter::new(file))
+}
+
+pub fn open_archive(path: &Path) -> Result<ZipArchive<fs::File>, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    ZipArchive::new(file).map_err(|e| format!("Invalid archive: {}", e))
+}
+
+/// 写入文本条目
+pub fn add_bytes<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    data: &[u8],
+) -> Result<(), String> {
+    zip.start_file(name, SimpleFileOptions::default())
+        .map_err(|e| format!("Failed to write archive entry: {}", e))?;
+    zip.write_all(data)
+        .map_err(|e| format!("Failed to write archive entry: {}", e))
+}
+
+/// 写入单个文件，返回字节数
+pub fn add_file<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    source: &Path,
+    name: &str,
+) -> Result<u64, String> {
+    let mut file = fs::File::open(source)
+        .map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+    zip.start_file(name, SimpleFileOptions::default())
+        .map_err(|e| format!("Failed to write archive entry: {}", e))?;
+    std::io::copy(&mut file, zip).map_err(|e| format!("Failed to write archive entry: {}", e))
+}
+
+/// 递归写入文件夹，条目名为 `prefix/相对路径`，返回字节数
+pub fn add_dir<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    dir: &Path,
+    prefix: &str,
+) -> Result<u64, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+
+    let mut total = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            total += add_dir(zip, &path, &name)?;
+        } else {
+            total += add_file(zip, &path, &name)?;
+        }
+    }
+    Ok(total)
+}
+
+/// 条目在归档内的安全相对路径（使用 `/` 分隔），不安全的条目返回 None
+pub fn entry_name<R: Read + Seek>(archive: &mut ZipArchive<R>, index: usize) -> Option<String> {
+    let entry = archive.by_index(index).ok()?;
+    let path = entry.enclosed_name()?;
+    Some(
+        path.components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// 检查归档：拒绝越界路径，并限制单个条目与总解压大小
+pub fn validate_archive<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    max_entry_bytes: u64,
+    max_total_bytes: u64,
+) -> Result<(), String> {
+    let mut total: u64 = 0;
+    for index in 0..archive.len() {
+        let entry = archive
+            .by_index(index)
+            .map_err(|e| format!("Invalid archive entry: {}", e))?;
+        if entry.enclosed_name().is_none() {
+            return Err(format!("Unsafe path in archive: {}", entry.name()));
+        }
+        if entry.size() > max_entry_bytes {
+            return Err(format!("Archive entry too large: {}", entry.name()));
+        }
+        total = total.saturating_add(entry.size());
+        if total > max_total_bytes {
+            return Err("Archive is too large".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// 读取文本条目
+pub fn read_string<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+    max_bytes: u64,
+) -> Result<String, String> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|_| format!("Missing {} in archive", name))?;
+    let mut content = String::new();
+    entry
+        .take(max_bytes + 1)
+        .read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    if content.len() as u64 > max_bytes {
+        return Err(format!("{} is too large", name));
+    }
+    Ok(content)
+}
+
+/// 解压条目到指定文件，实际大小超过上限时中止
+///
+/// 条目声明的大小不可信，`remaining` 为整个归档还可写入的字节数，按实际写入量扣减。
+pub fn extract_to<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    index: usize,
+    target: &Path,
+    max_bytes: u64,
+    remaining: &mut u64,
+) -> Result<u64, String> {
+    let entry = archive
+        .by_index(index)
+        .map_err(|e| format!("Invalid archive entry: {}", e))?;
+    if entry.is_dir() {
+        fs::create_dir_all(target).map_err(|e| format!("Failed to create directory: {}", e))?;
+        return Ok(0);
+    }
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let limit = max_bytes.min(*remaining);
+    let mut file = fs::File::create(target).map_err(|e| format!("Failed to create file: {}", e))?;
+    let written = std::io::copy(&mut entry.take(limit + 1), &mut file)
+        .map_err(|e| format!("Failed to extract file: {}", e))?;
+    if written > limit {
+        drop(file);
+        let _ = fs::remove_file(target);
+        return Err(if written > max_bytes {
+            format!("Archive entry too large: {}", target.display())
+        } else {
+            "Archive is too large".to_string()
+        });
+    }
+    *remaining -= written;
+    Ok(written)
+}
+
+/// 将 `/` 分隔的归档内相对路径拼接到目录下
+pub fn join_relative(base: &Path, relative: &str) -> PathBuf {
+    relative
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .fold(base.to_path_buf(), |path, part| path.join(part))
+}
@@ -1,4 +1,6 @@
+use crate::data_dir::try_write_guard;
 use crate::fs_helper::{read_file, write_file};
+use crate::network::invalidate_network_settings;
 
 #[tauri::command]
 pub fn read_config() -> Result<String, String> {
@@ -17,7 +19,9 @@ pub fn read_config() -> Result<String, String> {
 
 #[tauri::command]
 pub fn set_config(content: String) -> Result<String, String> {
+    let _writing = try_write_guard()?;
     let config = write_file("config.json".to_string(), content);
+    invalidate_network_settings();
     match config {
         Ok(content) => Ok(content),
         Err(_) => {
@@ -26,3 +30,31 @@ pub fn set_config(content: String) -> Result<String, String> {
         }
     }
 }
+
+/// 读取配置并解析为 JSON 对象，解析失败时返回空对象
+pub fn read_config_value() -> serde_json::Value {
+    let config = read_config().unwrap_or_else(|_| "{}".to_string());
+    serde_json::from_str(&config).unwrap_or(serde_json::Value::Object(serde_json::Map::new()))
+}
+
+/// 合并更新配置中的顶层字段（与前端 Config.saveConfig 行为一致）
+pub fn update_config(updates: serde_json::Value) -> Result<(), String> {
+    let _writing = try_write_guard()?;
+    let mut config = read_config_value();
+    let obj = config
+        .as_object_mut()
+        .ok_or_else(|| "Invalid config format".to_string())?;
+
+    if let serde_json::Value::Object(updates) = updates {
+        for (key, value) in updates {
+            obj.insert(key, value);
+        }
+    }
+
+    let content =
+        serde_json::to_string(&config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    write_file("config.json".to_string(), content)?;
+    invalidate_network_settings();
+
+    Ok(())
+}
@@ -0,0 +1,261 @@
+// 网络连接监测：跟踪在线 / 离线状态，状态变化时发出 `connectivity-changed` 事件
+//
+// 在线时每 60 秒、离线时每 15 秒探测一次；其他模块的请求因网络错误失败时会立即重新探测。
+// 探测地址可在 network.connectivity_url 中设置，为空时依次尝试默认地址，任一有响应即视为在线。
+//
+// 离线时通过 URL 设置壁纸的请求加入待处理队列（pending_downloads.json），恢复在线后自动执行，
+// 同一时间只保留最后一次设置壁纸的请求。恢复在线后还会补做错过的每日订阅。
+//
+// 事件：
+// connectivity-changed: { "online": false, "since": 1700000000 }
+// pending-download: { "url": "https://...", "action": "set_wallpaper", "path": "...", "error": null }
+
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tokio::sync::Notify;
+
+use crate::data_dir::background_write_guard;
+use crate::fs_helper::{read_file, write_file};
+use crate::network::{http_client, read_network_settings};
+
+/// 待处理的下载（相对 appdata 目录）
+const PENDING_FILE: &str = "pending_downloads.json";
+const ONLINE_INTERVAL_SECS: u64 = 60;
+const OFFLINE_INTERVAL_SECS: u64 = 15;
+const PROBE_TIMEOUT_SECS: u64 = 5;
+const DEFAULT_PROBE_URLS: [&str; 2] = [
+    "http://www.gstatic.com/generate_204",
+    "http://www.msftconnecttest.com/connecttest.txt",
+];
+/// 离线时请求返回的错误
+pub const OFFLINE_ERROR: &str = "Network is offline";
+/// 设置壁纸
+pub const SET_WALLPAPER: &str = "set_wallpaper";
+
+/// 启动时假定在线，由第一次探测确定
+static ONLINE: AtomicBool = AtomicBool::new(true);
+/// 当前状态开始的时间（Unix 秒）
+static SINCE: AtomicI64 = AtomicI64::new(0);
+/// 请求立即重新探测
+static RECHECK: Notify = Notify::const_new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingDownload {
+    pub url: String,
+    pub action: String,
+    pub added_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectivityStatus {
+    pub online: bool,
+    pub since: i64,
+    pub pending: Vec<PendingDownload>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ConnectivityChanged {
+    online: bool,
+    since: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PendingResult {
+    url: String,
+    action: String,
+    path: Option<String>,
+    error: Option<String>,
+}
+
+/// 最近一次探测是否在线
+pub fn is_online() -> bool {
+    ONLINE.load(Ordering::SeqCst)
+}
+
+/// 已知离线时返回错误，避免发出注定失败的请求
+pub fn ensure_online() -> Result<(), String> {
+    if is_online() {
+        Ok(())
+    } else {
+        Err(OFFLINE_ERROR.to_string())
+    }
+}
+
+/// 请求因网络错误失败，立即重新探测
+pub fn report_network_error() {
+    RECHECK.notify_one();
+}
+
+fn load_pending() -> Vec<PendingDownload> {
+    read_file(PENDING_FILE.to_string())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_pending(pending: &[PendingDownload]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(pending)
+        .map_err(|e| format!("Failed to serialize pending downloads: {}", e))?;
+    write_file(PENDING_FILE.to_string(), content)?;
+    Ok(())
+}
+
+/// 加入待处理队列，同一操作只保留最后一次请求
+pub fn queue_download(url: &str, action: &str) -> Result<(), String> {
+    let mut pending = load_pending();
+    pending.retain(|item| item.action != action);
+    pending.push(PendingDownload {
+        url: url.to_string(),
+        action: action.to_string(),
+        added_at: chrono::Utc::now().timestamp(),
+    });
+    save_pending(&pending)?;
+    println!("Offline: queued {} {}", action, url);
+    Ok(())
+}
+
+/// 恢复在线后执行待处理的下载，再次失败且离线时会重新加入队列
+async fn process_pending(app: &tauri::AppHandle) {
+    let pending = load_pending();
+    if pending.is_empty() {
+        return;
+    }
+    if let Err(e) = save_pending(&[]) {
+        eprintln!("Failed to clear pending downloads: {}", e);
+        return;
+    }
+
+    for item in pending {
+        let result = match item.action.as_str() {
+            SET_WALLPAPER => {
+                crate::wallpaper_static::set_wallpaper_from_url(app, &item.url, true).await
+            }
+            action => Err(format!("Unknown pending action: {}", action)),
+        };
+        if let Err(e) = &result {
+            eprintln!("Pending {} {} failed: {}", item.action, item.url, e);
+        }
+        let (path, error) = match result {
+            Ok(path) => (Some(path), None),
+            Err(e) => (None, Some(e)),
+        };
+        let _ = app.emit(
+            "pending-download",
+            PendingResult {
+                url: item.url,
+                action: item.action,
+                path,
+                error,
+            },
+        );
+    }
+}
+
+/// 依次请求探测地址，任一有响应（不论状态码）即视为在线
+async fn probe() -> bool {
+    let client = match http_client() {
+        Ok(client) => client,
+        Err(_) => return false,
+    };
+    let urls: Vec<String> = match read_network_settings().connectivity_url {
+        Some(url) if !url.trim().is_empty() => vec![url.trim().to_string()],
+        _ => DEFAULT_PROBE_URLS
+            .iter()
+            .map(|url| url.to_string())
+            .collect(),
+    };
+
+    for url in urls {
+        let response = client
+            .get(&url)
+            .timeout(Duration::from_secs(PROBE_TIMEOUT_SECS))
+            .send()
+            .await;
+        if response.is_ok() {
+            return true;
+        }
+    }
+    false
+}
+
+fn set_online(app: &tauri::AppHandle, online: bool) {
+    if ONLINE.swap(online, Ordering::SeqCst) == online && SINCE.load(Ordering::SeqCst) != 0 {
+        return;
+    }
+    let since = chrono::Utc::now().timestamp();
+    SINCE.store(since, Ordering::SeqCst);
+    println!(
+        "Connectivity: {}",
+        if online { "online" } else { "offline" }
+    );
+    let _ = app.emit(
+        "connectivity-changed",
+        ConnectivityChanged { online, since },
+    );
+
+    if online {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            // 与其他后台任务一样，迁移数据目录期间暂停
+            let writing = background_write_guard().await;
+            process_pending(&app).await;
+            crate::subscriptions::catch_up_subscriptions(&app).await;
+            drop(writing);
+        });
+    }
+}
+
+/// 立即探测并更新状态，返回是否在线
+pub async fn check_connectivity(app: &tauri::AppHandle) -> bool {
+    let online = probe().await;
+    set_online(app, online);
+    online
+}
+
+fn status() -> ConnectivityStatus {
+    ConnectivityStatus {
+        online: is_online(),
+        since: SINCE.load(Ordering::SeqCst),
+        pending: load_pending(),
+    }
+}
+
+/// 当前连接状态与待处理的下载
+#[tauri::command]
+pub fn get_connectivity_status() -> ConnectivityStatus {
+    status()
+}
+
+/// 立即重新探测连接状态
+#[tauri::command]
+pub async fn check_connectivity_now(app: tauri::AppHandle) -> ConnectivityStatus {
+    check_connectivity(&app).await;
+    status()
+}
+
+/// 清空待处理的下载
+#[tauri::command]
+pub fn clear_pending_downloads() -> Result<(), String> {
+    save_pending(&[])
+}
+
+/// 启动后台连接监测任务（启动时立即探测一次）
+pub fn start_connectivity_monitor(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let online = check_connectivity(&app).await;
+            let interval = if online {
+                ONLINE_INTERVAL_SECS
+            } else {
+                OFFLINE_INTERVAL_SECS
+            };
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+                _ = RECHECK.notified() => {}
+            }
+        }
+    });
+}
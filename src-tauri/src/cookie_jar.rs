@@ -0,0 +1,168 @@
+// Cookie 会话模块：fetch_request 指定 session 时，按会话名称保存与发送 Cookie
+//
+// 每个会话保存在 cookies/<会话名>.json，包括没有过期时间的会话 Cookie，
+// 因此登录状态在重启后仍然有效，直到服务器使其过期或调用 clear_fetch_session。
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use cookie_store::CookieStore;
+use serde::Serialize;
+
+use crate::data_dir::try_write_guard;
+use crate::fs_helper::get_appdata_dir;
+
+/// 已加载的会话
+static SESSIONS: Mutex<Option<HashMap<String, CookieStore>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub name: String,
+    /// 未过期的 Cookie 数量
+    pub cookies: usize,
+    /// 各 Cookie 所属的域名（去重）
+    pub domains: Vec<String>,
+}
+
+fn cookies_dir() -> Result<PathBuf, String> {
+    let dir = get_appdata_dir()?.join("cookies");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create cookies directory: {}", e))?;
+    Ok(dir)
+}
+
+/// 会话名称只允许字母、数字、`-`、`_`，用作文件名
+fn session_path(name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!("Invalid session name: {}", name));
+    }
+    Ok(cookies_dir()?.join(format!("{}.json", name)))
+}
+
+fn load_session(name: &str) -> Result<CookieStore, String> {
+    let path = session_path(name)?;
+    match fs::File::open(&path) {
+        Ok(file) => cookie_store::serde::json::load_all(BufReader::new(file))
+            .map_err(|e| format!("Failed to load session {}: {}", name, e)),
+        Err(_) => Ok(CookieStore::default()),
+    }
+}
+
+fn save_session(name: &str, store: &CookieStore) -> Result<(), String> {
+    let mut content = Vec::new();
+    cookie_store::serde::json::save_incl_expired_and_nonpersistent(store, &mut content)
+        .map_err(|e| format!("Failed to serialize session {}: {}", name, e))?;
+    let _writing = try_write_guard()?;
+    fs::write(session_path(name)?, content)
+        .map_err(|e| format!("Failed to save session {}: {}", name, e))
+}
+
+fn with_session<T, F: FnOnce(&mut CookieStore) -> T>(name: &str, f: F) -> Result<T, String> {
+    let mut sessions = SESSIONS
+        .lock()
+        .map_err(|_| "Cookie sessions are locked".to_string())?;
+    let sessions = sessions.get_or_insert_with(HashMap::new);
+    if !sessions.contains_key(name) {
+        sessions.insert(name.to_string(), load_session(name)?);
+    }
+    let store = sessions
+        .get_mut(name)
+        .ok_or_else(|| format!("Session not found: {}", name))?;
+    Ok(f(store))
+}
+
+/// 请求 `url` 时应发送的 Cookie 请求头，没有匹配的 Cookie 时为空
+pub fn cookie_header(session: &str, url: &reqwest::Url) -> Result<Option<String>, String> {
+    with_session(session, |store| {
+        let pairs: Vec<String> = store
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        if pairs.is_empty() {
+            None
+        } else {
+            Some(pairs.join("; "))
+        }
+    })
+}
+
+/// 保存响应中的 Set-Cookie
+pub fn store_response_cookies(
+    session: &str,
+    url: &reqwest::Url,
+    headers: &reqwest::header::HeaderMap,
+) -> Result<(), String> {
+    let cookies: Vec<cookie_store::RawCookie<'static>> = headers
+        .get_all(reqwest::header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| cookie_store::RawCookie::parse(value.to_string()).ok())
+        .collect();
+    if cookies.is_empty() {
+        return Ok(());
+    }
+
+    with_session(session, |store| {
+        store.store_response_cookies(cookies.into_iter(), url);
+        save_session(session, store)
+    })?
+}
+
+/// 列出已保存的会话
+#[tauri::command]
+pub fn list_fetch_sessions() -> Result<Vec<SessionInfo>, String> {
+    let mut names: Vec<String> = fs::read_dir(cookies_dir()?)
+        .map_err(|e| format!("Failed to read cookies directory: {}", e))?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+            } else {
+                None
+            }
+        })
+        .collect();
+    names.sort();
+
+    names
+        .into_iter()
+        .map(|name| {
+            with_session(&name, |store| {
+                let mut domains: Vec<String> = store
+                    .iter_unexpired()
+                    .filter_map(|cookie| cookie.domain.as_cow().map(|domain| domain.to_string()))
+                    .collect();
+                domains.sort();
+                domains.dedup();
+                SessionInfo {
+                    name: name.clone(),
+                    cookies: store.iter_unexpired().count(),
+                    domains,
+                }
+            })
+        })
+        .collect()
+}
+
+/// 清除会话中的全部 Cookie
+#[tauri::command]
+pub fn clear_fetch_session(name: String) -> Result<(), String> {
+    let path = session_path(&name)?;
+    if let Ok(mut sessions) = SESSIONS.lock() {
+        if let Some(sessions) = sessions.as_mut() {
+            sessions.remove(&name);
+        }
+    }
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to remove session {}: {}", name, e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,230 @@
+// 凭据存储模块：API Key 等机密只保存在后端，fetch_request 的请求头中以 `{{secret:名称}}` 引用
+//
+// 凭据以 AES-256-GCM 加密后保存在 credentials.json，密钥为数据目录下的 credentials.key（首次保存时生成）。
+// 保存时可指定允许的主机（规则同 fetch_policy），向其他主机发送的请求不会替换该凭据。
+// 天气等模块配置中的 API Key 也可以这样引用。凭据内容不会返回给前端。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::data_dir::try_write_guard;
+use crate::fetch_policy::host_matches;
+use crate::fs_helper::{get_appdata_dir, read_file, write_file};
+
+/// 加密后的凭据（相对 appdata 目录）
+const STORE_FILE: &str = "credentials.json";
+const KEY_FILE: &str = "credentials.key";
+const SECRET_PREFIX: &str = "{{secret:";
+const SECRET_SUFFIX: &str = "}}";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredCredential {
+    /// base64 编码的随机数
+    nonce: String,
+    /// base64 编码的密文
+    data: String,
+    #[serde(default)]
+    hosts: Vec<String>,
+    updated_at: i64,
+}
+
+/// 返回给前端的凭据信息（不含内容）
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialInfo {
+    pub name: String,
+    pub hosts: Vec<String>,
+    pub updated_at: i64,
+}
+
+fn key_path() -> Result<PathBuf, String> {
+    Ok(get_appdata_dir()?.join(KEY_FILE))
+}
+
+/// 读取密钥，不存在时生成；`create` 为 false 时不生成
+fn load_key(create: bool) -> Result<Option<Key<Aes256Gcm>>, String> {
+    let path = key_path()?;
+    if let Ok(bytes) = fs::read(&path) {
+        if bytes.len() != 32 {
+            return Err("Invalid credential key file".to_string());
+        }
+        return Ok(Some(*Key::<Aes256Gcm>::from_slice(&bytes)));
+    }
+    if !create {
+        return Ok(None);
+    }
+
+    let key = Aes256Gcm::generate_key(OsRng);
+    let _writing = try_write_guard()?;
+    fs::write(&path, key.as_slice())
+        .map_err(|e| format!("Failed to write credential key: {}", e))?;
+    // 仅当前用户可读
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&path, fs::Permissions::from_mode(0o600));
+    }
+    Ok(Some(key))
+}
+
+fn load_store() -> HashMap<String, StoredCredential> {
+    read_file(STORE_FILE.to_string())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_store(store: &HashMap<String, StoredCredential>) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(store)
+        .map_err(|e| format!("Failed to serialize credentials: {}", e))?;
+    let _writing = try_write_guard()?;
+    write_file(STORE_FILE.to_string(), content)?;
+    Ok(())
+}
+
+fn decode(data: &str) -> Result<Vec<u8>, String> {
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| format!("Invalid credential data: {}", e))
+}
+
+fn decrypt(key: &Key<Aes256Gcm>, credential: &StoredCredential) -> Result<String, String> {
+    let nonce = decode(&credential.nonce)?;
+    if nonce.len() != 12 {
+        return Err("Invalid credential data".to_string());
+    }
+    let plain = Aes256Gcm::new(key)
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            decode(&credential.data)?.as_slice(),
+        )
+        .map_err(|_| "Failed to decrypt credential".to_string())?;
+    String::from_utf8(plain).map_err(|_| "Failed to decrypt credential".to_string())
+}
+
+/// 凭据名称只允许字母、数字、`-`、`_`、`.`
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid credential name: {}", name))
+    }
+}
+
+/// 是否引用了凭据
+pub fn has_secret_reference(value: &str) -> bool {
+    value.contains(SECRET_PREFIX)
+}
+
+/// 替换字符串中的 `{{secret:名称}}`；`host` 为请求的主机，用于检查凭据允许的主机
+pub fn resolve_secret_value(value: &str, host: &str) -> Result<String, String> {
+    if !has_secret_reference(value) {
+        return Ok(value.to_string());
+    }
+
+    let key = load_key(false)?.ok_or_else(|| "No credentials have been saved".to_string())?;
+    let store = load_store();
+    let host = host.to_lowercase();
+    let ip = host.parse().ok();
+
+    let mut resolved = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find(SECRET_PREFIX) {
+        let after = &rest[start + SECRET_PREFIX.len()..];
+        let end = after
+            .find(SECRET_SUFFIX)
+            .ok_or_else(|| "Unterminated secret reference".to_string())?;
+        let name = after[..end].trim();
+        let credential = store
+            .get(name)
+            .ok_or_else(|| format!("Credential not found: {}", name))?;
+        if !credential.hosts.is_empty()
+            && !credential
+                .hosts
+                .iter()
+                .any(|rule| host_matches(&host, ip.as_ref(), rule))
+        {
+            return Err(format!("Credential {} is not allowed for {}", name, host));
+        }
+
+        resolved.push_str(&rest[..start]);
+        resolved.push_str(&decrypt(&key, credential)?);
+        rest = &after[end + SECRET_SUFFIX.len()..];
+    }
+    resolved.push_str(rest);
+    Ok(resolved)
+}
+
+/// 替换请求头中引用的凭据
+pub fn resolve_secrets(headers: &mut HashMap<String, String>, host: &str) -> Result<(), String> {
+    for value in headers.values_mut() {
+        *value = resolve_secret_value(value, host)?;
+    }
+    Ok(())
+}
+
+/// 保存（或覆盖）凭据，`hosts` 为空时不限制主机
+#[tauri::command]
+pub fn save_credential(
+    name: String,
+    value: String,
+    hosts: Option<Vec<String>>,
+) -> Result<CredentialInfo, String> {
+    validate_name(&name)?;
+    let key = load_key(true)?.ok_or_else(|| "Credential key is unavailable".to_string())?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let data = Aes256Gcm::new(&key)
+        .encrypt(&nonce, value.as_bytes())
+        .map_err(|_| "Failed to encrypt credential".to_string())?;
+
+    let engine = base64::engine::general_purpose::STANDARD;
+    let credential = StoredCredential {
+        nonce: engine.encode(nonce),
+        data: engine.encode(data),
+        hosts: hosts.unwrap_or_default(),
+        updated_at: chrono::Utc::now().timestamp(),
+    };
+    let info = CredentialInfo {
+        name: name.clone(),
+        hosts: credential.hosts.clone(),
+        updated_at: credential.updated_at,
+    };
+
+    let mut store = load_store();
+    store.insert(name, credential);
+    save_store(&store)?;
+    Ok(info)
+}
+
+/// 列出已保存的凭据（不含内容）
+#[tauri::command]
+pub fn list_credentials() -> Vec<CredentialInfo> {
+    let mut list: Vec<CredentialInfo> = load_store()
+        .into_iter()
+        .map(|(name, credential)| CredentialInfo {
+            name,
+            hosts: credential.hosts,
+            updated_at: credential.updated_at,
+        })
+        .collect();
+    list.sort_by(|a, b| a.name.cmp(&b.name));
+    list
+}
+
+#[tauri::command]
+pub fn delete_credential(name: String) -> Result<(), String> {
+    let mut store = load_store();
+    if store.remove(&name).is_none() {
+        return Err(format!("Credential not found: {}", name));
+    }
+    save_store(&store)
+}
@@ -0,0 +1,467 @@
+// 数据目录模块：统一解析 oPaper 数据目录，支持自定义位置、便携模式与迁移
+//
+// 解析优先级：
+//   1. 命令行参数 `--data-dir <路径>`（或 `--data-dir=<路径>`）
+//   2. 环境变量 OPAPER_DATA_DIR
+//   3. 可执行文件旁的 `portable` 标记文件：内容为空时使用 `<程序目录>/data`，否则使用其中的路径（可为相对程序目录的路径）
+//   4. move_data_dir 记录的位置（配置目录下的 oPaper/location.json）
+//   5. 默认 `<系统数据目录>/oPaper`
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{RwLock, RwLockReadGuard};
+
+use crate::config::read_config_value;
+use crate::library::mark_library_stale;
+use crate::network::invalidate_network_settings;
+use crate::storage::active_wallpaper_source;
+use crate::wallpaper_static::apply_static_wallpaper;
+
+const ENV_VAR: &str = "OPAPER_DATA_DIR";
+const CLI_FLAG: &str = "--data-dir";
+const PORTABLE_MARKER: &str = "portable";
+const PORTABLE_DEFAULT_DIR: &str = "data";
+const LOCATION_FILE: &str = "location.json";
+
+/// 已解析的数据目录，首次使用时解析，迁移后更新
+static DATA_DIR: Mutex<Option<(PathBuf, DataDirSource)>> = Mutex::new(None);
+
+/// 后台写入任务（库同步、存储清理、回收站清理、轮播、订阅、关联文件夹监听等）每轮持有读锁，
+/// 迁移数据目录时持有写锁，复制与切换期间后台任务暂停
+static BACKGROUND_WRITERS: RwLock<()> = RwLock::const_new(());
+
+/// 进行中的写入（配置、索引、回收站、导入、缓存等），迁移数据目录时拒绝新的写入并等待其完成
+static WRITES: Mutex<WriteState> = Mutex::new(WriteState {
+    moving: false,
+    active: 0,
+});
+
+/// 迁移时等待进行中的写入完成的检查间隔
+const WRITE_DRAIN_INTERVAL_MS: u64 = 50;
+
+struct WriteState {
+    moving: bool,
+    active: usize,
+}
+
+/// 写入数据目录期间持有，释放后迁移才会开始
+pub struct WriteGuard(());
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        let mut writes = WRITES.lock().unwrap_or_else(|e| e.into_inner());
+        writes.active -= 1;
+    }
+}
+
+/// 迁移期间持有，释放后恢复写入
+struct MovingGuard(());
+
+impl Drop for MovingGuard {
+    fn drop(&mut self) {
+        WRITES.lock().unwrap_or_else(|e| e.into_inner()).moving = false;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataDirSource {
+    Cli,
+    Env,
+    Portable,
+    Custom,
+    Default,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Location {
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataDirInfo {
+    pub path: String,
+    pub source: DataDirSource,
+    /// 数据目录由命令行或环境变量指定时无法在应用内迁移
+    pub movable: bool,
+}
+
+fn cli_data_dir() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == CLI_FLAG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(value) = arg.strip_prefix(&format!("{}=", CLI_FLAG)) {
+            return Some(PathBuf::from(value));
+        }
+    }
+    None
+}
+
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()?
+        .parent()
+        .map(|dir| dir.to_path_buf())
+}
+
+fn portable_marker() -> Option<PathBuf> {
+    let marker = exe_dir()?.join(PORTABLE_MARKER);
+    marker.is_file().then_some(marker)
+}
+
+fn portable_data_dir(marker: &Path) -> Option<PathBuf> {
+    let exe_dir = marker.parent()?;
+    let content = fs::read_to_string(marker).unwrap_or_default();
+    let content = content.trim();
+    Some(if content.is_empty() {
+        exe_dir.join(PORTABLE_DEFAULT_DIR)
+    } else {
+        exe_dir.join(content)
+    })
+}
+
+fn location_file() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("oPaper").join(LOCATION_FILE))
+}
+
+fn recorded_data_dir() -> Option<PathBuf> {
+    let content = fs::read_to_string(location_file()?).ok()?;
+    let location: Location = serde_json::from_str(&content).ok()?;
+    Some(PathBuf::from(location.path))
+}
+
+fn default_data_dir() -> Result<PathBuf, String> {
+    let app_data =
+        dirs::data_dir().ok_or_else(|| "Failed to get app data directory".to_string())?;
+    Ok(app_data.join("oPaper"))
+}
+
+fn resolve() -> Result<(PathBuf, DataDirSource), String> {
+    if let Some(dir) = cli_data_dir() {
+        return Ok((dir, DataDirSource::Cli));
+    }
+    if let Some(dir) = std::env::var_os(ENV_VAR).filter(|v| !v.is_empty()) {
+        return Ok((PathBuf::from(dir), DataDirSource::Env));
+    }
+    if let Some(dir) = portable_marker().and_then(|marker| portable_data_dir(&marker)) {
+        return Ok((dir, DataDirSource::Portable));
+    }
+    if let Some(dir) = recorded_data_dir() {
+        return Ok((dir, DataDirSource::Custom));
+    }
+    Ok((default_data_dir()?, DataDirSource::Default))
+}
+
+fn current() -> Result<(PathBuf, DataDirSource), String> {
+    let mut cached = DATA_DIR
+        .lock()
+        .map_err(|_| "Data directory is locked".to_string())?;
+    if let Some(current) = cached.as_ref() {
+        return Ok(current.clone());
+    }
+    let resolved = resolve()?;
+    *cached = Some(resolved.clone());
+    Ok(resolved)
+}
+
+/// 当前数据目录（不保证已创建）
+pub fn data_dir() -> Result<PathBuf, String> {
+    current().map(|(dir, _)| dir)
+}
+
+/// 后台任务每轮执行前获取，数据目录迁移期间会等待迁移完成
+pub async fn background_write_guard() -> RwLockReadGuard<'static, ()> {
+    BACKGROUND_WRITERS.read().await
+}
+
+/// 同 background_write_guard，供阻塞线程中的任务使用
+pub fn background_write_guard_blocking() -> RwLockReadGuard<'static, ()> {
+    BACKGROUND_WRITERS.blocking_read()
+}
+
+/// 写入数据目录前获取，迁移数据目录期间返回错误
+///
+/// 应在解析数据目录下的路径之前获取，迁移会等待所有已获取的写入完成。
+/// 可以嵌套获取，但迁移开始后内层获取同样会失败，调用方需按普通写入错误处理。
+pub fn try_write_guard() -> Result<WriteGuard, String> {
+    let mut writes = WRITES.lock().unwrap_or_else(|e| e.into_inner());
+    if writes.moving {
+        return Err("Data directory is being moved, please try again later".to_string());
+    }
+    writes.active += 1;
+    Ok(WriteGuard(()))
+}
+
+/// 拒绝新的写入，并等待进行中的写入完成
+async fn pause_writes() -> MovingGuard {
+    WRITES.lock().unwrap_or_else(|e| e.into_inner()).moving = true;
+    let guard = MovingGuard(());
+    while WRITES.lock().unwrap_or_else(|e| e.into_inner()).active > 0 {
+        tokio::time::sleep(std::time::Duration::from_millis(WRITE_DRAIN_INTERVAL_MS)).await;
+    }
+    guard
+}
+
+/// `path` 相对 `base` 的路径，没有共同根（如位于不同盘符）时返回 None
+fn relative_to(path: &Path, base: &Path) -> Option<PathBuf> {
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = path
+        .iter()
+        .zip(base.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        return None;
+    }
+
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    for component in &path[common..] {
+        relative.push(component.as_os_str());
+    }
+    Some(relative)
+}
+
+/// 记录新的数据目录位置：便携模式写入标记文件，否则写入配置目录
+fn persist_location(new_dir: &Path, source: DataDirSource) -> Result<(), String> {
+    match source {
+        DataDirSource::Portable => {
+            let marker =
+                portable_marker().ok_or_else(|| "Portable marker not found".to_string())?;
+            // 便携模式下整个程序目录可能被移动，尽量保存相对程序目录的路径
+            let content = marker
+                .parent()
+                .and_then(|exe_dir| relative_to(new_dir, exe_dir))
+                .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_else(|| new_dir.to_string_lossy().to_string());
+            fs::write(&marker, content.as_bytes())
+                .map_err(|e| format!("Failed to update portable marker: {}", e))
+        }
+        _ => {
+            let file =
+                location_file().ok_or_else(|| "Failed to get config directory".to_string())?;
+            if new_dir == default_data_dir()? {
+                let _ = fs::remove_file(&file);
+                return Ok(());
+            }
+            if let Some(parent) = file.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create config directory: {}", e))?;
+            }
+            let content = serde_json::to_string(&Location {
+                path: new_dir.to_string_lossy().to_string(),
+            })
+            .map_err(|e| format!("Failed to serialize location: {}", e))?;
+            fs::write(&file, content).map_err(|e| format!("Failed to save location: {}", e))
+        }
+    }
+}
+
+/// 递归复制目录内容，跳过 `skip`
+fn copy_dir(from: &Path, to: &Path, skip: Option<&Path>) -> Result<u64, String> {
+    fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+    let entries =
+        fs::read_dir(from).map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+
+    let mut total = 0;
+    for entry in entries.flatten() {
+        let source = entry.path();
+        if Some(source.as_path()) == skip {
+            continue;
+        }
+        let target = to.join(entry.file_name());
+        if source.is_dir() {
+            total += copy_dir(&source, &target, skip)?;
+        } else {
+            let copied = fs::copy(&source, &target)
+                .map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+            let expected = entry.metadata().map(|m| m.len()).unwrap_or(copied);
+            if copied != expected {
+                return Err(format!("Incomplete copy: {}", source.display()));
+            }
+            total += copied;
+        }
+    }
+    Ok(total)
+}
+
+/// 将 JSON 文件中指向旧数据目录的路径改为新数据目录
+///
+/// 只改写等于旧目录或位于其中的字符串值，共享前缀的其他路径（如 `oPaper-backup`）保持不变。
+fn rewrite_paths(file: &Path, old_dir: &Path, new_dir: &Path) {
+    let mut value: serde_json::Value = match fs::read_to_string(file)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+    {
+        Some(value) => value,
+        None => return,
+    };
+    if rewrite_value(&mut value, old_dir, new_dir) {
+        if let Ok(content) = serde_json::to_string(&value) {
+            let _ = fs::write(file, content);
+        }
+    }
+}
+
+/// 递归改写 JSON 中的路径字符串，返回是否有改动
+fn rewrite_value(value: &mut serde_json::Value, old_dir: &Path, new_dir: &Path) -> bool {
+    match value {
+        serde_json::Value::String(text) => {
+            // 按路径组件比较，`/data/oPaper-backup` 不在 `/data/oPaper` 之内
+            match Path::new(text.as_str()).strip_prefix(old_dir) {
+                Ok(rest) => {
+                    let path = if rest.as_os_str().is_empty() {
+                        new_dir.to_path_buf()
+                    } else {
+                        new_dir.join(rest)
+                    };
+                    *text = path.to_string_lossy().to_string();
+                    true
+                }
+                _ => false,
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().fold(false, |changed, item| {
+            rewrite_value(item, old_dir, new_dir) || changed
+        }),
+        serde_json::Value::Object(map) => map.values_mut().fold(false, |changed, item| {
+            rewrite_value(item, old_dir, new_dir) || changed
+        }),
+        _ => false,
+    }
+}
+
+/// 获取当前数据目录及其来源
+#[tauri::command]
+pub fn get_data_dir_info() -> Result<DataDirInfo, String> {
+    let (dir, source) = current()?;
+    Ok(DataDirInfo {
+        path: dir.to_string_lossy().to_string(),
+        source,
+        movable: !matches!(source, DataDirSource::Cli | DataDirSource::Env),
+    })
+}
+
+/// 将数据目录迁移到新位置：先完整复制，切换成功后再删除旧目录
+///
+/// 复制与切换期间暂停后台写入任务并拒绝其他写入，避免写入已复制过的旧目录而丢失。
+#[tauri::command]
+pub async fn move_data_dir(app: tauri::AppHandle, new_path: String) -> Result<DataDirInfo, String> {
+    let (old_dir, source) = current()?;
+    if matches!(source, DataDirSource::Cli | DataDirSource::Env) {
+        return Err(format!(
+            "Data directory is set by {} and cannot be moved here",
+            if source == DataDirSource::Cli {
+                CLI_FLAG
+            } else {
+                ENV_VAR
+            }
+        ));
+    }
+
+    let new_dir = PathBuf::from(&new_path);
+    if !new_dir.is_absolute() {
+        return Err("Data directory must be an absolute path".to_string());
+    }
+    if new_dir == old_dir || new_dir.starts_with(&old_dir) || old_dir.starts_with(&new_dir) {
+        return Err("New data directory must not overlap the current one".to_string());
+    }
+    if new_dir.exists() {
+        let empty = fs::read_dir(&new_dir)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+        if !empty {
+            return Err("New data directory must be empty".to_string());
+        }
+    }
+
+    {
+        // 先等后台任务完成本轮（期间仍可写入），再拒绝其他写入并等待进行中的写入完成
+        let _paused = BACKGROUND_WRITERS.write().await;
+        let _moving = pause_writes().await;
+        tauri::async_runtime::spawn_blocking(move || switch_data_dir(&old_dir, &new_dir, source))
+            .await
+            .map_err(|e| format!("Failed to move data directory: {}", e))??;
+    }
+    mark_library_stale();
+    invalidate_network_settings();
+
+    // 系统壁纸可能仍引用旧目录中的图片，重新设置当前静态壁纸
+    let mode = read_config_value()
+        .get("mode")
+        .and_then(|v| v.as_str())
+        .map(|mode| mode.to_string());
+    if mode.as_deref() == Some("static") {
+        if let Some(source) = active_wallpaper_source().filter(|p| Path::new(p).exists()) {
+            if let Err(e) = apply_static_wallpaper(&app, &source).await {
+                eprintln!("Failed to re-apply wallpaper after moving data: {}", e);
+            }
+        }
+    }
+
+    get_data_dir_info()
+}
+
+/// 复制数据、记录新位置并切换，成功后清理旧目录
+fn switch_data_dir(old_dir: &Path, new_dir: &Path, source: DataDirSource) -> Result<(), String> {
+    // 旧目录中可能有 location.json（macOS 上配置目录与数据目录相同），不复制
+    let location = location_file();
+    if let Err(e) = copy_dir(old_dir, new_dir, location.as_deref()) {
+        let _ = fs::remove_dir_all(new_dir);
+        return Err(e);
+    }
+
+    // 改写保存了绝对路径的文件（库索引会在下次同步时按相对路径自动更新）
+    rewrite_paths(&new_dir.join("config.json"), old_dir, new_dir);
+    rewrite_paths(&new_dir.join("active_wallpaper.json"), old_dir, new_dir);
+    if let Ok(entries) = fs::read_dir(new_dir.join("trash")) {
+        for entry in entries.flatten() {
+            rewrite_paths(&entry.path().join("trash.json"), old_dir, new_dir);
+        }
+    }
+
+    if let Err(e) = persist_location(new_dir, source) {
+        let _ = fs::remove_dir_all(new_dir);
+        return Err(e);
+    }
+    let new_source = if source == DataDirSource::Portable {
+        DataDirSource::Portable
+    } else if new_dir == default_data_dir()? {
+        DataDirSource::Default
+    } else {
+        DataDirSource::Custom
+    };
+    if let Ok(mut cached) = DATA_DIR.lock() {
+        *cached = Some((new_dir.to_path_buf(), new_source));
+    }
+
+    // 切换完成后清理旧目录，失败只记录日志
+    if let Ok(entries) = fs::read_dir(old_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if Some(path.as_path()) == location.as_deref() {
+                continue;
+            }
+            let result = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            if let Err(e) = result {
+                eprintln!("Failed to remove old data {}: {}", path.display(), e);
+            }
+        }
+    }
+    let _ = fs::remove_dir(old_dir);
+
+    println!("Data directory moved to {}", new_dir.display());
+    Ok(())
+}
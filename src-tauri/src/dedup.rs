@@ -0,0 +1,315 @@
+// 壁纸去重模块：内容哈希去重导入与感知哈希相似图检测
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::image_format::decodable_path;
+use crate::image_pipeline::load_image;
+use crate::library::{load_library, register_import, update_library_items, LibraryItem};
+use crate::linked_folders::is_linked_path;
+use crate::trash::move_to_trash;
+
+/// 相似图判定的默认汉明距离阈值（64 位 dHash）
+const DEFAULT_SIMILAR_THRESHOLD: u32 = 6;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportOutcome {
+    /// 库中的文件路径（重复时为已存在的文件）
+    pub path: String,
+    /// 内容与已有壁纸完全相同，未复制
+    pub duplicate: bool,
+    /// 因重名而改名
+    pub renamed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub hash: String,
+    /// 第一项为保留的壁纸（最早加入库）
+    pub items: Vec<LibraryItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+    /// 已移入回收站的重复文件
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarGroup {
+    pub items: Vec<LibraryItem>,
+    /// 组内两两之间的最大汉明距离
+    pub max_distance: u32,
+}
+
+/// 计算文件内容的 SHA-256
+pub fn content_hash(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// 计算 64 位差值哈希（dHash），对缩放、压缩、轻微调色不敏感
+pub fn perceptual_hash(path: &str) -> Result<u64, String> {
+    let decodable = decodable_path(path)?;
+    let image = load_image(Path::new(&decodable), true)?;
+    let gray = image
+        .resize_exact(9, 8, image::imageops::FilterType::Triangle)
+        .to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if gray.get_pixel(x, y)[0] > gray.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+
+    Ok(hash)
+}
+
+/// 目标目录中不冲突的文件路径：重名时依次尝试 `name (1).ext`、`name (2).ext`……
+pub fn unique_destination(dir: &Path, file_name: &str) -> PathBuf {
+    let candidate = dir.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let path = Path::new(file_name);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_name.to_string());
+    let extension = path.extension().map(|e| e.to_string_lossy().to_string());
+
+    (1..)
+        .map(|n| match &extension {
+            Some(ext) => dir.join(format!("{} ({}).{}", stem, n, ext)),
+            None => dir.join(format!("{} ({})", stem, n)),
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("unbounded range always yields a free name")
+}
+
+/// 复制（或硬链接）文件到目录中不冲突的路径，返回目标路径及是否改名
+///
+/// 硬链接失败（如跨分区）时退回复制。
+pub fn place_file(
+    source: &Path,
+    dest_dir: &Path,
+    hard_link: bool,
+) -> Result<(PathBuf, bool), String> {
+    let file_name = source
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| "Invalid file name".to_string())?;
+
+    let dest_path = unique_destination(dest_dir, file_name);
+    let renamed = dest_path.file_name().and_then(|n| n.to_str()) != Some(file_name);
+
+    if !(hard_link && fs::hard_link(source, &dest_path).is_ok()) {
+        fs::copy(source, &dest_path).map_err(|e| format!("Failed to copy image: {}", e))?;
+    }
+
+    Ok((dest_path, renamed))
+}
+
+/// 将图片导入静态壁纸目录：内容已存在则跳过，重名则改名
+///
+/// 关联文件夹中的文件不属于库本身（文件夹可能被移除），与其内容相同时仍然复制。
+pub fn import_static_file(source: &str, dest_dir: &Path) -> Result<ImportOutcome, String> {
+    let source_path = PathBuf::from(source);
+    let hash = content_hash(&source_path)?;
+    if let Some(existing) = load_library()?.items.into_iter().find(|item| {
+        item.kind == "static"
+            && item.content_hash.as_deref() == Some(&hash)
+            && !is_linked_path(&item.path)
+    }) {
+        println!(
+            "Skipping duplicate wallpaper: {} == {}",
+            source, existing.path
+        );
+        return Ok(ImportOutcome {
+            path: existing.path,
+            duplicate: true,
+            renamed: false,
+        });
+    }
+
+    let (dest_path, renamed) = place_file(&source_path, dest_dir, false)?;
+    let dest_path = dest_path.to_string_lossy().to_string();
+    // 登记来源，失败不影响导入
+    if let Err(e) = register_import("static", source, &dest_path) {
+        eprintln!("Failed to register library item: {}", e);
+    }
+
+    Ok(ImportOutcome {
+        path: dest_path,
+        duplicate: false,
+        renamed,
+    })
+}
+
+/// 扫描静态壁纸库中内容完全相同的文件，`remove` 为 true 时只保留最早加入的一份（其余移入回收站）
+#[tauri::command]
+pub fn scan_duplicate_wallpapers(remove: Option<bool>) -> Result<DuplicateReport, String> {
+    let mut by_hash: HashMap<String, Vec<LibraryItem>> = HashMap::new();
+    for item in load_library()?.items {
+        if item.kind != "static" {
+            continue;
+        }
+        if let Some(hash) = item.content_hash.clone() {
+            by_hash.entry(hash).or_default().push(item);
+        }
+    }
+
+    let mut groups: Vec<DuplicateGroup> = by_hash
+        .into_iter()
+        .filter(|(_, items)| items.len() > 1)
+        .map(|(hash, mut items)| {
+            items.sort_by_key(|item| (item.added_at, item.path.clone()));
+            DuplicateGroup { hash, items }
+        })
+        .collect();
+    groups.sort_by(|a, b| a.items[0].path.cmp(&b.items[0].path));
+
+    let mut removed = Vec::new();
+    if remove.unwrap_or(false) {
+        for group in &groups {
+            // 关联文件夹为只读来源，不删除其中的文件
+            for item in group.items[1..]
+                .iter()
+                .filter(|item| !is_linked_path(&item.path))
+            {
+                match move_to_trash("static", Path::new(&item.path)) {
+                    Ok(_) => removed.push(item.path.clone()),
+                    Err(e) => eprintln!("Failed to remove duplicate {}: {}", item.path, e),
+                }
+            }
+        }
+    }
+
+    Ok(DuplicateReport { groups, removed })
+}
+
+/// 查找外观相近的静态壁纸（感知哈希），仅报告不删除
+#[tauri::command]
+pub async fn find_similar_wallpapers(threshold: Option<u32>) -> Result<Vec<SimilarGroup>, String> {
+    let threshold = threshold.unwrap_or(DEFAULT_SIMILAR_THRESHOLD).min(32);
+    let items: Vec<LibraryItem> = load_library()?
+        .items
+        .into_iter()
+        .filter(|item| item.kind == "static")
+        .collect();
+
+    // 补算缺失的感知哈希并写回索引
+    let missing: Vec<(String, String)> = items
+        .iter()
+        .filter(|item| item.perceptual_hash.is_none())
+        .map(|item| (item.id.clone(), item.path.clone()))
+        .collect();
+    let computed: HashMap<String, String> = tokio::task::spawn_blocking(move || {
+        missing
+            .into_iter()
+            .filter_map(|(id, path)| match perceptual_hash(&path) {
+                Ok(hash) => Some((id, format!("{:016x}", hash))),
+                Err(e) => {
+                    eprintln!("Failed to hash {}: {}", path, e);
+                    None
+                }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Hash task failed: {}", e))?;
+
+    if !computed.is_empty() {
+        update_library_items(|all| {
+            for item in all.iter_mut() {
+                if let Some(hash) = computed.get(&item.id) {
+                    item.perceptual_hash = Some(hash.clone());
+                }
+            }
+        })?;
+    }
+
+    let hashed: Vec<(LibraryItem, u64)> = items
+        .into_iter()
+        .filter_map(|item| {
+            let hash = item
+                .perceptual_hash
+                .clone()
+                .or_else(|| computed.get(&item.id).cloned())?;
+            let hash = u64::from_str_radix(&hash, 16).ok()?;
+            Some((item, hash))
+        })
+        .collect();
+
+    // 按距离阈值做连通分组
+    let mut parent: Vec<usize> = (0..hashed.len()).collect();
+    fn find(parent: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parent[root] != root {
+            root = parent[root];
+        }
+        parent[i] = root;
+        root
+    }
+    for i in 0..hashed.len() {
+        for j in (i + 1)..hashed.len() {
+            if (hashed[i].1 ^ hashed[j].1).count_ones() <= threshold {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                parent[b] = a;
+            }
+        }
+    }
+
+    let mut clusters: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..hashed.len() {
+        let root = find(&mut parent, i);
+        clusters.entry(root).or_default().push(i);
+    }
+
+    let mut groups: Vec<SimilarGroup> = clusters
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let mut max_distance = 0;
+            for (n, &i) in members.iter().enumerate() {
+                for &j in &members[n + 1..] {
+                    max_distance = max_distance.max((hashed[i].1 ^ hashed[j].1).count_ones());
+                }
+            }
+            SimilarGroup {
+                items: members.iter().map(|&i| hashed[i].0.clone()).collect(),
+                max_distance,
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| a.items[0].path.cmp(&b.items[0].path));
+
+    Ok(groups)
+}
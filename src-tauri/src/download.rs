@@ -0,0 +1,437 @@
+// 下载管理模块：排队限流、超时重试、断点续传与进度事件
+//
+// 下载先写入 temp 下以 URL 哈希命名的 `.part` 文件，旁边的 `.validator` 记录响应的
+// ETag 或 Last-Modified。中断后再次下载同一 URL 时带上 If-Range 通过 Range 请求续传，
+// 远端文件已变化时服务器返回完整内容；完成后再移动到目标位置。
+//
+// 进度事件 `download-progress`：
+// { "id": "...", "url": "...", "status": "queued|downloading|retrying|done|failed",
+//   "bytes": 1024, "total": 4096, "speed": 51200, "error": null }
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::Emitter;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Semaphore;
+
+use crate::connectivity::{is_online, report_network_error};
+use crate::fs_helper::get_appdata_dir;
+use crate::network::http_client;
+
+/// 两次收到数据之间的最长等待
+const READ_TIMEOUT_SECS: u64 = 30;
+const MAX_CONCURRENT: usize = 3;
+/// 进度事件最短间隔
+const PROGRESS_INTERVAL_MS: u64 = 200;
+
+/// 下载队列：同时进行的下载数量上限
+static QUEUE: Semaphore = Semaphore::const_new(MAX_CONCURRENT);
+
+/// 正在下载的 ID，同一 URL 共用续传文件，不能同时下载
+static ACTIVE: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// 最大字节数
+    pub max_bytes: u64,
+    /// 允许的 Content-Type 前缀，为空时不检查
+    pub accept: &'static [&'static str],
+    /// 失败后的最多重试次数
+    pub retries: u32,
+}
+
+impl DownloadOptions {
+    /// 图片下载的默认限制
+    pub fn image() -> Self {
+        DownloadOptions {
+            max_bytes: 100 * 1024 * 1024,
+            accept: &["image/", "application/octet-stream", "binary/octet-stream"],
+            retries: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DownloadProgress<'a> {
+    id: &'a str,
+    url: &'a str,
+    status: &'a str,
+    bytes: u64,
+    total: Option<u64>,
+    /// 字节/秒
+    speed: u64,
+    error: Option<String>,
+}
+
+/// 单次尝试的失败：可重试或直接失败
+enum AttemptError {
+    Retry(String),
+    Fatal(String),
+}
+
+/// 下载 ID：URL 的哈希，同一 URL 共用一个续传文件
+fn download_id(url: &str) -> String {
+    let digest = Sha256::digest(url.as_bytes());
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn part_path(id: &str) -> Result<PathBuf, String> {
+    let dir = get_appdata_dir()?.join("temp");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    Ok(dir.join(format!("download_{}.part", id)))
+}
+
+/// 续传文件对应的校验信息文件
+fn validator_path(part: &Path) -> PathBuf {
+    part.with_extension("validator")
+}
+
+/// 删除续传文件及其校验信息
+async fn remove_partial(part: &Path) {
+    let _ = tokio::fs::remove_file(part).await;
+    let _ = tokio::fs::remove_file(validator_path(part)).await;
+}
+
+/// 响应中可用于 If-Range 的校验值：强 ETag，否则为 Last-Modified
+fn response_validator(response: &reqwest::Response) -> Option<String> {
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    // 弱 ETag 不能用于 If-Range
+    header(reqwest::header::ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(reqwest::header::LAST_MODIFIED))
+}
+
+/// 解析 `Content-Range: bytes <start>-<end>/<total>`，返回起始位置与总长度
+fn content_range(response: &reqwest::Response) -> Option<(u64, Option<u64>)> {
+    let value = response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?;
+    let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let start = range.split_once('-')?.0.trim().parse().ok()?;
+    Some((start, total.trim().parse().ok()))
+}
+
+struct Progress<'a> {
+    app: Option<&'a tauri::AppHandle>,
+    id: &'a str,
+    url: &'a str,
+    started: Instant,
+    /// 本次开始时已有的字节数（续传部分不计入速度）
+    resumed_from: u64,
+    last_emit: Option<Instant>,
+}
+
+impl Progress<'_> {
+    fn emit(&mut self, status: &str, bytes: u64, total: Option<u64>, error: Option<String>) {
+        let app = match self.app {
+            Some(app) => app,
+            None => return,
+        };
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let speed = if elapsed > 0.0 {
+            (bytes.saturating_sub(self.resumed_from) as f64 / elapsed) as u64
+        } else {
+            0
+        };
+        let _ = app.emit(
+            "download-progress",
+            DownloadProgress {
+                id: self.id,
+                url: self.url,
+                status,
+                bytes,
+                total,
+                speed,
+                error,
+            },
+        );
+        self.last_emit = Some(Instant::now());
+    }
+
+    fn tick(&mut self, bytes: u64, total: Option<u64>) {
+        let due = self
+            .last_emit
+            .is_none_or(|last| last.elapsed() >= Duration::from_millis(PROGRESS_INTERVAL_MS));
+        if due {
+            self.emit("downloading", bytes, total, None);
+        }
+    }
+}
+
+fn check_content_type(
+    response: &reqwest::Response,
+    options: &DownloadOptions,
+) -> Result<(), String> {
+    if options.accept.is_empty() {
+        return Ok(());
+    }
+    let content_type = match response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(content_type) => content_type.to_lowercase(),
+        // 未声明类型时交给调用方按内容识别
+        None => return Ok(()),
+    };
+    if options
+        .accept
+        .iter()
+        .any(|prefix| content_type.starts_with(prefix))
+    {
+        Ok(())
+    } else {
+        Err(format!("Unexpected content type: {}", content_type))
+    }
+}
+
+/// 单次下载尝试，返回文件总字节数
+async fn attempt(
+    client: &reqwest::Client,
+    url: &str,
+    part: &Path,
+    options: &DownloadOptions,
+    progress: &mut Progress<'_>,
+) -> Result<u64, AttemptError> {
+    let mut existing = tokio::fs::metadata(part)
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    let validator = tokio::fs::read_to_string(validator_path(part))
+        .await
+        .ok()
+        .filter(|v| !v.is_empty());
+
+    let mut request = client.get(url);
+    match validator {
+        Some(validator) if existing > 0 => {
+            request = request
+                .header(reqwest::header::RANGE, format!("bytes={}-", existing))
+                .header(reqwest::header::IF_RANGE, validator);
+        }
+        // 没有校验信息时无法确认远端文件未变化，从头下载
+        _ => existing = 0,
+    }
+    let response = request.send().await.map_err(|e| {
+        if e.is_connect() {
+            report_network_error();
+        }
+        AttemptError::Retry(format!("Request failed: {}", e))
+    })?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // 续传文件已失效，下次从头下载
+        remove_partial(part).await;
+        return Err(AttemptError::Retry("Range not satisfiable".to_string()));
+    }
+    if status.is_server_error()
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+    {
+        return Err(AttemptError::Retry(format!("HTTP {}", status)));
+    }
+    if !status.is_success() {
+        return Err(AttemptError::Fatal(format!("HTTP {}", status)));
+    }
+    check_content_type(&response, options).map_err(AttemptError::Fatal)?;
+
+    // 服务器不支持 Range 或远端文件已变化（If-Range 不匹配）时返回 200，从头写入
+    let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut total = None;
+    if resumed {
+        match content_range(&response) {
+            Some((start, range_total)) if start == existing && existing > 0 => total = range_total,
+            _ => {
+                remove_partial(part).await;
+                return Err(AttemptError::Retry(
+                    "Content-Range does not match the partial file".to_string(),
+                ));
+            }
+        }
+    } else {
+        // 记录新的校验信息，没有时删除旧的，之后不再续传
+        let validator_file = validator_path(part);
+        let saved = match response_validator(&response) {
+            Some(validator) => tokio::fs::write(&validator_file, validator).await,
+            None => match tokio::fs::remove_file(&validator_file).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            },
+        };
+        saved.map_err(|e| AttemptError::Fatal(format!("Failed to save download state: {}", e)))?;
+    }
+    let mut written = if resumed { existing } else { 0 };
+    let total = total.or_else(|| response.content_length().map(|len| len + written));
+    if total.is_some_and(|total| total > options.max_bytes) {
+        return Err(AttemptError::Fatal(format!(
+            "File is too large: {} bytes",
+            total.unwrap_or(0)
+        )));
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(part)
+        .await
+        .map_err(|e| AttemptError::Fatal(format!("Failed to open download file: {}", e)))?;
+
+    progress.resumed_from = written;
+    progress.emit("downloading", written, total, None);
+
+    let mut response = response;
+    loop {
+        let chunk = tokio::time::timeout(Duration::from_secs(READ_TIMEOUT_SECS), response.chunk())
+            .await
+            .map_err(|_| AttemptError::Retry("Read timed out".to_string()))?
+            .map_err(|e| AttemptError::Retry(format!("Failed to read response: {}", e)))?;
+
+        let chunk = match chunk {
+            Some(chunk) => chunk,
+            None => break,
+        };
+        written += chunk.len() as u64;
+        if written > options.max_bytes {
+            drop(file);
+            return Err(AttemptError::Fatal(format!(
+                "File exceeds the {} byte limit",
+                options.max_bytes
+            )));
+        }
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| AttemptError::Fatal(format!("Failed to write download file: {}", e)))?;
+        progress.tick(written, total);
+    }
+
+    file.flush()
+        .await
+        .map_err(|e| AttemptError::Fatal(format!("Failed to write download file: {}", e)))?;
+    if total.is_some_and(|total| written < total) {
+        return Err(AttemptError::Retry("Connection closed early".to_string()));
+    }
+
+    Ok(written)
+}
+
+/// 占用下载 ID，离开作用域时释放
+struct ActiveGuard(String);
+
+impl ActiveGuard {
+    fn acquire(id: &str) -> Result<Self, String> {
+        let mut active = ACTIVE
+            .lock()
+            .map_err(|_| "Download list is locked".to_string())?;
+        if !active
+            .get_or_insert_with(HashSet::new)
+            .insert(id.to_string())
+        {
+            return Err("The same URL is already being downloaded".to_string());
+        }
+        Ok(ActiveGuard(id.to_string()))
+    }
+}
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        if let Ok(mut active) = ACTIVE.lock() {
+            if let Some(active) = active.as_mut() {
+                active.remove(&self.0);
+            }
+        }
+    }
+}
+
+/// 下载到指定文件（排队、重试、续传），返回字节数
+pub async fn download_to_file(
+    app: Option<&tauri::AppHandle>,
+    url: &str,
+    dest: &Path,
+    options: &DownloadOptions,
+) -> Result<u64, String> {
+    let id = download_id(url);
+    let _active = ActiveGuard::acquire(&id)?;
+    let part = part_path(&id)?;
+    let client = http_client()?;
+
+    let mut progress = Progress {
+        app,
+        id: &id,
+        url,
+        started: Instant::now(),
+        resumed_from: 0,
+        last_emit: None,
+    };
+
+    progress.emit("queued", 0, None, None);
+    let _permit = QUEUE
+        .acquire()
+        .await
+        .map_err(|e| format!("Download queue closed: {}", e))?;
+    progress.started = Instant::now();
+
+    let mut tries = 0;
+    let result = loop {
+        match attempt(&client, url, &part, options, &mut progress).await {
+            Ok(bytes) => break Ok(bytes),
+            // 离线时不再重试
+            Err(AttemptError::Retry(e)) if tries < options.retries && is_online() => {
+                tries += 1;
+                // 指数退避：1s、2s、4s……
+                let delay = Duration::from_secs(1 << (tries - 1).min(5));
+                eprintln!(
+                    "Download {} failed ({}), retrying in {}s",
+                    url,
+                    e,
+                    delay.as_secs()
+                );
+                progress.emit("retrying", 0, None, Some(e));
+                tokio::time::sleep(delay).await;
+            }
+            Err(AttemptError::Retry(e)) => break Err(e),
+            // 无法完成的下载不保留续传文件
+            Err(AttemptError::Fatal(e)) => {
+                remove_partial(&part).await;
+                break Err(e);
+            }
+        }
+    };
+
+    let bytes = match result {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let e = format!("Failed to download {}: {}", url, e);
+            progress.emit("failed", 0, None, Some(e.clone()));
+            return Err(e);
+        }
+    };
+
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    std::fs::rename(&part, dest)
+        .or_else(|_| std::fs::copy(&part, dest).and_then(|_| std::fs::remove_file(&part)))
+        .map_err(|e| format!("Failed to save download: {}", e))?;
+    let _ = std::fs::remove_file(validator_path(&part));
+
+    progress.emit("done", bytes, Some(bytes), None);
+    Ok(bytes)
+}
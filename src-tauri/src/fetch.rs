@@ -1,59 +1,510 @@
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use tauri::Manager;
+use tokio::io::AsyncWriteExt;
+
+use crate::connectivity;
+use crate::cookie_jar;
+use crate::credentials::{has_secret_reference, resolve_secret_value, resolve_secrets};
+use crate::fetch_policy::{caller_for, check_request};
+use crate::fs_helper::get_appdata_dir;
+use crate::http_cache::{CacheEntry, CacheSlot};
+use crate::network::{http_client, http_client_without_redirects, pinned_client, request_timeout};
+
+/// 手动跟随重定向的最多次数
+const MAX_REDIRECTS: usize = 10;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FetchResponse {
     pub status: u16,
     pub headers: HashMap<String, String>,
+    /// 响应内容：text / json 为文本，base64 为编码后的内容，file 为保存的文件路径
     pub body: String,
+    /// response_type 为 json 时解析后的内容
+    #[serde(default)]
+    pub json: Option<serde_json::Value>,
+    /// 响应来自本地缓存
+    #[serde(default)]
+    pub from_cache: bool,
+    /// 网络不可用或服务器出错（5xx）时返回的过期缓存
+    #[serde(default)]
+    pub stale: bool,
 }
 
+/// multipart 表单字段：value（文本）、file_path（本地文件）、base64（二进制内容）三选一
 #[derive(Debug, Serialize, Deserialize)]
+pub struct MultipartField {
+    pub name: String,
+    pub value: Option<String>,
+    pub file_path: Option<String>,
+    pub base64: Option<String>,
+    /// 上传的文件名，默认取 file_path 的文件名
+    pub file_name: Option<String>,
+    pub content_type: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct FetchOptions {
     pub method: Option<String>,
     pub headers: Option<HashMap<String, String>>,
     pub body: Option<String>,
+    /// 二进制请求体（base64 编码）
+    pub body_base64: Option<String>,
+    /// 二进制请求体（字节数组）
+    pub body_bytes: Option<Vec<u8>>,
+    /// application/x-www-form-urlencoded 表单
+    pub form: Option<HashMap<String, String>>,
+    /// multipart/form-data 表单
+    pub multipart: Option<Vec<MultipartField>>,
+    /// 响应类型：text（默认）/ json / base64 / file
+    pub response_type: Option<String>,
+    /// response_type 为 file 时的保存路径，默认保存到 temp 目录
+    pub save_path: Option<String>,
+    /// 缓存有效期（秒），代替服务器的缓存策略；0 表示不使用缓存
+    #[serde(default)]
+    pub cache_ttl: Option<u64>,
+    /// Cookie 会话名称，指定时发送并保存该会话的 Cookie（不使用缓存）
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ResponseType {
+    Text,
+    Json,
+    Base64,
+    File,
+}
+
+impl ResponseType {
+    fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value.map(|v| v.to_lowercase()).as_deref() {
+            None | Some("text") => Ok(ResponseType::Text),
+            Some("json") => Ok(ResponseType::Json),
+            Some("base64") => Ok(ResponseType::Base64),
+            Some("file") => Ok(ResponseType::File),
+            Some(other) => Err(format!("Unsupported response type: {}", other)),
+        }
+    }
+}
+
+fn cached_response(entry: &CacheEntry, stale: bool, response_type: ResponseType) -> FetchResponse {
+    FetchResponse {
+        status: entry.status,
+        headers: entry.headers.clone(),
+        body: entry.body.clone(),
+        json: if response_type == ResponseType::Json {
+            serde_json::from_str(&entry.body).ok()
+        } else {
+            None
+        },
+        from_cache: true,
+        stale,
+    }
+}
+
+fn decode_base64(data: &str) -> Result<Vec<u8>, String> {
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| format!("Invalid base64 data: {}", e))
+}
+
+/// 构建 multipart 表单；`local_files` 为 false 时不能读取本地文件（壁纸发起的请求）
+fn build_multipart(fields: Vec<MultipartField>, local_files: bool) -> Result<reqwest::multipart::Form, String> {
+    let mut form = reqwest::multipart::Form::new();
+    for field in fields {
+        let (bytes, file_name) = match (field.value, field.file_path, field.base64) {
+            (Some(value), None, None) => {
+                form = form.text(field.name, value);
+                continue;
+            }
+            (None, Some(_), None) if !local_files => {
+                return Err(format!("Multipart field {} cannot read local files", field.name));
+            }
+            (None, Some(path), None) => {
+                let bytes = std::fs::read(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+                let name = std::path::Path::new(&path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string());
+                (bytes, field.file_name.or(name))
+            }
+            (None, None, Some(data)) => (decode_base64(&data)?, field.file_name),
+            _ => return Err(format!("Multipart field {} needs exactly one of value, file_path, base64", field.name)),
+        };
+
+        let mut part = reqwest::multipart::Part::bytes(bytes);
+        if let Some(file_name) = file_name {
+            part = part.file_name(file_name);
+        }
+        if let Some(content_type) = field.content_type {
+            part = part
+                .mime_str(&content_type)
+                .map_err(|e| format!("Invalid content type {}: {}", content_type, e))?;
+        }
+        form = form.part(field.name, part);
+    }
+    Ok(form)
+}
+
+/// 添加请求体（各种请求体只能指定一种）
+fn apply_body(
+    mut request_builder: reqwest::RequestBuilder,
+    opts: &mut FetchOptions,
+    local_files: bool,
+) -> Result<reqwest::RequestBuilder, String> {
+    let count = [
+        opts.body.is_some(),
+        opts.body_base64.is_some(),
+        opts.body_bytes.is_some(),
+        opts.form.is_some(),
+        opts.multipart.is_some(),
+    ]
+    .iter()
+    .filter(|set| **set)
+    .count();
+    if count > 1 {
+        return Err("Only one request body can be specified".to_string());
+    }
+
+    if let Some(body) = opts.body.take() {
+        request_builder = request_builder.body(body);
+    } else if let Some(data) = opts.body_base64.take() {
+        request_builder = request_builder.body(decode_base64(&data)?);
+    } else if let Some(bytes) = opts.body_bytes.take() {
+        request_builder = request_builder.body(bytes);
+    } else if let Some(form) = opts.form.take() {
+        request_builder = request_builder.form(&form);
+    } else if let Some(fields) = opts.multipart.take() {
+        request_builder = request_builder.multipart(build_multipart(fields, local_files)?);
+    }
+    Ok(request_builder)
+}
+
+/// 将响应内容流式写入文件，返回文件路径；`local_files` 为 false 时不能写入本地文件（壁纸发起的请求）
+async fn save_response(mut response: reqwest::Response, save_path: Option<String>, local_files: bool) -> Result<String, String> {
+    if !local_files {
+        return Err("Saving responses to local files is not allowed".to_string());
+    }
+    let path = match save_path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => {
+            let dir = get_appdata_dir()?.join("temp");
+            dir.join(format!("fetch_{}", chrono::Utc::now().timestamp_millis()))
+        }
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let mut file = tokio::fs::File::create(&path)
+        .await
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read response body: {}", e))?
+    {
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+    }
+    file.flush()
+        .await
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// 重定向后的请求头：按新主机重新替换凭据（不允许的凭据不发送）并附加会话的 Cookie，
+/// 跨主机时不再发送 Authorization 等请求头
+fn redirect_headers(
+    mut headers: reqwest::header::HeaderMap,
+    url: &reqwest::Url,
+    cross_host: bool,
+    raw_headers: &HashMap<String, String>,
+    session: Option<&str>,
+) -> reqwest::header::HeaderMap {
+    use reqwest::header::{HeaderName, HeaderValue};
+
+    headers.remove(reqwest::header::COOKIE);
+    if cross_host {
+        headers.remove(reqwest::header::AUTHORIZATION);
+        headers.remove(reqwest::header::PROXY_AUTHORIZATION);
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    for (name, raw) in raw_headers {
+        if !has_secret_reference(raw) {
+            continue;
+        }
+        let name = match HeaderName::from_bytes(name.as_bytes()) {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+        headers.remove(&name);
+        match resolve_secret_value(raw, host) {
+            Ok(value) => {
+                if let Ok(value) = HeaderValue::from_str(&value) {
+                    headers.insert(name, value);
+                }
+            }
+            Err(e) => eprintln!("Dropping header {} on redirect to {}: {}", name, host, e),
+        }
+    }
+
+    if let Some(session) = session {
+        match cookie_jar::cookie_header(session, url) {
+            Ok(Some(cookie)) => {
+                if let Ok(value) = HeaderValue::from_str(&cookie) {
+                    headers.insert(reqwest::header::COOKIE, value);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    headers
+}
+
+/// 手动跟随重定向：每一跳都保存会话的 Cookie，并按新主机重新检查凭据
+///
+/// 使用不跟随重定向的客户端；请求体无法复制（如 multipart）且需要重发时返回重定向响应本身。
+async fn send_following_redirects(
+    client: &reqwest::Client,
+    mut request: reqwest::Request,
+    raw_headers: &HashMap<String, String>,
+    session: Option<&str>,
+) -> Result<reqwest::Response, reqwest::Error> {
+    let mut redirects = 0;
+    loop {
+        let method = request.method().clone();
+        let headers = request.headers().clone();
+        let timeout = request.timeout().copied();
+        let had_body = request.body().is_some();
+        let body = request
+            .try_clone()
+            .and_then(|mut copy| copy.body_mut().take());
+
+        let response = client.execute(request).await?;
+        let status = response.status().as_u16();
+        if !matches!(status, 301 | 302 | 303 | 307 | 308) || redirects >= MAX_REDIRECTS {
+            return Ok(response);
+        }
+        let location = match response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|location| response.url().join(location).ok())
+        {
+            Some(location) if matches!(location.scheme(), "http" | "https") => location,
+            _ => return Ok(response),
+        };
+
+        if let Some(session) = session {
+            if let Err(e) =
+                cookie_jar::store_response_cookies(session, response.url(), response.headers())
+            {
+                eprintln!("{}", e);
+            }
+        }
+
+        // 301/302/303 改为不带请求体的 GET，307/308 原样重发
+        let keep_method = matches!(status, 307 | 308) || method == reqwest::Method::HEAD;
+        if keep_method && had_body && body.is_none() {
+            return Ok(response);
+        }
+        let cross_host = location.host_str() != response.url().host_str()
+            || location.port_or_known_default() != response.url().port_or_known_default();
+
+        let mut next = reqwest::Request::new(
+            if keep_method {
+                method
+            } else {
+                reqwest::Method::GET
+            },
+            location.clone(),
+        );
+        let mut headers = redirect_headers(headers, &location, cross_host, raw_headers, session);
+        if keep_method {
+            *next.body_mut() = body;
+        } else {
+            headers.remove(reqwest::header::CONTENT_TYPE);
+            headers.remove(reqwest::header::CONTENT_LENGTH);
+        }
+        *next.headers_mut() = headers;
+        *next.timeout_mut() = timeout;
+
+        request = next;
+        redirects += 1;
+    }
 }
 
 #[tauri::command]
-pub async fn fetch_request(url: String, options: Option<FetchOptions>) -> Result<FetchResponse, String> {
-    let client = reqwest::Client::new();
+pub async fn fetch_request(
+    webview: tauri::Webview,
+    url: String,
+    options: Option<FetchOptions>,
+) -> Result<FetchResponse, String> {
+    // 壁纸发起的请求受访问策略限制，且不能读写本地文件
+    let caller = caller_for(webview.label());
+    let local_files = caller.is_none();
     
     // 设置默认选项
-    let opts = options.unwrap_or(FetchOptions {
-        method: Some("GET".to_string()),
-        headers: None,
-        body: None,
-    });
+    let mut opts = options.unwrap_or_default();
+    let response_type = ResponseType::parse(opts.response_type.as_deref())?;
     
     // 构建请求
-    let method = opts.method.unwrap_or("GET".to_string());
+    let method = opts.method.take().unwrap_or("GET".to_string());
+    
+    let uses_secret = opts
+        .headers
+        .as_ref()
+        .is_some_and(|headers| headers.values().any(|value| has_secret_reference(value)));
+    
+    let mut pinned = Vec::new();
+    if let Some(caller) = &caller {
+        let local_access = response_type == ResponseType::File
+            || opts.save_path.is_some()
+            || opts
+                .multipart
+                .as_ref()
+                .is_some_and(|fields| fields.iter().any(|field| field.file_path.is_some()));
+        let privileged = if local_access {
+            Some("local file access")
+        } else if opts.session.is_some() {
+            Some("cookie session")
+        } else if uses_secret {
+            Some("credential")
+        } else {
+            None
+        };
+        pinned = check_request(
+            webview.app_handle(),
+            caller,
+            &method,
+            &url,
+            opts.headers.as_ref(),
+            privileged,
+        )
+        .await?;
+    }
+    
+    let parsed_url = reqwest::Url::parse(&url).map_err(|e| format!("Invalid URL: {}", e))?;
+    
+    // 使用会话或凭据时手动跟随重定向，每一跳重新检查凭据并保存 Cookie
+    let follow_manually = caller.is_none() && (opts.session.is_some() || uses_secret);
+    
+    // 共享客户端，复用连接池；受限请求不跟随重定向，域名固定解析到检查过的地址
+    let client = match (&caller, parsed_url.host_str()) {
+        (None, _) if follow_manually => http_client_without_redirects()?,
+        (None, _) => http_client()?,
+        (Some(_), Some(host)) if !pinned.is_empty() => pinned_client(host, &pinned)?,
+        (Some(_), _) => http_client_without_redirects()?,
+    };
+    
+    // 替换请求头中引用的凭据（保留原始内容，重定向到其他主机时重新替换）
+    let raw_headers = opts.headers.clone().unwrap_or_default();
+    if let Some(headers) = opts.headers.as_mut() {
+        resolve_secrets(headers, parsed_url.host_str().unwrap_or_default())?;
+    }
+    
+    // 只缓存文本类型的 GET 请求，仍然新鲜的缓存直接返回
+    // （缓存键不含会话与凭据，使用它们的请求不缓存，避免返回给没有凭据的调用方）
+    let cacheable = matches!(response_type, ResponseType::Text | ResponseType::Json);
+    let cache_slot = if method.eq_ignore_ascii_case("GET")
+        && cacheable
+        && opts.cache_ttl != Some(0)
+        && opts.session.is_none()
+        && !uses_secret
+    {
+        Some(CacheSlot::new(&url, opts.headers.as_ref()))
+    } else {
+        None
+    };
+    let cached = match &cache_slot {
+        Some(slot) => slot.load().await,
+        None => None,
+    };
+    let now = chrono::Utc::now().timestamp();
+    if let Some(entry) = &cached {
+        if entry.is_fresh(opts.cache_ttl, now) {
+            return Ok(cached_response(entry, false, response_type));
+        }
+    }
+    
     let mut request_builder = match method.to_uppercase().as_str() {
         "GET" => client.get(&url),
         "POST" => client.post(&url),
         "PUT" => client.put(&url),
         "DELETE" => client.delete(&url),
         "PATCH" => client.patch(&url),
+        "HEAD" => client.head(&url),
+        "OPTIONS" => client.request(reqwest::Method::OPTIONS, &url),
         _ => return Err("Unsupported HTTP method".to_string()),
     };
     
+    request_builder = request_builder.timeout(request_timeout());
+    
     // 添加请求头
-    if let Some(headers) = opts.headers {
+    if let Some(headers) = opts.headers.take() {
         for (key, value) in headers {
             request_builder = request_builder.header(&key, &value);
         }
     }
     
-    // 添加请求体
-    if let Some(body) = opts.body {
-        request_builder = request_builder.body(body);
+    // 发送会话中的 Cookie
+    if let Some(session) = &opts.session {
+        if let Some(cookie) = cookie_jar::cookie_header(session, &parsed_url)? {
+            request_builder = request_builder.header(reqwest::header::COOKIE, cookie);
+        }
     }
     
+    // 缓存过期时发送条件请求
+    if let Some(entry) = &cached {
+        if let Some(etag) = &entry.etag {
+            request_builder = request_builder.header("If-None-Match", etag);
+        }
+        if let Some(last_modified) = &entry.last_modified {
+            request_builder = request_builder.header("If-Modified-Since", last_modified);
+        }
+    }
+    
+    // 添加请求体
+    request_builder = apply_body(request_builder, &mut opts, local_files)?;
+    
     // 发送请求
-    match request_builder.send().await {
+    let result = if follow_manually {
+        match request_builder.build() {
+            Ok(request) => {
+                send_following_redirects(&client, request, &raw_headers, opts.session.as_deref())
+                    .await
+            }
+            Err(e) => Err(e),
+        }
+    } else {
+        request_builder.send().await
+    };
+    match result {
         Ok(response) => {
             let status = response.status().as_u16();
             
+            // 保存响应中的 Cookie，失败不影响本次请求
+            if let Some(session) = &opts.session {
+                if let Err(e) = cookie_jar::store_response_cookies(session, response.url(), response.headers()) {
+                    eprintln!("{}", e);
+                }
+            }
+            
+            // 服务器出错时返回过期的缓存内容
+            if response.status().is_server_error() {
+                if let Some(entry) = &cached {
+                    eprintln!("Server returned {}, serving stale cache for {}", status, url);
+                    return Ok(cached_response(entry, true, response_type));
+                }
+            }
+            
             // 获取响应头
             let mut headers = HashMap::new();
             for (key, value) in response.headers() {
@@ -62,23 +513,89 @@ pub async fn fetch_request(url: String, options: Option<FetchOptions>) -> Result
                 }
             }
             
-            // 获取响应体
-            match response.text().await {
-                Ok(body) => Ok(FetchResponse {
-                    status,
-                    headers,
-                    body,
-                }),
-                Err(e) => Err(format!("Failed to read response body: {}", e)),
+            // 内容未变化，刷新缓存有效期后返回缓存内容
+            if status == 304 {
+                if let (Some(slot), Some(mut entry)) = (&cache_slot, cached) {
+                    entry.revalidated(&headers, now);
+                    slot.save(&entry).await;
+                    return Ok(cached_response(&entry, false, response_type));
+                }
+            }
+            
+            // 按响应类型读取响应体
+            let body = match response_type {
+                ResponseType::Text | ResponseType::Json => response
+                    .text()
+                    .await
+                    .map_err(|e| format!("Failed to read response body: {}", e))?,
+                ResponseType::Base64 => {
+                    let bytes = response
+                        .bytes()
+                        .await
+                        .map_err(|e| format!("Failed to read response body: {}", e))?;
+                    base64::engine::general_purpose::STANDARD.encode(bytes)
+                }
+                ResponseType::File => save_response(response, opts.save_path.take(), local_files).await?,
+            };
+            
+            let json = if response_type == ResponseType::Json {
+                match serde_json::from_str(&body) {
+                    Ok(json) => Some(json),
+                    // 错误响应可能不是 JSON，保留文本由调用方处理
+                    Err(_) if !(200..300).contains(&status) => None,
+                    Err(e) => return Err(format!("Failed to parse JSON: {}", e)),
+                }
+            } else {
+                None
+            };
+            
+            if let Some(slot) = &cache_slot {
+                if status == 200 {
+                    let (entry, store) = CacheEntry::from_response(&url, status, headers.clone(), body.clone(), now);
+                    // 指定 cache_ttl 时忽略服务器的 no-store
+                    if store || opts.cache_ttl.is_some() {
+                        slot.save(&entry).await;
+                    } else {
+                        slot.remove().await;
+                    }
+                }
+            }
+            
+            Ok(FetchResponse {
+                status,
+                headers,
+                body,
+                json,
+                from_cache: false,
+                stale: false,
+            })
+        }
+        Err(e) => {
+            if e.is_connect() || e.is_timeout() {
+                connectivity::report_network_error();
+            }
+            // 离线时返回过期的缓存内容
+            if let Some(entry) = &cached {
+                eprintln!("Request failed, serving stale cache for {}: {}", url, e);
+                return Ok(cached_response(entry, true, response_type));
             }
+            Err(format!("Request failed: {}", e))
         }
-        Err(e) => Err(format!("Request failed: {}", e)),
     }
 }
 
 #[tauri::command]
-pub async fn fetch_json(url: String, options: Option<FetchOptions>) -> Result<serde_json::Value, String> {
-    let response = fetch_request(url, options).await?;
+pub async fn fetch_json(
+    webview: tauri::Webview,
+    url: String,
+    options: Option<FetchOptions>,
+) -> Result<serde_json::Value, String> {
+    // 按文本读取后解析
+    let options = options.map(|opts| FetchOptions {
+        response_type: None,
+        ..opts
+    });
+    let response = fetch_request(webview, url, options).await?;
     
     match serde_json::from_str(&response.body) {
         Ok(json) => Ok(json),
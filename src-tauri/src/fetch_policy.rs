@@ -0,0 +1,460 @@
+// 请求访问策略：限制 HTML 壁纸通过 fetch_request 发出的请求
+//
+// HTML 壁纸运行在 background 窗口中，其请求按壁纸文件夹内 manifest.json 的 network 字段检查：
+// "network": {
+//   "allow": ["api.example.com", "*.example.org"],  // 允许的主机，为空时允许所有公网主机
+//   "deny": ["ads.example.com"],                     // 拒绝的主机，优先于 allow
+//   "rate_limit": 30                                 // 每个主机每分钟最多请求次数
+// }
+//
+// 主机规则支持完整主机名、`*.` 通配子域名与 IP 网段（如 `192.168.1.0/24`）。
+// 本机与局域网地址（解析后的地址）默认拒绝，只有 allow 中精确列出的局域网规则（局域网 IP、网段，
+// 或 localhost、`*.local` 等局域网主机名，不含通配）匹配且用户已确认时才允许。
+// rate_limit 超过 MAX_RATE_LIMIT 同样需要用户确认，未确认时按上限计算。
+// 确认记录在 fetch_approvals.json 中（壁纸 → 确认时的策略），策略改变后需要重新确认，
+// 可在导入 `.opaper` 包时确认，或调用 approve_wallpaper_network。
+// 检查时解析的地址会固定到本次请求使用的客户端，防止检查后 DNS 改变指向（DNS rebinding）；
+// 配置了代理时由代理解析域名。
+// 受限请求不跟随重定向，不能读写本地文件、使用 Cookie 会话或凭据，也不能设置 Host、Cookie 等请求头。
+// 被拦截的请求记录在 fetch_blocked.json 中，并发出 `fetch-blocked` 事件。
+
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::config::read_config_value;
+use crate::fs_helper::{read_file, write_file};
+
+/// 拦截记录（相对 appdata 目录）
+const BLOCKED_FILE: &str = "fetch_blocked.json";
+/// 用户确认过的策略（相对 appdata 目录）
+const APPROVALS_FILE: &str = "fetch_approvals.json";
+/// 未经确认时每个主机每分钟的请求上限
+pub const MAX_RATE_LIMIT: u32 = 120;
+const MAX_BLOCKED_ENTRIES: usize = 200;
+const DEFAULT_RATE_LIMIT: u32 = 30;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+/// 壁纸所在的窗口
+const WALLPAPER_WEBVIEW: &str = "background";
+/// 受限请求不能设置的请求头（前缀匹配）
+const FORBIDDEN_HEADERS: [&str; 6] = ["host", "cookie", "proxy-", "sec-", "origin", "referer"];
+
+/// 各调用方、各主机最近一分钟内的请求时间
+static REQUESTS: Mutex<Option<HashMap<String, VecDeque<Instant>>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FetchPolicy {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub rate_limit: u32,
+}
+
+impl Default for FetchPolicy {
+    fn default() -> Self {
+        FetchPolicy {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            rate_limit: DEFAULT_RATE_LIMIT,
+        }
+    }
+}
+
+/// 受访问策略限制的调用方
+#[derive(Debug, Clone)]
+pub struct FetchCaller {
+    /// 如 `wallpaper:<文件夹名>`
+    pub id: String,
+    pub policy: FetchPolicy,
+    /// 用户已确认策略中需要确认的权限（局域网访问、较高的请求频率）
+    pub approved: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedRequest {
+    pub time: String,
+    pub caller: String,
+    pub method: String,
+    pub url: String,
+    pub reason: String,
+}
+
+/// 读取壁纸文件夹中 manifest.json 的 network 字段
+pub fn read_wallpaper_policy(folder: &Path) -> Option<FetchPolicy> {
+    let content = std::fs::read_to_string(folder.join("manifest.json")).ok()?;
+    let manifest: serde_json::Value = serde_json::from_str(&content).ok()?;
+    serde_json::from_value(manifest.get("network")?.clone()).ok()
+}
+
+fn load_approvals() -> HashMap<String, FetchPolicy> {
+    read_file(APPROVALS_FILE.to_string())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 壁纸文件夹对应的调用方 id
+fn caller_id(folder: &Path) -> String {
+    let name = folder
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    format!("wallpaper:{}", name)
+}
+
+/// 精确的局域网规则：局域网 IP 或网段，以及 localhost、`*.local` 等局域网主机名（不含通配）
+fn is_private_rule(rule: &str) -> bool {
+    let rule = rule.trim().to_lowercase();
+    if rule.contains('*') {
+        return false;
+    }
+    if let Some((base, _)) = rule.split_once('/') {
+        return base.parse::<IpAddr>().is_ok_and(|ip| is_private(&ip));
+    }
+    let host = rule.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return is_private(&ip);
+    }
+    host == "localhost"
+        || !host.contains('.')
+        || [
+            ".localhost",
+            ".local",
+            ".lan",
+            ".home",
+            ".internal",
+            ".home.arpa",
+        ]
+        .iter()
+        .any(|suffix| host.ends_with(suffix))
+}
+
+/// 策略中需要用户确认的权限说明，为空表示无需确认
+pub fn elevated_permissions(policy: &FetchPolicy) -> Vec<String> {
+    let mut permissions: Vec<String> = policy
+        .allow
+        .iter()
+        .filter(|rule| is_private_rule(rule))
+        .map(|rule| format!("local network access to {}", rule.trim()))
+        .collect();
+    if policy.rate_limit > MAX_RATE_LIMIT {
+        permissions.push(format!("up to {} requests per minute", policy.rate_limit));
+    }
+    permissions
+}
+
+/// 记录或撤销用户对壁纸文件夹中访问策略的确认
+pub fn set_wallpaper_approval(folder: &Path, approved: bool) -> Result<(), String> {
+    let mut approvals = load_approvals();
+    let id = caller_id(folder);
+    match read_wallpaper_policy(folder).filter(|_| approved) {
+        Some(policy) => {
+            approvals.insert(id, policy);
+        }
+        None => {
+            approvals.remove(&id);
+        }
+    }
+    let content = serde_json::to_string(&approvals)
+        .map_err(|e| format!("Failed to serialize approvals: {}", e))?;
+    write_file(APPROVALS_FILE.to_string(), content)?;
+    Ok(())
+}
+
+/// 按发起请求的窗口确定调用方，应用自身的窗口不受限制
+pub fn caller_for(webview_label: &str) -> Option<FetchCaller> {
+    if webview_label != WALLPAPER_WEBVIEW {
+        return None;
+    }
+
+    let html_path = read_config_value()
+        .get("htmlPath")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .unwrap_or_default();
+    let folder = Path::new(&html_path).parent().unwrap_or(Path::new(""));
+    let id = caller_id(folder);
+    let policy = read_wallpaper_policy(folder).unwrap_or_default();
+    // 确认后策略被修改时需要重新确认
+    let approved = load_approvals().get(&id) == Some(&policy);
+
+    Some(FetchCaller {
+        id,
+        policy,
+        approved,
+    })
+}
+
+/// 本机、局域网、链路本地等非公网地址
+fn is_private(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // 100.64.0.0/10 运营商级 NAT
+                || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_private(&IpAddr::V4(v4));
+            }
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                // fc00::/7 唯一本地地址
+                || (first & 0xfe00) == 0xfc00
+                // fe80::/10 链路本地地址
+                || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+fn in_network(ip: &IpAddr, network: &str) -> bool {
+    let (base, prefix) = match network.split_once('/') {
+        Some((base, prefix)) => (base, prefix),
+        None => return false,
+    };
+    let (base, prefix) = match (base.parse::<IpAddr>(), prefix.parse::<u32>()) {
+        (Ok(base), Ok(prefix)) => (base, prefix),
+        _ => return false,
+    };
+    match (ip, base) {
+        (IpAddr::V4(ip), IpAddr::V4(base)) if prefix <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(*ip) & mask == u32::from(base) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(base)) if prefix <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(*ip) & mask == u128::from(base) & mask
+        }
+        _ => false,
+    }
+}
+
+/// 主机是否匹配规则
+pub fn host_matches(host: &str, ip: Option<&IpAddr>, rule: &str) -> bool {
+    let rule = rule.trim().to_lowercase();
+    if rule == "*" {
+        return true;
+    }
+    if let Some(suffix) = rule.strip_prefix("*.") {
+        return host.ends_with(&format!(".{}", suffix));
+    }
+    if rule.contains('/') {
+        return ip.is_some_and(|ip| in_network(ip, &rule));
+    }
+    host == rule.trim_start_matches('[').trim_end_matches(']')
+}
+
+/// 主机的全部地址，域名解析失败时为空（由请求本身报错）
+async fn resolve(host: &str, port: u16) -> Vec<IpAddr> {
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return vec![ip];
+    }
+    tokio::net::lookup_host((host, port))
+        .await
+        .map(|addrs| addrs.map(|addr| addr.ip()).collect())
+        .unwrap_or_default()
+}
+
+/// 记录一次请求，超过每分钟上限时返回 false
+fn take_rate(caller: &str, host: &str, limit: u32) -> bool {
+    let mut requests = match REQUESTS.lock() {
+        Ok(requests) => requests,
+        Err(_) => return false,
+    };
+    let requests = requests.get_or_insert_with(HashMap::new);
+    let now = Instant::now();
+    requests.retain(|_, times| {
+        times.retain(|time| now.duration_since(*time) < RATE_WINDOW);
+        !times.is_empty()
+    });
+
+    let times = requests.entry(format!("{}|{}", caller, host)).or_default();
+    if times.len() >= limit.max(1) as usize {
+        return false;
+    }
+    times.push_back(now);
+    true
+}
+
+fn load_blocked() -> Vec<BlockedRequest> {
+    read_file(BLOCKED_FILE.to_string())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 记录被拦截的请求并通知前端，返回错误信息
+fn block(
+    app: &tauri::AppHandle,
+    caller: &FetchCaller,
+    method: &str,
+    url: &str,
+    reason: String,
+) -> String {
+    eprintln!("Blocked {} {} from {}: {}", method, url, caller.id, reason);
+    let entry = BlockedRequest {
+        time: chrono::Local::now().to_rfc3339(),
+        caller: caller.id.clone(),
+        method: method.to_uppercase(),
+        url: url.to_string(),
+        reason: reason.clone(),
+    };
+
+    let mut blocked = load_blocked();
+    blocked.push(entry.clone());
+    if blocked.len() > MAX_BLOCKED_ENTRIES {
+        blocked.drain(..blocked.len() - MAX_BLOCKED_ENTRIES);
+    }
+    match serde_json::to_string(&blocked) {
+        Ok(content) => {
+            if let Err(e) = write_file(BLOCKED_FILE.to_string(), content) {
+                eprintln!("Failed to record blocked request: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Failed to record blocked request: {}", e),
+    }
+    let _ = app.emit("fetch-blocked", entry);
+
+    format!("Request blocked: {}", reason)
+}
+
+/// 检查受限调用方的请求；`privileged` 为请求用到的受限功能（本地文件、会话、凭据）
+///
+/// 返回检查过的地址，请求域名时应固定解析到这些地址（见 network::pinned_client），
+/// 主机为 IP 时为空。
+pub async fn check_request(
+    app: &tauri::AppHandle,
+    caller: &FetchCaller,
+    method: &str,
+    url: &str,
+    headers: Option<&HashMap<String, String>>,
+    privileged: Option<&str>,
+) -> Result<Vec<SocketAddr>, String> {
+    let deny = |reason: String| Err(block(app, caller, method, url, reason));
+
+    if let Some(feature) = privileged {
+        return deny(format!("{} is not allowed", feature));
+    }
+    if let Some(header) = headers.and_then(|headers| {
+        headers.keys().find(|key| {
+            let key = key.to_lowercase();
+            FORBIDDEN_HEADERS
+                .iter()
+                .any(|forbidden| key.starts_with(forbidden))
+        })
+    }) {
+        return deny(format!("header {} is not allowed", header));
+    }
+
+    let parsed = match reqwest::Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(e) => return deny(format!("invalid URL: {}", e)),
+    };
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return deny(format!("scheme {} is not allowed", parsed.scheme()));
+    }
+    let host = match parsed.host_str() {
+        Some(host) => host.to_lowercase(),
+        None => return deny("missing host".to_string()),
+    };
+    let host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let literal_ip = host.parse::<IpAddr>().ok();
+    let policy = &caller.policy;
+
+    if policy
+        .deny
+        .iter()
+        .any(|rule| host_matches(&host, literal_ip.as_ref(), rule))
+    {
+        return deny(format!("host {} is denied", host));
+    }
+    if !policy.allow.is_empty()
+        && !policy
+            .allow
+            .iter()
+            .any(|rule| host_matches(&host, literal_ip.as_ref(), rule))
+    {
+        return deny(format!("host {} is not in the allowlist", host));
+    }
+
+    // 非公网地址必须由精确的局域网规则允许且经用户确认；按解析后的地址判断，防止域名指向本机
+    let private_allowed = caller.approved
+        && policy
+            .allow
+            .iter()
+            .any(|rule| is_private_rule(rule) && host_matches(&host, literal_ip.as_ref(), rule));
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    let addresses = resolve(&host, port).await;
+    if addresses.is_empty() {
+        return deny(format!("failed to resolve host {}", host));
+    }
+    if !private_allowed {
+        if let Some(ip) = addresses.iter().find(|ip| is_private(ip)) {
+            return deny(format!("private address {} is not allowed", ip));
+        }
+    }
+
+    // 未经确认的频率上限不超过 MAX_RATE_LIMIT
+    let rate_limit = if caller.approved {
+        policy.rate_limit
+    } else {
+        policy.rate_limit.min(MAX_RATE_LIMIT)
+    };
+    if !take_rate(&caller.id, &host, rate_limit) {
+        return deny(format!(
+            "rate limit of {} requests per minute exceeded for {}",
+            rate_limit, host
+        ));
+    }
+
+    Ok(if literal_ip.is_some() {
+        Vec::new()
+    } else {
+        addresses
+            .into_iter()
+            .map(|ip| SocketAddr::new(ip, port))
+            .collect()
+    })
+}
+
+/// 确认或撤销 HTML 壁纸访问策略中需要确认的权限（局域网访问、较高的请求频率）
+#[tauri::command]
+pub fn approve_wallpaper_network(path: String, approved: bool) -> Result<Vec<String>, String> {
+    let mut folder = std::path::PathBuf::from(&path);
+    if folder.is_file() {
+        folder.pop();
+    }
+    let policy = read_wallpaper_policy(&folder)
+        .ok_or_else(|| format!("No network policy in {}", folder.display()))?;
+    set_wallpaper_approval(&folder, approved)?;
+    Ok(elevated_permissions(&policy))
+}
+
+/// 列出被拦截的请求（最近的在前）
+#[tauri::command]
+pub fn list_blocked_requests() -> Vec<BlockedRequest> {
+    let mut blocked = load_blocked();
+    blocked.reverse();
+    blocked
+}
+
+/// 清空拦截记录
+#[tauri::command]
+pub fn clear_blocked_requests() -> Result<(), String> {
+    write_file(BLOCKED_FILE.to_string(), "[]".to_string())?;
+    Ok(())
+}
@@ -1,12 +1,12 @@
 use std::fs;
 use std::process::Command;
 
+use crate::data_dir::try_write_guard;
+
 // 获取 appdata 目录下的 oPaper 路径
 pub fn get_appdata_dir() -> Result<std::path::PathBuf, String> {
-    let app_data =
-        dirs::data_dir().ok_or_else(|| "Failed to get app data directory".to_string())?;
-
-    let app_dir = app_data.join("oPaper");
+    // 支持命令行、环境变量、便携模式等方式自定义位置
+    let app_dir = crate::data_dir::data_dir()?;
 
     // 确保目录存在
     fs::create_dir_all(&app_dir)
@@ -56,6 +56,8 @@ pub fn open_folder(path: String) -> Result<String, String> {
 
 #[tauri::command]
 pub fn write_file(path: String, content: String) -> Result<String, String> {
+    // 迁移数据目录期间拒绝写入，避免写入即将删除的旧目录
+    let _writing = try_write_guard()?;
     // 获取 appdata 目录下的 oPaper 路径
     let base_dir = get_appdata_dir()?;
 
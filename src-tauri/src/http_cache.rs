@@ -0,0 +1,269 @@
+// HTTP 缓存模块：为 fetch_request 的 GET 请求提供磁盘缓存
+//
+// 缓存保存在 cache/http/<key>.json，随存储管理的缓存配额一起淘汰。
+// 新鲜度按 Cache-Control（no-store / no-cache / max-age）与 Expires 计算，
+// 过期后带 If-None-Match / If-Modified-Since 发送条件请求；
+// 调用方指定 cache_ttl 时以其代替服务器给出的有效期。
+//
+// 缓存键由 URL、Authorization 与响应 Vary 中列出的请求头共同决定（使用会话或 {{secret:…}} 凭据的请求不缓存）：
+// 每个 URL 的 Vary 记录在 cache/http/<URL 哈希>.vary.json 中，查找时先读取它再计算键。
+// 磁盘读写在阻塞线程池中执行，不占用异步运行时。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::data_dir::try_write_guard;
+use crate::fs_helper::get_appdata_dir;
+use crate::storage::touch;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+    /// 保存（或最近一次验证）的时间，Unix 秒
+    pub stored_at: i64,
+    /// 服务器给出的过期时间，为空表示需要验证后才能使用
+    pub expires_at: Option<i64>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// 响应头中的缓存策略
+pub struct CachePolicy {
+    pub store: bool,
+    pub expires_at: Option<i64>,
+}
+
+fn cache_dir() -> Result<PathBuf, String> {
+    let dir = get_appdata_dir()?.join("cache").join("http");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create cache directory: {}", e))?;
+    Ok(dir)
+}
+
+/// 一个请求对应的缓存位置
+#[derive(Clone)]
+pub struct CacheSlot {
+    url: String,
+    /// 请求头，名称为小写
+    headers: HashMap<String, String>,
+}
+
+fn hex_digest(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 记录 URL 对应 Vary 的文件
+fn vary_path(dir: &Path, url: &str) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(url.as_bytes());
+    dir.join(format!("{}.vary.json", hex_digest(hasher)))
+}
+
+/// 响应头中的 Vary 列表（小写）
+fn vary_of(headers: &HashMap<String, String>) -> Vec<String> {
+    let mut names: Vec<String> = header(headers, "vary")
+        .map(|vary| {
+            vary.split(',')
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.dedup();
+    names
+}
+
+impl CacheSlot {
+    pub fn new(url: &str, headers: Option<&HashMap<String, String>>) -> Self {
+        CacheSlot {
+            url: url.to_string(),
+            headers: headers
+                .map(|headers| {
+                    headers
+                        .iter()
+                        .map(|(key, value)| (key.to_lowercase(), value.clone()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// 缓存键：URL、Authorization（不同凭据的响应互不共用）与 Vary 中的请求头
+    fn key(&self, vary: &[String]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.url.as_bytes());
+        let names = std::iter::once("authorization").chain(vary.iter().map(String::as_str));
+        for name in names {
+            hasher.update(b"\n");
+            hasher.update(name.as_bytes());
+            hasher.update(b":");
+            if let Some(value) = self.headers.get(name) {
+                hasher.update(value.as_bytes());
+            }
+        }
+        hex_digest(hasher)
+    }
+
+    fn entry_path(&self, dir: &Path) -> PathBuf {
+        let vary: Vec<String> = fs::read_to_string(vary_path(dir, &self.url))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        dir.join(format!("{}.json", self.key(&vary)))
+    }
+
+    fn load_blocking(&self) -> Option<CacheEntry> {
+        let path = self.entry_path(&cache_dir().ok()?);
+        let content = fs::read_to_string(&path).ok()?;
+        let entry = serde_json::from_str(&content).ok()?;
+        // 命中时更新修改时间，作为缓存淘汰依据
+        touch(&path);
+        Some(entry)
+    }
+
+    fn save_blocking(&self, entry: &CacheEntry) -> Result<(), String> {
+        let _writing = try_write_guard()?;
+        let dir = cache_dir()?;
+        let vary = vary_of(&entry.headers);
+        // Vary: * 表示响应无法按请求头区分，不缓存
+        if vary.iter().any(|name| name == "*") {
+            self.remove_blocking();
+            return Ok(());
+        }
+
+        let content = serde_json::to_string(&vary)
+            .map_err(|e| format!("Failed to serialize cache entry: {}", e))?;
+        fs::write(vary_path(&dir, &self.url), content)
+            .map_err(|e| format!("Failed to write cache entry: {}", e))?;
+        let content = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize cache entry: {}", e))?;
+        fs::write(dir.join(format!("{}.json", self.key(&vary))), content)
+            .map_err(|e| format!("Failed to write cache entry: {}", e))
+    }
+
+    fn remove_blocking(&self) {
+        if let Ok(dir) = cache_dir() {
+            let _ = fs::remove_file(self.entry_path(&dir));
+        }
+    }
+
+    pub async fn load(&self) -> Option<CacheEntry> {
+        let slot = self.clone();
+        tokio::task::spawn_blocking(move || slot.load_blocking())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    pub async fn save(&self, entry: &CacheEntry) {
+        let (slot, entry) = (self.clone(), entry.clone());
+        let result = tokio::task::spawn_blocking(move || slot.save_blocking(&entry))
+            .await
+            .map_err(|e| format!("Failed to write cache entry: {}", e))
+            .and_then(|result| result);
+        if let Err(e) = result {
+            eprintln!("HTTP cache: {}", e);
+        }
+    }
+
+    pub async fn remove(&self) {
+        let slot = self.clone();
+        let _ = tokio::task::spawn_blocking(move || slot.remove_blocking()).await;
+    }
+}
+
+fn header<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// 按响应头计算缓存策略
+pub fn policy(headers: &HashMap<String, String>, now: i64) -> CachePolicy {
+    let mut store = true;
+    let mut no_cache = false;
+    let mut max_age: Option<i64> = None;
+
+    if let Some(cache_control) = header(headers, "cache-control") {
+        for directive in cache_control.split(',') {
+            let directive = directive.trim().to_lowercase();
+            if directive == "no-store" {
+                store = false;
+            } else if directive == "no-cache" {
+                no_cache = true;
+            } else if let Some(value) = directive.strip_prefix("max-age=") {
+                max_age = value.trim_matches('"').parse::<i64>().ok();
+            }
+        }
+    }
+
+    let age = header(headers, "age")
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .unwrap_or(0);
+
+    let expires_at = if no_cache {
+        None
+    } else if let Some(max_age) = max_age {
+        Some(now + (max_age - age).max(0))
+    } else {
+        header(headers, "expires")
+            .and_then(|v| chrono::DateTime::parse_from_rfc2822(v).ok())
+            .map(|date| date.timestamp())
+    };
+
+    CachePolicy { store, expires_at }
+}
+
+impl CacheEntry {
+    /// 是否可以不经验证直接使用；`ttl` 为调用方指定的有效期（秒）
+    pub fn is_fresh(&self, ttl: Option<u64>, now: i64) -> bool {
+        match ttl {
+            Some(ttl) => now < self.stored_at + ttl as i64,
+            None => self.expires_at.is_some_and(|expires_at| now < expires_at),
+        }
+    }
+
+    pub fn from_response(
+        url: &str,
+        status: u16,
+        headers: HashMap<String, String>,
+        body: String,
+        now: i64,
+    ) -> (Self, bool) {
+        let policy = policy(&headers, now);
+        let entry = CacheEntry {
+            url: url.to_string(),
+            status,
+            etag: header(&headers, "etag").map(str::to_string),
+            last_modified: header(&headers, "last-modified").map(str::to_string),
+            headers,
+            body,
+            stored_at: now,
+            expires_at: policy.expires_at,
+        };
+        (entry, policy.store)
+    }
+
+    /// 304 响应后刷新有效期
+    pub fn revalidated(&mut self, headers: &HashMap<String, String>, now: i64) {
+        let policy = policy(headers, now);
+        self.stored_at = now;
+        self.expires_at = policy.expires_at;
+        if let Some(etag) = header(headers, "etag") {
+            self.etag = Some(etag.to_string());
+        }
+    }
+}
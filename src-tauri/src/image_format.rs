@@ -0,0 +1,326 @@
+// 图片格式识别与转码模块
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::fs_helper::get_appdata_dir;
+use crate::storage::touch;
+
+/// 临时文件序号，并发生成同一缓存文件时各自写入不同的临时文件
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// 静态壁纸库支持的图片格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Gif,
+    WebP,
+    Avif,
+    Bmp,
+    Tiff,
+    Heic,
+}
+
+impl ImageFormat {
+    /// 保存文件时使用的扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Gif => "gif",
+            ImageFormat::WebP => "webp",
+            ImageFormat::Avif => "avif",
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::Tiff => "tiff",
+            ImageFormat::Heic => "heic",
+        }
+    }
+
+    /// 当前平台的桌面壁纸接口能否直接使用该格式
+    pub fn is_desktop_native(&self) -> bool {
+        #[cfg(target_os = "windows")]
+        {
+            // 旧版本 Windows 只接受 BMP / JPEG，PNG 自 Windows 8 起可用
+            matches!(
+                self,
+                ImageFormat::Jpeg | ImageFormat::Bmp | ImageFormat::Png
+            )
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            matches!(
+                self,
+                ImageFormat::Jpeg
+                    | ImageFormat::Png
+                    | ImageFormat::Gif
+                    | ImageFormat::Bmp
+                    | ImageFormat::Tiff
+                    | ImageFormat::Heic
+            )
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            // gdk-pixbuf（GNOME）与 imlib2（feh）默认都支持的格式
+            matches!(
+                self,
+                ImageFormat::Jpeg
+                    | ImageFormat::Png
+                    | ImageFormat::Gif
+                    | ImageFormat::Bmp
+                    | ImageFormat::Tiff
+            )
+        }
+    }
+
+    fn to_image_crate(self) -> Option<image::ImageFormat> {
+        match self {
+            ImageFormat::Jpeg => Some(image::ImageFormat::Jpeg),
+            ImageFormat::Png => Some(image::ImageFormat::Png),
+            ImageFormat::Gif => Some(image::ImageFormat::Gif),
+            ImageFormat::WebP => Some(image::ImageFormat::WebP),
+            ImageFormat::Bmp => Some(image::ImageFormat::Bmp),
+            ImageFormat::Tiff => Some(image::ImageFormat::Tiff),
+            // AVIF / HEIC 需要系统解码器
+            ImageFormat::Avif | ImageFormat::Heic => None,
+        }
+    }
+}
+
+/// 根据文件头（magic bytes）识别图片格式
+pub fn detect_format(data: &[u8]) -> Option<ImageFormat> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some(ImageFormat::Jpeg);
+    }
+    if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        return Some(ImageFormat::Png);
+    }
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        return Some(ImageFormat::Gif);
+    }
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return Some(ImageFormat::WebP);
+    }
+    if data.starts_with(b"BM") && data.len() >= 14 {
+        return Some(ImageFormat::Bmp);
+    }
+    if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        return Some(ImageFormat::Tiff);
+    }
+
+    // ISO BMFF：第 4~8 字节为 ftyp，随后是主品牌与兼容品牌列表
+    if data.len() >= 12 && &data[4..8] == b"ftyp" {
+        let box_size = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        let end = box_size.clamp(12, data.len());
+        let brands: Vec<&[u8]> = std::iter::once(&data[8..12])
+            .chain(data[16.min(end)..end].chunks_exact(4))
+            .collect();
+
+        if brands.iter().any(|b| *b == b"avif" || *b == b"avis") {
+            return Some(ImageFormat::Avif);
+        }
+        if brands.iter().any(|b| {
+            matches!(
+                *b,
+                b"heic" | b"heix" | b"heim" | b"heis" | b"hevc" | b"hevx" | b"mif1" | b"msf1"
+            )
+        }) {
+            return Some(ImageFormat::Heic);
+        }
+    }
+
+    None
+}
+
+/// 读取文件头识别图片格式
+pub fn detect_file_format(path: &Path) -> Option<ImageFormat> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut header = [0u8; 64];
+    let mut read = 0;
+    // 部分文件系统单次 read 可能返回不足，循环读满文件头
+    while read < header.len() {
+        match file.read(&mut header[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(_) => return None,
+        }
+    }
+    detect_format(&header[..read])
+}
+
+/// 确保图片可以被当前桌面后端直接使用
+///
+/// 原生支持的格式直接返回原路径，否则转码为 JPEG 存入 `cache/converted`，
+/// 以源文件路径、大小和修改时间作为缓存键，重复设置时直接复用。
+pub fn prepare_for_desktop(path: &str) -> Result<String, String> {
+    let source = PathBuf::from(path);
+    let format =
+        detect_file_format(&source).ok_or_else(|| format!("Unsupported image format: {}", path))?;
+
+    if format.is_desktop_native() {
+        return Ok(path.to_string());
+    }
+
+    let cache_dir = get_appdata_dir()?.join("cache").join("converted");
+    fs::create_dir_all(&cache_dir)
+        .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+
+    let target = cache_dir.join(format!("{}.jpg", file_cache_key(&source, &[])?));
+    if target.exists() {
+        touch(&target);
+        return Ok(target.to_string_lossy().to_string());
+    }
+
+    transcode_to_cache(&source, &target, format.to_image_crate())?;
+
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// 返回可被 image 库解码的图片路径
+///
+/// AVIF / HEIC 先借助系统工具转码为 JPEG（与 `prepare_for_desktop` 共用缓存），
+/// 其他格式直接返回原路径。
+pub fn decodable_path(path: &str) -> Result<String, String> {
+    let source = PathBuf::from(path);
+    let format =
+        detect_file_format(&source).ok_or_else(|| format!("Unsupported image format: {}", path))?;
+
+    if format.to_image_crate().is_some() {
+        return Ok(path.to_string());
+    }
+
+    let cache_dir = get_appdata_dir()?.join("cache").join("converted");
+    fs::create_dir_all(&cache_dir)
+        .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+
+    let target = cache_dir.join(format!("{}.jpg", file_cache_key(&source, &[])?));
+    if target.exists() {
+        touch(&target);
+    } else {
+        transcode_to_cache(&source, &target, None)?;
+    }
+
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// 本地文件的缓存键：由路径、大小、修改时间与 `extra`（如处理参数）计算，源文件变化后自动失效
+pub fn file_cache_key(path: &Path, extra: &[u8]) -> Result<String, String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("Failed to read metadata: {}", e))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(path.to_string_lossy().as_bytes());
+    hasher.update(metadata.len().to_le_bytes());
+    hasher.update(modified.to_le_bytes());
+    hasher.update(extra);
+    let digest = hasher.finalize();
+
+    Ok(digest[..12].iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 缓存文件的临时写入路径：与目标同目录并保留扩展名（系统工具据此判断输出格式），
+/// 写完后再重命名为目标，读取方不会看到写了一半的文件
+pub fn temp_sibling(target: &Path) -> PathBuf {
+    let stem = target
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = target
+        .extension()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    target.with_file_name(format!(
+        "{}.{}_{}.part.{}",
+        stem,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed),
+        extension
+    ))
+}
+
+/// 转码到临时文件后重命名为缓存目标；`format` 为空时借助系统工具
+fn transcode_to_cache(
+    source: &Path,
+    target: &Path,
+    format: Option<image::ImageFormat>,
+) -> Result<(), String> {
+    let temp = temp_sibling(target);
+    let result = match format {
+        Some(image_format) => transcode_with_image_crate(source, &temp, image_format),
+        None => transcode_with_system_tool(source, &temp),
+    }
+    .and_then(|_| {
+        fs::rename(&temp, target).map_err(|e| format!("Failed to save converted image: {}", e))
+    });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn transcode_with_image_crate(
+    source: &Path,
+    target: &Path,
+    format: image::ImageFormat,
+) -> Result<(), String> {
+    let data = fs::read(source).map_err(|e| format!("Failed to read image: {}", e))?;
+    let image = image::load_from_memory_with_format(&data, format)
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+
+    // JPEG 不支持透明通道
+    image
+        .to_rgb8()
+        .save_with_format(target, image::ImageFormat::Jpeg)
+        .map_err(|e| format!("Failed to encode image: {}", e))
+}
+
+/// AVIF / HEIC 借助系统工具转码
+fn transcode_with_system_tool(source: &Path, target: &Path) -> Result<(), String> {
+    let source_str = source.to_string_lossy().to_string();
+    let target_str = target.to_string_lossy().to_string();
+
+    #[cfg(target_os = "macos")]
+    let candidates: Vec<(&str, Vec<&str>)> = vec![(
+        "sips",
+        vec!["-s", "format", "jpeg", &source_str, "--out", &target_str],
+    )];
+
+    #[cfg(target_os = "linux")]
+    let candidates: Vec<(&str, Vec<&str>)> = vec![
+        ("heif-convert", vec![&source_str, &target_str]),
+        ("magick", vec![&source_str, &target_str]),
+        ("convert", vec![&source_str, &target_str]),
+    ];
+
+    #[cfg(target_os = "windows")]
+    let candidates: Vec<(&str, Vec<&str>)> = vec![("magick", vec![&source_str, &target_str])];
+
+    for (program, args) in candidates {
+        let output = Command::new(program).args(&args).output();
+        if let Ok(output) = output {
+            if output.status.success() && target.exists() {
+                return Ok(());
+            }
+        }
+    }
+
+    Err(format!(
+        "Failed to convert {}: please install libheif (heif-convert) or ImageMagick",
+        source_str
+    ))
+}
@@ -0,0 +1,496 @@
+// 静态壁纸图片处理管线：旋转、裁剪、滤镜、文字叠加
+
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use image::{imageops::FilterType, DynamicImage, ImageDecoder, ImageReader, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+
+use crate::fs_helper::get_appdata_dir;
+use crate::image_format::{decodable_path, file_cache_key, temp_sibling};
+use crate::storage::touch;
+
+/// 智能裁剪时用于计算显著性的缩略图边长
+const SALIENCY_SIZE: u32 = 256;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CropMode {
+    /// 居中裁剪
+    #[default]
+    Center,
+    /// 按画面细节（梯度能量）选择保留区域
+    Smart,
+}
+
+/// 文字叠加
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextOverlay {
+    /// 文字内容，支持 `{date}`、`{time}`、`{weekday}` 占位符，`\n` 换行
+    pub content: String,
+    /// top-left / top / top-right / left / center / right / bottom-left / bottom / bottom-right
+    pub position: String,
+    /// 字号（像素，相对输出图片）
+    pub size: f32,
+    /// 颜色，`#rrggbb`
+    pub color: String,
+    /// 字体文件路径，为空时使用系统字体
+    pub font_path: Option<String>,
+}
+
+impl Default for TextOverlay {
+    fn default() -> Self {
+        TextOverlay {
+            content: String::new(),
+            position: "bottom-right".to_string(),
+            size: 48.0,
+            color: "#ffffff".to_string(),
+            font_path: None,
+        }
+    }
+}
+
+/// 处理管线参数，对应配置中的 `image_pipeline`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineOptions {
+    pub enabled: bool,
+    /// 按 EXIF 方向自动旋转
+    pub auto_rotate: bool,
+    /// 缩放并裁剪到目标分辨率
+    pub resize: bool,
+    /// 目标分辨率，为空时使用主显示器分辨率
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub crop: CropMode,
+    /// 高斯模糊半径（sigma），0 表示不模糊
+    pub blur: f32,
+    /// 压暗程度 0~1
+    pub dim: f32,
+    pub grayscale: bool,
+    /// 暗角强度 0~1
+    pub vignette: f32,
+    /// 色调，`#rrggbb`
+    pub tint: Option<String>,
+    /// 色调强度 0~1
+    pub tint_strength: f32,
+    pub text: Option<TextOverlay>,
+}
+
+impl Default for PipelineOptions {
+    fn default() -> Self {
+        PipelineOptions {
+            enabled: false,
+            auto_rotate: true,
+            resize: false,
+            width: None,
+            height: None,
+            crop: CropMode::Center,
+            blur: 0.0,
+            dim: 0.0,
+            grayscale: false,
+            vignette: 0.0,
+            tint: None,
+            tint_strength: 0.3,
+            text: None,
+        }
+    }
+}
+
+/// 按管线参数处理图片，返回缓存中处理后的 JPEG 路径
+///
+/// `screen_size` 为目标显示器的物理分辨率，参数中未指定宽高时使用。
+/// 以源文件、修改时间和最终参数的哈希作为缓存键，重复设置时直接返回缓存。
+pub fn process_image(
+    path: &str,
+    options: &PipelineOptions,
+    screen_size: Option<(u32, u32)>,
+) -> Result<String, String> {
+    let source = PathBuf::from(path);
+    if !source.exists() {
+        return Err(format!("File not found: {}", path));
+    }
+
+    // 解析出最终参数：目标分辨率、文字占位符
+    let mut resolved = options.clone();
+    if resolved.resize {
+        let (screen_width, screen_height) = screen_size.unwrap_or((1920, 1080));
+        resolved.width = Some(resolved.width.unwrap_or(screen_width).max(1));
+        resolved.height = Some(resolved.height.unwrap_or(screen_height).max(1));
+    }
+    if let Some(text) = resolved.text.as_mut() {
+        text.content = render_text(&text.content);
+    }
+
+    let cache_dir = get_appdata_dir()?.join("cache").join("processed");
+    fs::create_dir_all(&cache_dir)
+        .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+
+    let target = cache_dir.join(format!("{}.jpg", cache_key(&source, &resolved)?));
+    if target.exists() {
+        touch(&target);
+        return Ok(target.to_string_lossy().to_string());
+    }
+
+    // AVIF / HEIC 先转码为 image 库可解码的格式
+    let decodable = decodable_path(path)?;
+    let mut image = load_image(Path::new(&decodable), resolved.auto_rotate)?;
+
+    if let (true, Some(width), Some(height)) = (resolved.resize, resolved.width, resolved.height) {
+        image = resize_and_crop(&image, width, height, resolved.crop);
+    }
+    if resolved.blur > 0.0 {
+        image = image.fast_blur(resolved.blur);
+    }
+    if resolved.grayscale {
+        image = image.grayscale();
+    }
+
+    let mut rgb = image.to_rgb8();
+
+    if let Some(tint) = resolved.tint.as_deref().and_then(parse_color) {
+        apply_tint(&mut rgb, tint, resolved.tint_strength.clamp(0.0, 1.0));
+    }
+    if resolved.dim > 0.0 {
+        apply_dim(&mut rgb, resolved.dim.clamp(0.0, 1.0));
+    }
+    if resolved.vignette > 0.0 {
+        apply_vignette(&mut rgb, resolved.vignette.clamp(0.0, 1.0));
+    }
+    if let Some(text) = resolved.text.as_ref() {
+        if !text.content.trim().is_empty() {
+            draw_text(&mut rgb, text)?;
+        }
+    }
+
+    // 先写入临时文件再重命名，中途失败不会留下被当作缓存复用的半成品
+    let temp = temp_sibling(&target);
+    let saved = rgb
+        .save_with_format(&temp, image::ImageFormat::Jpeg)
+        .map_err(|e| format!("Failed to save processed image: {}", e))
+        .and_then(|_| {
+            fs::rename(&temp, &target).map_err(|e| format!("Failed to save processed image: {}", e))
+        });
+    if let Err(e) = saved {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+
+    Ok(target.to_string_lossy().to_string())
+}
+
+fn cache_key(source: &Path, options: &PipelineOptions) -> Result<String, String> {
+    let params = serde_json::to_string(options)
+        .map_err(|e| format!("Failed to serialize pipeline options: {}", e))?;
+    file_cache_key(source, params.as_bytes())
+}
+
+/// 读取图片，可选按 EXIF 方向旋转
+pub fn load_image(source: &Path, auto_rotate: bool) -> Result<DynamicImage, String> {
+    let data = fs::read(source).map_err(|e| format!("Failed to read image: {}", e))?;
+    let reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read image: {}", e))?;
+    let mut decoder = reader
+        .into_decoder()
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+
+    let orientation = if auto_rotate {
+        decoder.orientation().ok()
+    } else {
+        None
+    };
+
+    let mut image = DynamicImage::from_decoder(decoder)
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    if let Some(orientation) = orientation {
+        image.apply_orientation(orientation);
+    }
+
+    Ok(image)
+}
+
+/// 等比缩放并裁剪到目标分辨率（铺满，不留黑边）
+fn resize_and_crop(image: &DynamicImage, width: u32, height: u32, mode: CropMode) -> DynamicImage {
+    let (src_width, src_height) = (image.width(), image.height());
+    let target_ratio = width as f64 / height as f64;
+
+    // 在原图上计算与目标同比例的最大裁剪区域
+    let (crop_width, crop_height) = if src_width as f64 / src_height as f64 > target_ratio {
+        (
+            ((src_height as f64 * target_ratio).round() as u32).clamp(1, src_width),
+            src_height,
+        )
+    } else {
+        (
+            src_width,
+            ((src_width as f64 / target_ratio).round() as u32).clamp(1, src_height),
+        )
+    };
+
+    let (x, y) = match mode {
+        CropMode::Center => ((src_width - crop_width) / 2, (src_height - crop_height) / 2),
+        CropMode::Smart => smart_crop_offset(image, crop_width, crop_height),
+    };
+
+    image
+        .crop_imm(x, y, crop_width, crop_height)
+        .resize_exact(width, height, FilterType::Lanczos3)
+}
+
+/// 在缩略图上计算梯度能量，沿可移动方向找出能量最高的窗口
+fn smart_crop_offset(image: &DynamicImage, crop_width: u32, crop_height: u32) -> (u32, u32) {
+    let (src_width, src_height) = (image.width(), image.height());
+    let small = image
+        .resize(SALIENCY_SIZE, SALIENCY_SIZE, FilterType::Triangle)
+        .to_luma8();
+    let (small_width, small_height) = small.dimensions();
+    if small_width < 3 || small_height < 3 {
+        return ((src_width - crop_width) / 2, (src_height - crop_height) / 2);
+    }
+
+    // 每行 / 每列的能量和
+    let mut column_energy = vec![0f64; small_width as usize];
+    let mut row_energy = vec![0f64; small_height as usize];
+    for y in 1..small_height - 1 {
+        for x in 1..small_width - 1 {
+            let dx = small.get_pixel(x + 1, y)[0] as f64 - small.get_pixel(x - 1, y)[0] as f64;
+            let dy = small.get_pixel(x, y + 1)[0] as f64 - small.get_pixel(x, y - 1)[0] as f64;
+            let energy = dx.abs() + dy.abs();
+            column_energy[x as usize] += energy;
+            row_energy[y as usize] += energy;
+        }
+    }
+
+    let best_window = |energy: &[f64], window: usize| -> usize {
+        let window = window.clamp(1, energy.len());
+        let mut sum: f64 = energy[..window].iter().sum();
+        let (mut best_sum, mut best_start) = (sum, 0);
+        for start in 1..=energy.len() - window {
+            sum += energy[start + window - 1] - energy[start - 1];
+            if sum > best_sum {
+                best_sum = sum;
+                best_start = start;
+            }
+        }
+        best_start
+    };
+
+    let x = if crop_width < src_width {
+        let window = (crop_width as f64 * small_width as f64 / src_width as f64).round() as usize;
+        let start = best_window(&column_energy, window);
+        ((start as f64 * src_width as f64 / small_width as f64).round() as u32)
+            .min(src_width - crop_width)
+    } else {
+        0
+    };
+    let y = if crop_height < src_height {
+        let window =
+            (crop_height as f64 * small_height as f64 / src_height as f64).round() as usize;
+        let start = best_window(&row_energy, window);
+        ((start as f64 * src_height as f64 / small_height as f64).round() as u32)
+            .min(src_height - crop_height)
+    } else {
+        0
+    };
+
+    (x, y)
+}
+
+fn apply_dim(image: &mut RgbImage, amount: f32) {
+    let factor = 1.0 - amount;
+    for pixel in image.pixels_mut() {
+        for channel in pixel.0.iter_mut() {
+            *channel = (*channel as f32 * factor) as u8;
+        }
+    }
+}
+
+fn apply_tint(image: &mut RgbImage, color: Rgb<u8>, strength: f32) {
+    for pixel in image.pixels_mut() {
+        let [r, g, b] = pixel.0;
+        let luma = (0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32) / 255.0;
+        for (channel, tint) in pixel.0.iter_mut().zip(color.0) {
+            let tinted = tint as f32 * luma;
+            *channel = (*channel as f32 * (1.0 - strength) + tinted * strength) as u8;
+        }
+    }
+}
+
+fn apply_vignette(image: &mut RgbImage, strength: f32) {
+    let (width, height) = image.dimensions();
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    let max_distance = (cx * cx + cy * cy).sqrt();
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+        let distance = (dx * dx + dy * dy).sqrt() / max_distance;
+        // 中心区域不受影响，向边缘平滑变暗
+        let t = ((distance - 0.4) / 0.6).clamp(0.0, 1.0);
+        let factor = 1.0 - strength * t * t * (3.0 - 2.0 * t);
+        for channel in pixel.0.iter_mut() {
+            *channel = (*channel as f32 * factor) as u8;
+        }
+    }
+}
+
+fn parse_color(value: &str) -> Option<Rgb<u8>> {
+    let hex = value.trim().trim_start_matches('#');
+    // 先确认全是 ASCII 十六进制字符，按字节切分才不会落在多字节字符中间
+    if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Rgb([channel(0)?, channel(2)?, channel(4)?]))
+}
+
+/// 替换文字中的日期时间占位符
+fn render_text(content: &str) -> String {
+    let now = chrono::Local::now();
+    content
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H:%M").to_string())
+        .replace("{weekday}", &now.format("%A").to_string())
+}
+
+fn load_font(font_path: Option<&str>) -> Result<FontVec, String> {
+    let candidates: Vec<PathBuf> = match font_path {
+        Some(path) if !path.is_empty() => vec![PathBuf::from(path)],
+        _ => default_font_paths(),
+    };
+
+    for path in candidates {
+        if let Ok(data) = fs::read(&path) {
+            if let Ok(font) = FontVec::try_from_vec_and_index(data, 0) {
+                return Ok(font);
+            }
+        }
+    }
+
+    Err("No usable font found, please set text.font_path".to_string())
+}
+
+/// 各平台常见的系统字体（优先支持中文的字体）
+fn default_font_paths() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let paths = vec![
+        "C:\\Windows\\Fonts\\msyh.ttc",
+        "C:\\Windows\\Fonts\\simhei.ttf",
+        "C:\\Windows\\Fonts\\arial.ttf",
+    ];
+
+    #[cfg(target_os = "macos")]
+    let paths = vec![
+        "/System/Library/Fonts/PingFang.ttc",
+        "/System/Library/Fonts/STHeiti Medium.ttc",
+        "/System/Library/Fonts/Helvetica.ttc",
+    ];
+
+    #[cfg(target_os = "linux")]
+    let paths = vec![
+        "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+        "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+        "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+        "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    ];
+
+    paths.into_iter().map(PathBuf::from).collect()
+}
+
+/// 在图片上绘制文字（带阴影，保证在亮色背景上可读）
+fn draw_text(image: &mut RgbImage, overlay: &TextOverlay) -> Result<(), String> {
+    let font = load_font(overlay.font_path.as_deref())?;
+    let scale = PxScale::from(overlay.size.max(1.0));
+    let scaled = font.as_scaled(scale);
+    let color = parse_color(&overlay.color).unwrap_or(Rgb([255, 255, 255]));
+
+    let line_height = scaled.height() + scaled.line_gap();
+    let lines: Vec<&str> = overlay.content.lines().collect();
+    let line_widths: Vec<f32> = lines
+        .iter()
+        .map(|line| {
+            let mut width = 0.0;
+            let mut previous = None;
+            for c in line.chars() {
+                let id = scaled.glyph_id(c);
+                if let Some(previous) = previous {
+                    width += scaled.kern(previous, id);
+                }
+                width += scaled.h_advance(id);
+                previous = Some(id);
+            }
+            width
+        })
+        .collect();
+
+    let block_width = line_widths.iter().cloned().fold(0.0, f32::max);
+    let block_height = line_height * lines.len() as f32;
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    let margin = overlay.size;
+
+    let position = overlay.position.as_str();
+    let left = if position.ends_with("left") || position == "left" {
+        margin
+    } else if position.ends_with("right") || position == "right" {
+        width - block_width - margin
+    } else {
+        (width - block_width) / 2.0
+    };
+    let top = if position.starts_with("top") {
+        margin
+    } else if position.starts_with("bottom") {
+        height - block_height - margin
+    } else {
+        (height - block_height) / 2.0
+    };
+
+    let shadow_offset = (overlay.size / 24.0).max(1.0);
+    for (offset, fill, opacity) in [(shadow_offset, Rgb([0, 0, 0]), 0.5), (0.0, color, 1.0)] {
+        for (index, line) in lines.iter().enumerate() {
+            // 多行文字按整体位置对齐
+            let line_left = if position.ends_with("right") || position == "right" {
+                left + block_width - line_widths[index]
+            } else if position.ends_with("left") || position == "left" {
+                left
+            } else {
+                left + (block_width - line_widths[index]) / 2.0
+            };
+            let baseline = top + scaled.ascent() + line_height * index as f32;
+
+            let mut caret = line_left + offset;
+            let mut previous = None;
+            for c in line.chars() {
+                let id = scaled.glyph_id(c);
+                if let Some(previous) = previous {
+                    caret += scaled.kern(previous, id);
+                }
+                let glyph = id.with_scale_and_position(scale, point(caret, baseline + offset));
+                caret += scaled.h_advance(id);
+                previous = Some(id);
+
+                if let Some(outlined) = font.outline_glyph(glyph) {
+                    let bounds = outlined.px_bounds();
+                    outlined.draw(|gx, gy, coverage| {
+                        let x = bounds.min.x as i32 + gx as i32;
+                        let y = bounds.min.y as i32 + gy as i32;
+                        if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+                            return;
+                        }
+                        let alpha = coverage * opacity;
+                        let pixel = image.get_pixel_mut(x as u32, y as u32);
+                        for (channel, value) in pixel.0.iter_mut().zip(fill.0) {
+                            *channel = (*channel as f32 * (1.0 - alpha) + value as f32 * alpha)
+                                .round() as u8;
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,335 @@
+// 批量导入模块：将整个文件夹中的图片导入静态壁纸库
+//
+// 导入在后台执行，通过 `import-progress` 事件报告进度，完成（或取消）后发送 `import-finished`。
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::data_dir::background_write_guard_blocking;
+use crate::dedup::{content_hash, place_file};
+use crate::fs_helper::get_appdata_dir;
+use crate::image_format::detect_file_format;
+use crate::library::{load_library, sync_library, update_library_items};
+use crate::linked_folders::is_linked_path;
+
+/// 正在执行的导入任务及其取消标记
+static JOBS: Mutex<Option<HashMap<String, Arc<AtomicBool>>>> = Mutex::new(None);
+static JOB_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ImportOptions {
+    /// 使用硬链接代替复制（同一分区内不占用额外空间）
+    pub hard_link: bool,
+    /// 包含以 `.` 开头的隐藏文件和文件夹
+    pub include_hidden: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ImportProgress {
+    job_id: String,
+    done: usize,
+    total: usize,
+    current: String,
+    imported: usize,
+    duplicates: usize,
+    failed: usize,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummary {
+    pub job_id: String,
+    pub total: usize,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub failed: usize,
+    pub cancelled: bool,
+    /// 导入失败的文件及原因
+    pub errors: Vec<(String, String)>,
+}
+
+/// 遍历文件夹时的状态
+struct ImageWalk<'a> {
+    recursive: bool,
+    options: &'a ImportOptions,
+    skip: Option<&'a Path>,
+    cancelled: Option<&'a AtomicBool>,
+    /// 已进入的文件夹（规范化路径），符号链接成环时不会重复进入
+    visited: HashSet<PathBuf>,
+    files: Vec<PathBuf>,
+}
+
+impl ImageWalk<'_> {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+            .is_some_and(|cancelled| cancelled.load(Ordering::Relaxed))
+    }
+
+    fn walk(&mut self, dir: &Path) {
+        let canonical = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        if !self.visited.insert(canonical) {
+            return;
+        }
+
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("Failed to read directory {}: {}", dir.display(), e);
+                return;
+            }
+        };
+
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+        paths.sort();
+
+        for path in paths {
+            if self.is_cancelled() {
+                return;
+            }
+
+            let hidden = path
+                .file_name()
+                .map(|name| name.to_string_lossy().starts_with('.'))
+                .unwrap_or(false);
+            if hidden && !self.options.include_hidden {
+                continue;
+            }
+
+            if path.is_dir() {
+                // 不重复导入壁纸库自身
+                if self.recursive && !self.skip.is_some_and(|skip| path.starts_with(skip)) {
+                    self.walk(&path);
+                }
+            } else if detect_file_format(&path).is_some() {
+                self.files.push(path);
+            }
+        }
+    }
+}
+
+/// 收集文件夹中可识别的图片，`skip` 目录不会被递归进入，`cancelled` 置位后提前结束
+pub fn collect_images(
+    dir: &Path,
+    recursive: bool,
+    options: &ImportOptions,
+    skip: Option<&Path>,
+    cancelled: Option<&AtomicBool>,
+) -> Vec<PathBuf> {
+    let mut walk = ImageWalk {
+        recursive,
+        options,
+        skip,
+        cancelled,
+        visited: HashSet::new(),
+        files: Vec::new(),
+    };
+    walk.walk(dir);
+    walk.files
+}
+
+fn register_job(job_id: &str) -> Arc<AtomicBool> {
+    let flag = Arc::new(AtomicBool::new(false));
+    if let Ok(mut jobs) = JOBS.lock() {
+        jobs.get_or_insert_with(HashMap::new)
+            .insert(job_id.to_string(), flag.clone());
+    }
+    flag
+}
+
+fn finish_job(job_id: &str) {
+    if let Ok(mut jobs) = JOBS.lock() {
+        if let Some(jobs) = jobs.as_mut() {
+            jobs.remove(job_id);
+        }
+    }
+}
+
+fn run_import(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    files: Vec<PathBuf>,
+    options: &ImportOptions,
+    cancelled: &AtomicBool,
+) -> ImportSummary {
+    let total = files.len();
+    let mut summary = ImportSummary {
+        job_id: job_id.to_string(),
+        total,
+        imported: 0,
+        duplicates: 0,
+        failed: 0,
+        cancelled: false,
+        errors: Vec::new(),
+    };
+
+    // 库中已有内容的哈希，导入过程中同步追加，避免同一批次内的重复
+    // （关联文件夹中的文件不属于库本身，不计入）
+    let mut known: HashSet<String> = match load_library() {
+        Ok(index) => index
+            .items
+            .into_iter()
+            .filter(|item| !is_linked_path(&item.path))
+            .filter_map(|item| item.content_hash)
+            .collect(),
+        Err(e) => {
+            eprintln!("Failed to load library index: {}", e);
+            HashSet::new()
+        }
+    };
+    // 导入的文件名（数据目录可能在导入期间迁移，最后按当前目录匹配）
+    let mut imported_names: HashSet<String> = HashSet::new();
+
+    for (done, file) in files.iter().enumerate() {
+        if cancelled.load(Ordering::Relaxed) {
+            summary.cancelled = true;
+            break;
+        }
+
+        let current = file.to_string_lossy().to_string();
+        // 每个文件单独持有写入锁并重新解析目标目录，迁移数据目录只需等待当前文件完成
+        let result = {
+            let _writing = background_write_guard_blocking();
+            content_hash(file).and_then(|hash| {
+                if known.contains(&hash) {
+                    return Ok(None);
+                }
+                let (dest, _) = place_file(file, &static_dir()?, options.hard_link)?;
+                known.insert(hash);
+                Ok(Some(dest))
+            })
+        };
+
+        let error = match result {
+            Ok(Some(dest)) => {
+                summary.imported += 1;
+                if let Some(name) = dest.file_name() {
+                    imported_names.insert(name.to_string_lossy().to_string());
+                }
+                None
+            }
+            Ok(None) => {
+                summary.duplicates += 1;
+                None
+            }
+            Err(e) => {
+                summary.failed += 1;
+                summary.errors.push((current.clone(), e.clone()));
+                Some(e)
+            }
+        };
+
+        let _ = app.emit(
+            "import-progress",
+            ImportProgress {
+                job_id: job_id.to_string(),
+                done: done + 1,
+                total,
+                current,
+                imported: summary.imported,
+                duplicates: summary.duplicates,
+                failed: summary.failed,
+                error,
+            },
+        );
+    }
+
+    // 一次性刷新索引并标记来源
+    if !imported_names.is_empty() {
+        let result = static_dir().and_then(|dest_dir| {
+            sync_library()?;
+            update_library_items(|items| {
+                for item in items.iter_mut() {
+                    let path = Path::new(&item.path);
+                    let imported = path.parent() == Some(dest_dir.as_path())
+                        && path
+                            .file_name()
+                            .is_some_and(|name| imported_names.contains(&*name.to_string_lossy()));
+                    if imported {
+                        item.source = "import".to_string();
+                    }
+                }
+            })
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to register imported wallpapers: {}", e);
+        }
+    }
+
+    summary
+}
+
+/// 静态壁纸目录（不存在时创建），应在持有写入锁时解析
+fn static_dir() -> Result<PathBuf, String> {
+    let dir = get_appdata_dir()?.join("wallpaper_static");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create resource directory: {}", e))?;
+    Ok(dir)
+}
+
+/// 后台导入文件夹中的图片，立即返回任务 ID
+#[tauri::command]
+pub fn import_folder(
+    app: tauri::AppHandle,
+    path: String,
+    recursive: Option<bool>,
+    options: Option<ImportOptions>,
+) -> Result<String, String> {
+    let source_dir = PathBuf::from(&path);
+    if !source_dir.is_dir() {
+        return Err(format!("Folder not found: {}", path));
+    }
+
+    // 仅用于扫描时跳过静态壁纸目录本身，导入时按文件重新解析
+    let dest_dir = get_appdata_dir()?.join("wallpaper_static");
+
+    let job_id = format!(
+        "import-{}-{}",
+        chrono::Utc::now().timestamp_millis(),
+        JOB_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let cancelled = register_job(&job_id);
+    let options = options.unwrap_or_default();
+    let recursive = recursive.unwrap_or(true);
+
+    let task_job_id = job_id.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let files = collect_images(
+            &source_dir,
+            recursive,
+            &options,
+            Some(&dest_dir),
+            Some(&cancelled),
+        );
+        println!(
+            "Importing {} images from {}",
+            files.len(),
+            source_dir.display()
+        );
+
+        let summary = run_import(&app, &task_job_id, files, &options, &cancelled);
+        finish_job(&task_job_id);
+        let _ = app.emit("import-finished", summary);
+    });
+
+    Ok(job_id)
+}
+
+/// 取消导入任务，已导入的文件保留
+#[tauri::command]
+pub fn import_cancel(job_id: String) -> Result<(), String> {
+    let jobs = JOBS
+        .lock()
+        .map_err(|_| "Import jobs are locked".to_string())?;
+    let flag = jobs
+        .as_ref()
+        .and_then(|jobs| jobs.get(&job_id))
+        .ok_or_else(|| format!("Import job not found: {}", job_id))?;
+    flag.store(true, Ordering::Relaxed);
+    Ok(())
+}
@@ -1,39 +1,117 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+mod archive;
 mod config;
+mod connectivity;
+mod cookie_jar;
+mod credentials;
+mod data_dir;
+mod dedup;
+mod download;
 mod fetch;
+mod fetch_policy;
 pub mod fs_helper;
+mod http_cache;
+mod image_format;
+mod image_pipeline;
+mod importer;
+mod library;
+mod library_archive;
+mod library_search;
+mod linked_folders;
+mod network;
+mod rotation;
+mod solar;
+mod storage;
+mod subscriptions;
+#[cfg(test)]
+mod test_server;
+mod theme;
+mod thumbnail;
 mod tool;
+mod trash;
 mod wallpaper_animation;
 mod wallpaper_html;
+mod wallpaper_package;
+mod wallpaper_schedule;
 mod wallpaper_shader;
+mod wallpaper_source;
 mod wallpaper_static;
+mod wallpaper_switch;
+mod weather;
 
 use config::{read_config, set_config};
+use connectivity::{check_connectivity_now, clear_pending_downloads, get_connectivity_status};
+use cookie_jar::{clear_fetch_session, list_fetch_sessions};
+use credentials::{delete_credential, list_credentials, save_credential};
+use data_dir::{get_data_dir_info, move_data_dir};
+use dedup::{find_similar_wallpapers, scan_duplicate_wallpapers};
 use fetch::{fetch_json, fetch_request};
+use fetch_policy::{approve_wallpaper_network, clear_blocked_requests, list_blocked_requests};
 use fs_helper::{open_folder, read_file};
+use importer::{import_cancel, import_folder};
+use library::{get_library_item, list_library, refresh_library, update_library_item_meta};
+use library_archive::{export_library, import_library};
+use library_search::{
+    delete_library_search, list_library_tags, list_saved_searches, save_library_search,
+    search_library,
+};
+use linked_folders::{add_linked_folder, list_linked_folders, remove_linked_folder};
+use network::{get_network_settings, set_network_settings};
+use rotation::{get_rotation_candidates, rotate_wallpaper_now};
+use storage::{get_storage_usage, run_storage_gc};
+use subscriptions::{check_subscriptions_now, list_subscriptions};
+use theme::{apply_theme_wallpaper, get_system_theme, read_wallpaper_themes, set_wallpaper_theme};
+use thumbnail::{generate_wallpaper_thumbnails, get_wallpaper_thumbnail};
 use tool::{get_system_stats, open_executable};
+use trash::{empty_trash, list_trash, restore_from_trash};
 
 use wallpaper_animation::{create_animation_wallpaper, destroy_animation_wallpaper};
 use wallpaper_html::{
-    delete_wallpaper_html, read_wallpaper_html, read_wallpaper_html_file, save_temp_html,
-    save_wallpaper_html, write_wallpaper_html_file,
+    delete_wallpaper_html, export_wallpaper_html_package, import_wallpaper_html_package,
+    read_wallpaper_html, read_wallpaper_html_file, save_temp_html, save_wallpaper_html,
+    write_wallpaper_html_file,
+};
+use wallpaper_schedule::{apply_solar_schedule, preview_solar_schedule};
+use wallpaper_shader::{
+    delete_wallpaper_shader, export_wallpaper_shader_package, import_wallpaper_shader_package,
+    read_wallpaper_shader, save_wallpaper_shader,
 };
-use wallpaper_shader::{delete_wallpaper_shader, read_wallpaper_shader, save_wallpaper_shader};
+use wallpaper_source::{fetch_wallpaper_source, list_wallpaper_sources};
 use wallpaper_static::{
-    copy_wallpaper_to_wallpaper_static, delete_wallpaper_static, read_wallpaper_static,
-    set_static_wallpaper_from_path, set_static_wallpaper_from_url,
+    copy_wallpaper_to_wallpaper_static, delete_wallpaper_static, preview_image_pipeline,
+    read_wallpaper_static, set_static_wallpaper_from_path, set_static_wallpaper_from_url,
 };
+use weather::get_weather;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-        .setup(|_app| {
+        .setup(|app| {
             // 自动创建动态壁纸窗口
             // match create_animation_window(&app.handle()) {
             //     Ok(_) => println!("Animation wallpaper window created on startup"),
             //     Err(e) => eprintln!("Failed to create animation wallpaper window: {}", e),
             // }
 
+            // 日出日落定时切换壁纸
+            wallpaper_schedule::start_schedule_loop(app.handle().clone());
+            // 跟随系统主题切换壁纸
+            theme::start_theme_watcher(app.handle().clone());
+            // 监听关联文件夹
+            linked_folders::start_linked_folder_watcher(app.handle().clone());
+            // 回收站过期清理
+            trash::start_trash_purge_loop();
+            // 临时文件清理与缓存配额
+            storage::start_storage_gc_loop();
+            // 壁纸轮播
+            rotation::start_rotation_loop(app.handle().clone());
+            // 每日图片订阅
+            subscriptions::start_subscription_loop(app.handle().clone());
+            // 天气刷新
+            weather::start_weather_loop(app.handle().clone());
+            // 网络连接监测
+            connectivity::start_connectivity_monitor(app.handle().clone());
+
             Ok(())
         })
         .plugin(tauri_plugin_opener::init())
@@ -41,6 +119,23 @@ pub fn run() {
             //fetch
             fetch_request,
             fetch_json,
+            // fetch_policy
+            list_blocked_requests,
+            clear_blocked_requests,
+            approve_wallpaper_network,
+            // cookie_jar
+            list_fetch_sessions,
+            clear_fetch_session,
+            // credentials
+            save_credential,
+            list_credentials,
+            delete_credential,
+            // network
+            get_network_settings,
+            set_network_settings,
+            // data_dir
+            get_data_dir_info,
+            move_data_dir,
             //file
             open_folder,
             read_file,
@@ -53,10 +148,25 @@ pub fn run() {
             copy_wallpaper_to_wallpaper_static,
             read_wallpaper_static,
             delete_wallpaper_static,
+            preview_image_pipeline,
+            // wallpaper_source
+            list_wallpaper_sources,
+            fetch_wallpaper_source,
+            // subscriptions
+            list_subscriptions,
+            check_subscriptions_now,
+            // importer
+            import_folder,
+            import_cancel,
+            // thumbnail
+            get_wallpaper_thumbnail,
+            generate_wallpaper_thumbnails,
             // wallpaper_shader
             read_wallpaper_shader,
             delete_wallpaper_shader,
             save_wallpaper_shader,
+            export_wallpaper_shader_package,
+            import_wallpaper_shader_package,
             // wallpaper_html
             save_temp_html,
             read_wallpaper_html,
@@ -64,9 +174,56 @@ pub fn run() {
             save_wallpaper_html,
             read_wallpaper_html_file,
             write_wallpaper_html_file,
+            export_wallpaper_html_package,
+            import_wallpaper_html_package,
             // wallpaper_animation:shader
             create_animation_wallpaper,
             destroy_animation_wallpaper,
+            // library
+            list_library,
+            get_library_item,
+            refresh_library,
+            update_library_item_meta,
+            // library_archive
+            export_library,
+            import_library,
+            // library_search
+            search_library,
+            list_library_tags,
+            list_saved_searches,
+            save_library_search,
+            delete_library_search,
+            // linked_folders
+            list_linked_folders,
+            add_linked_folder,
+            remove_linked_folder,
+            // rotation
+            rotate_wallpaper_now,
+            get_rotation_candidates,
+            // dedup
+            scan_duplicate_wallpapers,
+            find_similar_wallpapers,
+            // weather
+            get_weather,
+            // connectivity
+            get_connectivity_status,
+            check_connectivity_now,
+            clear_pending_downloads,
+            // wallpaper_schedule
+            preview_solar_schedule,
+            apply_solar_schedule,
+            // theme
+            get_system_theme,
+            read_wallpaper_themes,
+            set_wallpaper_theme,
+            apply_theme_wallpaper,
+            // trash
+            list_trash,
+            restore_from_trash,
+            empty_trash,
+            // storage
+            get_storage_usage,
+            run_storage_gc,
             // tool
             get_system_stats,
             open_executable
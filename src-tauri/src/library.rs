@@ -0,0 +1,686 @@
+// 壁纸库索引模块：为静态 / 着色器 / HTML 壁纸维护带元数据的持久化索引
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::data_dir::try_write_guard;
+use crate::dedup::content_hash;
+use crate::fs_helper::get_appdata_dir;
+use crate::image_format::detect_file_format;
+use crate::linked_folders::{is_in_unavailable_folder, is_linked_path};
+use crate::wallpaper_html::read_wallpaper_html;
+use crate::wallpaper_shader::read_wallpaper_shader;
+use crate::wallpaper_static::static_wallpaper_files;
+
+/// 索引文件（相对 appdata 目录）
+const LIBRARY_FILE: &str = "library.json";
+const LIBRARY_VERSION: u32 = 1;
+
+/// 串行化索引的读-改-写
+static LIBRARY_LOCK: Mutex<()> = Mutex::new(());
+
+/// 壁纸目录可能已变化，下次读取时重新扫描（启动后首次读取总会扫描）
+static STALE: AtomicBool = AtomicBool::new(true);
+
+/// 下载得到、尚未导入库中的临时图片及其来源 URL
+static PENDING_SOURCES: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryItem {
+    /// 稳定 ID：由类型与相对数据目录的路径计算
+    pub id: String,
+    /// static / shader / html
+    pub kind: String,
+    pub title: String,
+    /// 图片文件或壁纸文件夹的完整路径
+    pub path: String,
+    /// 字节数（文件夹为其中文件大小之和）
+    pub size: u64,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub format: Option<String>,
+    /// 着色器 / HTML 壁纸的缩略图路径
+    pub thumbnail: Option<String>,
+    /// 加入库的时间（Unix 秒）
+    pub added_at: i64,
+    pub modified_at: i64,
+    /// local（扫描发现）/ import（手动导入）/ download（从网络下载）/ linked（关联文件夹）
+    pub source: String,
+    pub source_url: Option<String>,
+    /// 适用的系统主题：light / dark / any
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    /// 用户标签
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub favorite: bool,
+    /// 评分 1~5，未评分为空
+    #[serde(default)]
+    pub rating: Option<u8>,
+    #[serde(default)]
+    pub notes: String,
+    /// 静态壁纸文件内容的 SHA-256，用于导入去重
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    /// 静态壁纸的感知哈希（16 位十六进制），查找相似图时按需计算
+    #[serde(default)]
+    pub perceptual_hash: Option<String>,
+    /// 文件当前是否可访问（关联文件夹断开时为 false，保留条目与用户数据）
+    #[serde(default = "default_available")]
+    pub available: bool,
+    /// 在线来源图片的署名信息
+    #[serde(default)]
+    pub attribution: Option<Attribution>,
+}
+
+/// 在线图片的出处、作者与许可
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Attribution {
+    /// 来源 id，如 bing / nasa / unsplash
+    pub provider: String,
+    pub title: String,
+    pub author: String,
+    pub license: String,
+    pub page_url: Option<String>,
+}
+
+fn default_theme() -> String {
+    "any".to_string()
+}
+
+fn default_available() -> bool {
+    true
+}
+
+/// 保存的搜索条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryIndex {
+    pub version: u32,
+    pub items: Vec<LibraryItem>,
+    #[serde(default)]
+    pub saved_searches: Vec<SavedSearch>,
+}
+
+impl Default for LibraryIndex {
+    fn default() -> Self {
+        LibraryIndex {
+            version: LIBRARY_VERSION,
+            items: Vec::new(),
+            saved_searches: Vec::new(),
+        }
+    }
+}
+
+/// 用户元数据的部分更新，字段为空表示不修改
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LibraryMetaPatch {
+    pub tags: Option<Vec<String>>,
+    pub favorite: Option<bool>,
+    /// 0 表示清除评分
+    pub rating: Option<u8>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LibraryFilter {
+    /// static / shader / html，为空表示全部
+    pub kind: Option<String>,
+    /// 按标题模糊匹配（不区分大小写）
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LibrarySort {
+    /// title / added_at / modified_at / size / rating
+    pub field: String,
+    pub desc: bool,
+}
+
+impl Default for LibrarySort {
+    fn default() -> Self {
+        LibrarySort {
+            field: "added_at".to_string(),
+            desc: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LibraryPage {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Default for LibraryPage {
+    fn default() -> Self {
+        LibraryPage {
+            offset: 0,
+            limit: 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryPageResult {
+    pub items: Vec<LibraryItem>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// 计算库条目的稳定 ID
+///
+/// 数据目录内的条目使用相对路径，迁移数据目录后 ID 不变。
+pub fn item_id(kind: &str, path: &Path) -> String {
+    let relative = get_appdata_dir()
+        .ok()
+        .and_then(|base| path.strip_prefix(base).ok().map(|p| p.to_path_buf()))
+        .unwrap_or_else(|| path.to_path_buf());
+    let relative = relative.to_string_lossy().replace('\\', "/");
+
+    let mut hasher = Sha256::new();
+    hasher.update(kind.as_bytes());
+    hasher.update(b":");
+    hasher.update(relative.as_bytes());
+    let digest = hasher.finalize();
+
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 读取保存的索引
+///
+/// 文件不存在时返回空索引；内容无法解析时先将其移到 `library.json.bak` 保留标签、评分等数据，
+/// 再从空索引开始，避免下次保存直接覆盖。
+fn load_index() -> Result<LibraryIndex, String> {
+    let path = get_appdata_dir()?.join(LIBRARY_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(LibraryIndex::default()),
+        Err(e) => return Err(format!("Failed to read library index: {}", e)),
+    };
+
+    match serde_json::from_str(&content) {
+        Ok(index) => Ok(index),
+        Err(parse_error) => {
+            let backup = path.with_extension("json.bak");
+            fs::rename(&path, &backup).map_err(|e| {
+                format!(
+                    "Library index is corrupt ({}) and could not be backed up: {}",
+                    parse_error, e
+                )
+            })?;
+            eprintln!(
+                "Library index is corrupt ({}), moved it to {}",
+                parse_error,
+                backup.display()
+            );
+            Ok(LibraryIndex::default())
+        }
+    }
+}
+
+/// 先写入临时文件再重命名，写入中途崩溃不会留下损坏的索引
+fn save_index(index: &LibraryIndex) -> Result<(), String> {
+    let _writing = try_write_guard()?;
+    let content = serde_json::to_string(index)
+        .map_err(|e| format!("Failed to serialize library index: {}", e))?;
+    let path = get_appdata_dir()?.join(LIBRARY_FILE);
+    let temp = path.with_extension("json.part");
+    fs::write(&temp, content).map_err(|e| format!("Failed to write library index: {}", e))?;
+    fs::rename(&temp, &path).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        format!("Failed to save library index: {}", e)
+    })
+}
+
+fn unix_seconds(time: std::io::Result<std::time::SystemTime>) -> Option<i64> {
+    time.ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+}
+
+/// 文件夹内文件大小之和与最新修改时间
+fn folder_stats(path: &Path) -> (u64, i64) {
+    let mut size = 0;
+    let mut modified = 0;
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            if let Ok(metadata) = entry.metadata() {
+                if metadata.is_file() {
+                    size += metadata.len();
+                    modified = modified.max(unix_seconds(metadata.modified()).unwrap_or(0));
+                }
+            }
+        }
+    }
+    (size, modified)
+}
+
+/// 根据磁盘上的文件生成（或刷新）条目
+fn scan_item(kind: &str, path: &str, existing: Option<&LibraryItem>) -> Option<LibraryItem> {
+    let path_buf = PathBuf::from(path);
+    let metadata = fs::metadata(&path_buf).ok()?;
+
+    let (size, modified_at) = if metadata.is_dir() {
+        folder_stats(&path_buf)
+    } else {
+        (
+            metadata.len(),
+            unix_seconds(metadata.modified()).unwrap_or(0),
+        )
+    };
+
+    // 未变化的条目直接复用，避免重复读取图片尺寸
+    if let Some(existing) = existing {
+        let hashed = kind != "static" || existing.content_hash.is_some();
+        if existing.size == size
+            && existing.modified_at == modified_at
+            && existing.path == path
+            && hashed
+        {
+            let mut item = existing.clone();
+            item.available = true;
+            return Some(item);
+        }
+    }
+
+    let title = if metadata.is_dir() {
+        path_buf.file_name()
+    } else {
+        path_buf.file_stem()
+    }
+    .map(|name| name.to_string_lossy().to_string())
+    .unwrap_or_default();
+
+    let content_hash = if kind == "static" {
+        content_hash(&path_buf).ok()
+    } else {
+        None
+    };
+
+    let (width, height, format, thumbnail) = if kind == "static" {
+        let format = detect_file_format(&path_buf).map(|f| f.extension().to_string());
+        let dimensions = image::image_dimensions(&path_buf).ok();
+        (
+            dimensions.map(|d| d.0),
+            dimensions.map(|d| d.1),
+            format,
+            None,
+        )
+    } else {
+        let thumbnail = path_buf.join("thumbnail.png");
+        (
+            None,
+            None,
+            None,
+            thumbnail
+                .exists()
+                .then(|| thumbnail.to_string_lossy().to_string()),
+        )
+    };
+
+    let added_at = existing.map(|e| e.added_at).unwrap_or_else(|| {
+        unix_seconds(metadata.created())
+            .or(unix_seconds(metadata.modified()))
+            .unwrap_or_else(|| chrono::Utc::now().timestamp())
+    });
+
+    Some(LibraryItem {
+        id: item_id(kind, &path_buf),
+        kind: kind.to_string(),
+        title,
+        path: path.to_string(),
+        size,
+        width,
+        height,
+        format,
+        thumbnail,
+        added_at,
+        modified_at,
+        source: existing.map(|e| e.source.clone()).unwrap_or_else(|| {
+            if is_linked_path(path) {
+                "linked".to_string()
+            } else {
+                "local".to_string()
+            }
+        }),
+        source_url: existing.and_then(|e| e.source_url.clone()),
+        theme: existing
+            .map(|e| e.theme.clone())
+            .unwrap_or_else(default_theme),
+        tags: existing.map(|e| e.tags.clone()).unwrap_or_default(),
+        favorite: existing.map(|e| e.favorite).unwrap_or(false),
+        rating: existing.and_then(|e| e.rating),
+        notes: existing.map(|e| e.notes.clone()).unwrap_or_default(),
+        content_hash,
+        perceptual_hash: None,
+        available: true,
+        attribution: existing.and_then(|e| e.attribution.clone()),
+    })
+}
+
+/// 扫描各壁纸目录，与索引合并后保存（内容未变化时不写入）
+fn sync_index(index: &mut LibraryIndex) -> Result<(), String> {
+    // 扫描期间的新变化会重新标记
+    STALE.store(false, Ordering::SeqCst);
+    let before = serde_json::to_string(&*index).unwrap_or_default();
+
+    let mut entries: Vec<(&str, String)> = Vec::new();
+    entries.extend(static_wallpaper_files()?.into_iter().map(|p| ("static", p)));
+    entries.extend(read_wallpaper_shader()?.into_iter().map(|p| ("shader", p)));
+    entries.extend(read_wallpaper_html()?.into_iter().map(|p| ("html", p)));
+
+    let existing: HashMap<String, LibraryItem> = index
+        .items
+        .drain(..)
+        .map(|item| (item.id.clone(), item))
+        .collect();
+
+    for (kind, path) in entries {
+        let id = item_id(kind, Path::new(&path));
+        if let Some(item) = scan_item(kind, &path, existing.get(&id)) {
+            index.items.push(item);
+        }
+    }
+
+    // 暂时不可用的关联文件夹中的条目保留，待文件夹恢复后继续使用
+    let scanned: std::collections::HashSet<String> =
+        index.items.iter().map(|item| item.id.clone()).collect();
+    for (id, mut item) in existing {
+        if !scanned.contains(&id) && is_in_unavailable_folder(&item.path) {
+            item.available = false;
+            index.items.push(item);
+        }
+    }
+
+    migrate_theme_tags(index);
+    index.version = LIBRARY_VERSION;
+    if serde_json::to_string(&*index).unwrap_or_default() == before {
+        return Ok(());
+    }
+    save_index(index)
+}
+
+/// 将旧版 wallpaper_theme.json 中的主题标签合并到索引
+fn migrate_theme_tags(index: &mut LibraryIndex) {
+    let legacy = match get_appdata_dir() {
+        Ok(base) => base.join("wallpaper_theme.json"),
+        Err(_) => return,
+    };
+    let tags: Vec<serde_json::Value> = match fs::read_to_string(&legacy)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+    {
+        Some(tags) => tags,
+        None => return,
+    };
+
+    for tag in tags {
+        let path = tag.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        let theme = tag.get("theme").and_then(|v| v.as_str()).unwrap_or("any");
+        // 旧标签可能记录的是 shader.glsl / index.html 入口文件
+        let folder = Path::new(path)
+            .parent()
+            .map(|p| p.to_string_lossy().to_string());
+        if let Some(item) = index
+            .items
+            .iter_mut()
+            .find(|item| item.path == path || Some(&item.path) == folder.as_ref())
+        {
+            item.theme = theme.to_string();
+        }
+    }
+
+    let _ = fs::remove_file(legacy);
+}
+
+/// 标记壁纸目录已变化，下次读取索引时重新扫描
+pub fn mark_library_stale() {
+    STALE.store(true, Ordering::SeqCst);
+}
+
+/// 读取索引：没有标记变化时直接使用保存的索引，不扫描磁盘
+pub fn load_library() -> Result<LibraryIndex, String> {
+    if STALE.load(Ordering::SeqCst) {
+        return sync_library();
+    }
+    let _guard = LIBRARY_LOCK
+        .lock()
+        .map_err(|_| "Library index is locked".to_string())?;
+    load_index()
+}
+
+/// 重新扫描各壁纸目录，同步并返回完整索引
+pub fn sync_library() -> Result<LibraryIndex, String> {
+    let _guard = LIBRARY_LOCK
+        .lock()
+        .map_err(|_| "Library index is locked".to_string())?;
+    let mut index = load_index()?;
+    sync_index(&mut index)?;
+    Ok(index)
+}
+
+/// 修改单个条目并保存，返回修改后的条目
+pub fn update_library_item<F>(id: &str, update: F) -> Result<LibraryItem, String>
+where
+    F: FnOnce(&mut LibraryItem),
+{
+    let _guard = LIBRARY_LOCK
+        .lock()
+        .map_err(|_| "Library index is locked".to_string())?;
+    let mut index = load_index()?;
+    if !index.items.iter().any(|item| item.id == id) {
+        sync_index(&mut index)?;
+    }
+
+    let item = index
+        .items
+        .iter_mut()
+        .find(|item| item.id == id)
+        .ok_or_else(|| format!("Library item not found: {}", id))?;
+    update(item);
+    let updated = item.clone();
+
+    save_index(&index)?;
+    Ok(updated)
+}
+
+/// 批量修改条目并保存
+pub fn update_library_items<F>(update: F) -> Result<(), String>
+where
+    F: FnOnce(&mut Vec<LibraryItem>),
+{
+    let _guard = LIBRARY_LOCK
+        .lock()
+        .map_err(|_| "Library index is locked".to_string())?;
+    let mut index = load_index()?;
+    update(&mut index.items);
+    save_index(&index)
+}
+
+/// 修改保存的搜索列表并保存
+pub fn update_saved_searches<F>(update: F) -> Result<Vec<SavedSearch>, String>
+where
+    F: FnOnce(&mut Vec<SavedSearch>),
+{
+    let _guard = LIBRARY_LOCK
+        .lock()
+        .map_err(|_| "Library index is locked".to_string())?;
+    let mut index = load_index()?;
+    update(&mut index.saved_searches);
+    save_index(&index)?;
+    Ok(index.saved_searches)
+}
+
+/// 按路径查找条目 ID
+pub fn find_item_id_by_path(path: &str) -> Result<Option<String>, String> {
+    let index = load_library()?;
+    let path_buf = PathBuf::from(path);
+    // 兼容传入 shader.glsl / index.html 入口文件
+    let folder = path_buf
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    Ok(index
+        .items
+        .iter()
+        .find(|item| item.path == path)
+        .or_else(|| index.items.iter().find(|item| item.path == folder))
+        .map(|item| item.id.clone()))
+}
+
+/// 记录下载到临时目录的图片来源，导入库时写入条目
+pub fn remember_download_source(temp_path: &str, url: &str) {
+    if let Ok(mut pending) = PENDING_SOURCES.lock() {
+        pending
+            .get_or_insert_with(HashMap::new)
+            .insert(temp_path.to_string(), url.to_string());
+    }
+}
+
+/// 导入文件后登记来源信息
+pub fn register_import(kind: &str, source_path: &str, dest_path: &str) -> Result<(), String> {
+    let source_url = PENDING_SOURCES
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.as_mut().and_then(|p| p.remove(source_path)));
+    let id = item_id(kind, Path::new(dest_path));
+
+    update_library_item(&id, |item| match source_url {
+        Some(url) => {
+            item.source = "download".to_string();
+            item.source_url = Some(url);
+        }
+        None => item.source = "import".to_string(),
+    })?;
+
+    Ok(())
+}
+
+/// 整理标签：去除首尾空白、空标签与重复（不区分大小写）
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_string();
+        if !tag.is_empty() && !result.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            result.push(tag);
+        }
+    }
+    result
+}
+
+/// 修改壁纸的标签、收藏、评分、备注
+#[tauri::command]
+pub fn update_library_item_meta(id: String, meta: LibraryMetaPatch) -> Result<LibraryItem, String> {
+    if let Some(rating) = meta.rating {
+        if rating > 5 {
+            return Err(format!("Invalid rating: {}", rating));
+        }
+    }
+
+    update_library_item(&id, |item| {
+        if let Some(tags) = meta.tags {
+            item.tags = normalize_tags(tags);
+        }
+        if let Some(favorite) = meta.favorite {
+            item.favorite = favorite;
+        }
+        if let Some(rating) = meta.rating {
+            item.rating = (rating > 0).then_some(rating);
+        }
+        if let Some(notes) = meta.notes {
+            item.notes = notes;
+        }
+    })
+}
+
+/// 分页列出壁纸库
+#[tauri::command]
+pub fn list_library(
+    filter: Option<LibraryFilter>,
+    sort: Option<LibrarySort>,
+    page: Option<LibraryPage>,
+) -> Result<LibraryPageResult, String> {
+    let filter = filter.unwrap_or_default();
+    let sort = sort.unwrap_or_default();
+    let page = page.unwrap_or_default();
+
+    let index = load_library()?;
+    let query = filter.query.as_ref().map(|q| q.to_lowercase());
+
+    let items: Vec<LibraryItem> = index
+        .items
+        .into_iter()
+        .filter(|item| filter.kind.as_ref().is_none_or(|kind| &item.kind == kind))
+        .filter(|item| {
+            query
+                .as_ref()
+                .is_none_or(|q| item.title.to_lowercase().contains(q))
+        })
+        .collect();
+
+    Ok(sort_and_page(items, &sort, &page))
+}
+
+/// 排序并分页
+pub fn sort_and_page(
+    mut items: Vec<LibraryItem>,
+    sort: &LibrarySort,
+    page: &LibraryPage,
+) -> LibraryPageResult {
+    match sort.field.as_str() {
+        "title" => items.sort_by_key(|item| item.title.to_lowercase()),
+        "modified_at" => items.sort_by_key(|item| item.modified_at),
+        "size" => items.sort_by_key(|item| item.size),
+        "rating" => items.sort_by_key(|item| item.rating.unwrap_or(0)),
+        _ => items.sort_by_key(|item| item.added_at),
+    }
+    if sort.desc {
+        items.reverse();
+    }
+
+    let total = items.len();
+    let limit = page.limit.clamp(1, 500);
+    let items = items.into_iter().skip(page.offset).take(limit).collect();
+
+    LibraryPageResult {
+        items,
+        total,
+        offset: page.offset,
+        limit,
+    }
+}
+
+/// 获取单个壁纸的完整信息
+#[tauri::command]
+pub fn get_library_item(id: String) -> Result<LibraryItem, String> {
+    load_library()?
+        .items
+        .into_iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| format!("Library item not found: {}", id))
+}
+
+/// 重新扫描壁纸目录（如手动向数据目录中添加了文件），返回条目数量
+#[tauri::command]
+pub fn refresh_library() -> Result<usize, String> {
+    Ok(sync_library()?.items.len())
+}
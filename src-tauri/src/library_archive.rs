@@ -0,0 +1,607 @@
+// 壁纸库归档模块：将整个壁纸库（图片、着色器、HTML、元数据、配置）导出为 zip，并在另一台机器上合并导入
+//
+// 归档结构：
+//   manifest.json                 条目元数据、保存的搜索、配置
+//   wallpaper_static/<文件>
+//   wallpaper_shader/<文件夹>/...
+//   wallpaper_html/<文件夹>/...
+// 配置中指向数据目录的绝对路径以 `${DATA_DIR}/` 开头的相对路径保存，导入时改写为新的数据目录。
+// 关联文件夹、网络设置与 API 密钥只属于本机，不导出。
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::archive::{
+    add_bytes, add_dir, add_file, create_archive, entry_name, extract_to, join_relative,
+    open_archive, read_string, validate_archive,
+};
+use crate::config::{read_config_value, update_config};
+use crate::data_dir::try_write_guard;
+use crate::dedup::unique_destination;
+use crate::fs_helper::get_appdata_dir;
+use crate::library::{
+    item_id, load_library, sync_library, update_library_items, update_saved_searches, Attribution,
+    LibraryItem, SavedSearch,
+};
+use crate::trash::move_to_trash;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const ARCHIVE_FORMAT: &str = "opaper-library";
+const ARCHIVE_VERSION: u32 = 1;
+const DATA_DIR_TOKEN: &str = "${DATA_DIR}";
+/// 含绝对路径的配置项，以 `.` 分隔层级，`*` 匹配任意键
+const PATH_KEYS: [&str; 4] = [
+    "shaderPath",
+    "htmlPath",
+    "schedule.wallpapers.*.path",
+    "image_pipeline.text.font_path",
+];
+/// 只在本机有意义或含密钥、不导出的配置项
+const MACHINE_KEYS: [&str; 4] = [
+    "linked_folders",
+    "network",
+    "weather.api_key",
+    "sources.*.api_key",
+];
+
+const MAX_MANIFEST_BYTES: u64 = 16 * 1024 * 1024;
+const MAX_ENTRY_BYTES: u64 = 512 * 1024 * 1024;
+const MAX_TOTAL_BYTES: u64 = 16 * 1024 * 1024 * 1024;
+
+/// 临时解压目录序号，同一毫秒内的多次导入也不会使用相同的目录
+static STAGING_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ExportSelection {
+    /// 只导出指定条目，为空表示全部
+    pub ids: Option<Vec<String>>,
+    /// 只导出指定类型（static / shader / html）
+    pub kinds: Option<Vec<String>>,
+    pub include_config: bool,
+}
+
+impl Default for ExportSelection {
+    fn default() -> Self {
+        ExportSelection {
+            ids: None,
+            kinds: None,
+            include_config: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// 保留本地，跳过归档中的同名条目
+    Skip,
+    /// 同名时自动改名为 `name (1)`
+    #[default]
+    Rename,
+    /// 本地同名条目移入回收站后覆盖
+    Overwrite,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchiveItem {
+    kind: String,
+    /// 归档内的相对路径，如 `wallpaper_shader/ocean`
+    path: String,
+    title: String,
+    theme: String,
+    tags: Vec<String>,
+    favorite: bool,
+    rating: Option<u8>,
+    notes: String,
+    source: String,
+    source_url: Option<String>,
+    added_at: i64,
+    #[serde(default)]
+    attribution: Option<Attribution>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LibraryManifest {
+    format: String,
+    version: u32,
+    exported_at: String,
+    items: Vec<ArchiveItem>,
+    saved_searches: Vec<SavedSearch>,
+    config: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub items: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportLibrarySummary {
+    pub imported: usize,
+    pub skipped: usize,
+    pub renamed: usize,
+    pub overwritten: usize,
+    /// 从归档中补充的配置项
+    pub config_keys: Vec<String>,
+}
+
+fn kind_dir(kind: &str) -> Option<&'static str> {
+    match kind {
+        "static" => Some("wallpaper_static"),
+        "shader" => Some("wallpaper_shader"),
+        "html" => Some("wallpaper_html"),
+        _ => None,
+    }
+}
+
+/// 将数据目录内的绝对路径转为 `${DATA_DIR}/...`，其他路径返回 None
+fn to_portable_path(path: &str, base_dir: &Path) -> Option<String> {
+    let relative = Path::new(path).strip_prefix(base_dir).ok()?;
+    Some(format!(
+        "{}/{}",
+        DATA_DIR_TOKEN,
+        relative.to_string_lossy().replace('\\', "/")
+    ))
+}
+
+/// 对配置中匹配 `pattern` 的每一项调用 `f(所在对象, 键)`
+fn for_each_key(
+    value: &mut serde_json::Value,
+    pattern: &str,
+    f: &mut dyn FnMut(&mut serde_json::Map<String, serde_json::Value>, &str),
+) {
+    let Some(obj) = value.as_object_mut() else {
+        return;
+    };
+    let (head, rest) = match pattern.split_once('.') {
+        Some((head, rest)) => (head, Some(rest)),
+        None => (pattern, None),
+    };
+
+    match rest {
+        None => {
+            let keys: Vec<String> = if head == "*" {
+                obj.keys().cloned().collect()
+            } else if obj.contains_key(head) {
+                vec![head.to_string()]
+            } else {
+                Vec::new()
+            };
+            for key in keys {
+                f(obj, &key);
+            }
+        }
+        Some(rest) => {
+            for (key, child) in obj.iter_mut() {
+                if head == "*" || key == head {
+                    for_each_key(child, rest, f);
+                }
+            }
+        }
+    }
+}
+
+/// 去掉本机专用项，并用 `rewrite` 改写路径项，返回 None 的路径项被移除
+fn transform_config(config: &mut serde_json::Value, rewrite: &dyn Fn(&str) -> Option<String>) {
+    for pattern in MACHINE_KEYS {
+        for_each_key(config, pattern, &mut |obj, key| {
+            obj.remove(key);
+        });
+    }
+    for pattern in PATH_KEYS {
+        for_each_key(config, pattern, &mut |obj, key| match obj
+            .get(key)
+            .and_then(|v| v.as_str())
+            .and_then(rewrite)
+        {
+            Some(path) => {
+                obj.insert(key.to_string(), serde_json::Value::String(path));
+            }
+            None => {
+                obj.remove(key);
+            }
+        });
+    }
+}
+
+/// 导出配置：去掉本机专用项与密钥，路径改写为可移植形式，数据目录外的路径不导出
+fn portable_config(base_dir: &Path) -> serde_json::Value {
+    let mut config = read_config_value();
+    transform_config(&mut config, &|path| to_portable_path(path, base_dir));
+    config
+}
+
+fn to_archive_item(item: &LibraryItem, path: String) -> ArchiveItem {
+    ArchiveItem {
+        kind: item.kind.clone(),
+        path,
+        title: item.title.clone(),
+        theme: item.theme.clone(),
+        tags: item.tags.clone(),
+        favorite: item.favorite,
+        rating: item.rating,
+        notes: item.notes.clone(),
+        source: item.source.clone(),
+        source_url: item.source_url.clone(),
+        added_at: item.added_at,
+        attribution: item.attribution.clone(),
+    }
+}
+
+/// 导出壁纸库为 zip 归档
+#[tauri::command]
+pub async fn export_library(
+    path: String,
+    selection: Option<ExportSelection>,
+) -> Result<ExportSummary, String> {
+    let selection = selection.unwrap_or_default();
+    tokio::task::spawn_blocking(move || export_library_blocking(&path, &selection))
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+fn export_library_blocking(
+    path: &str,
+    selection: &ExportSelection,
+) -> Result<ExportSummary, String> {
+    let base_dir = get_appdata_dir()?;
+    let index = load_library()?;
+
+    let items: Vec<&LibraryItem> = index
+        .items
+        .iter()
+        .filter(|item| item.available)
+        .filter(|item| {
+            selection
+                .ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&item.id))
+        })
+        .filter(|item| {
+            selection
+                .kinds
+                .as_ref()
+                .is_none_or(|kinds| kinds.contains(&item.kind))
+        })
+        .collect();
+
+    // 归档内的路径，关联文件夹中的图片可能重名
+    let mut used: HashSet<String> = HashSet::new();
+    let mut entries: Vec<(&LibraryItem, String)> = Vec::new();
+    for item in items {
+        let dir = match kind_dir(&item.kind) {
+            Some(dir) => dir,
+            None => continue,
+        };
+        let name = Path::new(&item.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| item.id.clone());
+
+        let mut archive_path = format!("{}/{}", dir, name);
+        let mut n = 1;
+        while !used.insert(archive_path.clone()) {
+            archive_path = format!("{}/{}_{}", dir, n, name);
+            n += 1;
+        }
+        entries.push((item, archive_path));
+    }
+
+    let manifest = LibraryManifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        exported_at: chrono::Local::now().to_rfc3339(),
+        items: entries
+            .iter()
+            .map(|(item, path)| to_archive_item(item, path.clone()))
+            .collect(),
+        saved_searches: index.saved_searches.clone(),
+        config: selection.include_config.then(|| portable_config(&base_dir)),
+    };
+
+    let target = PathBuf::from(path);
+    let mut zip = create_archive(&target)?;
+    let content = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    add_bytes(&mut zip, MANIFEST_FILE, content.as_bytes())?;
+
+    let mut bytes = 0;
+    for (item, archive_path) in &entries {
+        let source = Path::new(&item.path);
+        bytes += if source.is_dir() {
+            add_dir(&mut zip, source, archive_path)?
+        } else {
+            add_file(&mut zip, source, archive_path)?
+        };
+    }
+    zip.finish()
+        .map_err(|e| format!("Failed to finish archive: {}", e))?;
+
+    Ok(ExportSummary {
+        path: target.to_string_lossy().to_string(),
+        items: entries.len(),
+        bytes,
+    })
+}
+
+/// 从 zip 归档合并导入壁纸库
+#[tauri::command]
+pub async fn import_library(
+    path: String,
+    conflict_policy: Option<ConflictPolicy>,
+) -> Result<ImportLibrarySummary, String> {
+    let policy = conflict_policy.unwrap_or_default();
+    tokio::task::spawn_blocking(move || import_library_blocking(&path, policy))
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+fn import_library_blocking(
+    path: &str,
+    policy: ConflictPolicy,
+) -> Result<ImportLibrarySummary, String> {
+    let _writing = try_write_guard()?;
+    let base_dir = get_appdata_dir()?;
+    let mut archive = open_archive(Path::new(path))?;
+    validate_archive(&mut archive, MAX_ENTRY_BYTES, MAX_TOTAL_BYTES)?;
+
+    let manifest: LibraryManifest = serde_json::from_str(&read_string(
+        &mut archive,
+        MANIFEST_FILE,
+        MAX_MANIFEST_BYTES,
+    )?)
+    .map_err(|e| format!("Invalid manifest: {}", e))?;
+    if manifest.format != ARCHIVE_FORMAT || manifest.version > ARCHIVE_VERSION {
+        return Err("Unsupported library archive".to_string());
+    }
+
+    let names: Vec<Option<String>> = (0..archive.len())
+        .map(|index| entry_name(&mut archive, index))
+        .collect();
+
+    // 先把所有条目解压到临时目录，全部成功后再移入壁纸目录，解压失败不会留下残缺的条目
+    let staging = base_dir.join("temp").join(format!(
+        "library_{}_{}",
+        chrono::Utc::now().timestamp_millis(),
+        STAGING_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let staged = match stage_items(&mut archive, &names, manifest.items, &staging) {
+        Ok(staged) => staged,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+
+    let mut summary = ImportLibrarySummary::default();
+    // 归档内路径 -> 导入后的绝对路径
+    let mut mapping: HashMap<String, PathBuf> = HashMap::new();
+    let mut imported: Vec<(ArchiveItem, PathBuf)> = Vec::new();
+    let installed = install_items(
+        staged,
+        &base_dir,
+        policy,
+        &mut summary,
+        &mut mapping,
+        &mut imported,
+    );
+    let _ = fs::remove_dir_all(&staging);
+
+    // 刷新索引后写回标签、评分等元数据
+    sync_library()?;
+    let metadata: HashMap<String, ArchiveItem> = imported
+        .into_iter()
+        .map(|(item, target)| (item_id(&item.kind, &target), item))
+        .collect();
+    update_library_items(|items| {
+        for item in items.iter_mut() {
+            if let Some(meta) = metadata.get(&item.id) {
+                item.added_at = meta.added_at;
+                item.theme = meta.theme.clone();
+                item.tags = meta.tags.clone();
+                item.favorite = meta.favorite;
+                item.rating = meta.rating;
+                item.notes = meta.notes.clone();
+                item.source = meta.source.clone();
+                item.source_url = meta.source_url.clone();
+                item.attribution = meta.attribution.clone();
+            }
+        }
+    })?;
+
+    // 中途移入失败时，已导入的条目同样写回元数据后再返回错误
+    installed?;
+
+    update_saved_searches(|searches| {
+        for search in manifest.saved_searches {
+            if !searches.iter().any(|s| s.name == search.name) {
+                searches.push(search);
+            }
+        }
+    })?;
+
+    if let Some(config) = manifest.config {
+        summary.config_keys = merge_config(config, &base_dir, &mapping)?;
+    }
+
+    Ok(summary)
+}
+
+/// 解压到临时目录的条目：(条目, 条目名, 临时路径)
+type StagedItem = (ArchiveItem, String, PathBuf);
+
+/// 将各条目解压到 `staging` 下，无效条目跳过
+fn stage_items(
+    archive: &mut ZipArchive<fs::File>,
+    names: &[Option<String>],
+    items: Vec<ArchiveItem>,
+    staging: &Path,
+) -> Result<Vec<StagedItem>, String> {
+    // 条目声明的大小不可信，按实际解压的字节数限制总量
+    let mut remaining = MAX_TOTAL_BYTES;
+    let mut staged = Vec::new();
+
+    for item in items {
+        // 条目路径必须是 `<类型目录>/<名称>`
+        let dir = match kind_dir(&item.kind) {
+            Some(dir) => dir,
+            None => continue,
+        };
+        let name = match item.path.strip_prefix(&format!("{}/", dir)) {
+            Some(name) if !name.is_empty() && !name.contains(['/', '\\']) && name != ".." => {
+                name.to_string()
+            }
+            _ => {
+                eprintln!("Skipping invalid archive item: {}", item.path);
+                continue;
+            }
+        };
+
+        let target = staging.join(staged.len().to_string());
+        let prefix = format!("{}/", item.path);
+        for (index, entry) in names.iter().enumerate() {
+            let entry = match entry {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let destination = if entry == &item.path {
+                target.clone()
+            } else if let Some(relative) = entry.strip_prefix(&prefix) {
+                join_relative(&target, relative)
+            } else {
+                continue;
+            };
+            extract_to(
+                archive,
+                index,
+                &destination,
+                MAX_ENTRY_BYTES,
+                &mut remaining,
+            )?;
+        }
+        staged.push((item, name, target));
+    }
+
+    Ok(staged)
+}
+
+/// 按冲突策略将解压好的条目移入壁纸目录
+fn install_items(
+    staged: Vec<StagedItem>,
+    base_dir: &Path,
+    policy: ConflictPolicy,
+    summary: &mut ImportLibrarySummary,
+    mapping: &mut HashMap<String, PathBuf>,
+    imported: &mut Vec<(ArchiveItem, PathBuf)>,
+) -> Result<(), String> {
+    for (item, name, source) in staged {
+        // 归档中没有对应文件的条目
+        if !source.exists() {
+            continue;
+        }
+        let dir = kind_dir(&item.kind).unwrap_or_default();
+        let parent = base_dir.join(dir);
+        fs::create_dir_all(&parent)
+            .map_err(|e| format!("Failed to create resource directory: {}", e))?;
+
+        let mut target = parent.join(&name);
+        if target.exists() {
+            match policy {
+                ConflictPolicy::Skip => {
+                    summary.skipped += 1;
+                    mapping.insert(item.path.clone(), target);
+                    continue;
+                }
+                ConflictPolicy::Rename => {
+                    target = unique_destination(&parent, &name);
+                    summary.renamed += 1;
+                }
+                ConflictPolicy::Overwrite => {
+                    move_to_trash(&item.kind, &target)?;
+                    summary.overwritten += 1;
+                }
+            }
+        }
+
+        fs::rename(&source, &target)
+            .map_err(|e| format!("Failed to install {}: {}", item.path, e))?;
+        summary.imported += 1;
+        mapping.insert(item.path.clone(), target.clone());
+        imported.push((item, target));
+    }
+    Ok(())
+}
+
+/// 将 `${DATA_DIR}/...` 改写为新数据目录下的路径，并跟随导入时的改名
+///
+/// 含 `..` 或绝对路径部分的路径可能指向数据目录之外，返回 `None` 丢弃该配置项。
+fn resolve_portable_path(
+    path: &str,
+    base_dir: &Path,
+    mapping: &HashMap<String, PathBuf>,
+) -> Option<String> {
+    let relative = path.strip_prefix(&format!("{}/", DATA_DIR_TOKEN))?;
+    if !Path::new(relative)
+        .components()
+        .all(|part| matches!(part, Component::Normal(_) | Component::CurDir))
+    {
+        return None;
+    }
+
+    for (archive_path, target) in mapping {
+        if relative == archive_path {
+            return Some(target.to_string_lossy().to_string());
+        }
+        if let Some(rest) = relative.strip_prefix(&format!("{}/", archive_path)) {
+            return Some(join_relative(target, rest).to_string_lossy().to_string());
+        }
+    }
+
+    Some(
+        join_relative(base_dir, relative)
+            .to_string_lossy()
+            .to_string(),
+    )
+}
+
+/// 用归档中的配置补充本地未设置的配置项，返回补充的键
+fn merge_config(
+    mut config: serde_json::Value,
+    base_dir: &Path,
+    mapping: &HashMap<String, PathBuf>,
+) -> Result<Vec<String>, String> {
+    let local = read_config_value();
+    let is_unset = |key: &str| match local.get(key) {
+        None | Some(serde_json::Value::Null) => true,
+        Some(serde_json::Value::String(s)) => s.is_empty(),
+        _ => false,
+    };
+
+    // 旧版本导出的归档可能含本机专用项，导入时同样过滤
+    transform_config(&mut config, &|path| {
+        resolve_portable_path(path, base_dir, mapping)
+    });
+
+    let mut updates = serde_json::Map::new();
+    if let serde_json::Value::Object(config) = config {
+        for (key, value) in config {
+            if is_unset(&key) {
+                updates.insert(key, value);
+            }
+        }
+    }
+
+    let keys: Vec<String> = updates.keys().cloned().collect();
+    if !keys.is_empty() {
+        update_config(serde_json::Value::Object(updates))?;
+    }
+    Ok(keys)
+}
@@ -0,0 +1,195 @@
+// 壁纸库搜索模块：标签 / 文本查询与保存的搜索
+//
+// 查询语法（空格分隔，条件之间为“且”）：
+//   tag:自然          包含标签（可多个，需全部包含），带空格的标签使用 tag:"night sky"
+//   type:static       类型 static / shader / html
+//   is:favorite       仅收藏
+//   rating:4          评分不低于 4
+//   其他文字          匹配标题、备注或标签
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::library::{
+    load_library, sort_and_page, update_saved_searches, LibraryItem, LibraryPage,
+    LibraryPageResult, LibrarySort, SavedSearch,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LibraryQuery {
+    pub text: Vec<String>,
+    pub tags: Vec<String>,
+    pub kind: Option<String>,
+    pub favorite: Option<bool>,
+    pub min_rating: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// 按空白切分，引号内的空白不切分
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+impl LibraryQuery {
+    pub fn parse(query: &str) -> LibraryQuery {
+        let mut result = LibraryQuery::default();
+
+        for token in tokenize(query) {
+            let (key, value) = match token.split_once(':') {
+                Some((key, value)) if !value.is_empty() => (key.to_lowercase(), value.to_string()),
+                _ => {
+                    result.text.push(token.to_lowercase());
+                    continue;
+                }
+            };
+
+            match key.as_str() {
+                "tag" => result.tags.push(value.to_lowercase()),
+                "type" | "kind" => result.kind = Some(value.to_lowercase()),
+                "is" if matches!(value.as_str(), "favorite" | "fav") => {
+                    result.favorite = Some(true)
+                }
+                "rating" => result.min_rating = value.parse().ok(),
+                _ => result.text.push(token.to_lowercase()),
+            }
+        }
+
+        result
+    }
+
+    pub fn matches(&self, item: &LibraryItem) -> bool {
+        if let Some(kind) = &self.kind {
+            if &item.kind != kind {
+                return false;
+            }
+        }
+        if self.favorite == Some(true) && !item.favorite {
+            return false;
+        }
+        if let Some(min_rating) = self.min_rating {
+            if item.rating.unwrap_or(0) < min_rating {
+                return false;
+            }
+        }
+
+        let item_tags: Vec<String> = item.tags.iter().map(|t| t.to_lowercase()).collect();
+        if !self.tags.iter().all(|tag| item_tags.contains(tag)) {
+            return false;
+        }
+
+        let title = item.title.to_lowercase();
+        let notes = item.notes.to_lowercase();
+        self.text.iter().all(|term| {
+            title.contains(term)
+                || notes.contains(term)
+                || item_tags.iter().any(|tag| tag.contains(term))
+        })
+    }
+}
+
+/// 返回符合查询条件的全部条目
+pub fn search_items(query: &str) -> Result<Vec<LibraryItem>, String> {
+    let query = LibraryQuery::parse(query);
+    Ok(load_library()?
+        .items
+        .into_iter()
+        .filter(|item| query.matches(item))
+        .collect())
+}
+
+/// 按保存的搜索名称返回条目
+pub fn saved_search_items(name: &str) -> Result<Vec<LibraryItem>, String> {
+    let saved = load_library()?
+        .saved_searches
+        .into_iter()
+        .find(|search| search.name == name)
+        .ok_or_else(|| format!("Saved search not found: {}", name))?;
+    search_items(&saved.query)
+}
+
+/// 搜索壁纸库
+#[tauri::command]
+pub fn search_library(
+    query: String,
+    sort: Option<LibrarySort>,
+    page: Option<LibraryPage>,
+) -> Result<LibraryPageResult, String> {
+    let items = search_items(&query)?;
+    Ok(sort_and_page(
+        items,
+        &sort.unwrap_or_default(),
+        &page.unwrap_or_default(),
+    ))
+}
+
+/// 列出所有标签及使用次数
+#[tauri::command]
+pub fn list_library_tags() -> Result<Vec<TagCount>, String> {
+    // 以小写合并计数，展示首次出现的写法
+    let mut counts: BTreeMap<String, TagCount> = BTreeMap::new();
+    for item in load_library()?.items {
+        for tag in item.tags {
+            counts
+                .entry(tag.to_lowercase())
+                .or_insert(TagCount { tag, count: 0 })
+                .count += 1;
+        }
+    }
+
+    let mut tags: Vec<TagCount> = counts.into_values().collect();
+    tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    Ok(tags)
+}
+
+/// 列出保存的搜索
+#[tauri::command]
+pub fn list_saved_searches() -> Result<Vec<SavedSearch>, String> {
+    Ok(load_library()?.saved_searches)
+}
+
+/// 保存搜索（同名覆盖）
+#[tauri::command]
+pub fn save_library_search(name: String, query: String) -> Result<Vec<SavedSearch>, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Search name cannot be empty".to_string());
+    }
+
+    update_saved_searches(
+        |searches| match searches.iter_mut().find(|s| s.name == name) {
+            Some(search) => search.query = query,
+            None => searches.push(SavedSearch { name, query }),
+        },
+    )
+}
+
+/// 删除保存的搜索
+#[tauri::command]
+pub fn delete_library_search(name: String) -> Result<Vec<SavedSearch>, String> {
+    update_saved_searches(|searches| searches.retain(|search| search.name != name))
+}
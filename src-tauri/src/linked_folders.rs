@@ -0,0 +1,349 @@
+// 关联文件夹模块：将外部目录（如 ~/Pictures/Wallpapers、NAS 挂载点）作为只读壁纸来源
+//
+// 配置示例（config.json）：
+// "linked_folders": [
+//   { "path": "/home/me/Pictures/Wallpapers", "recursive": true }
+// ]
+// 关联文件夹中的图片只会被索引，不会被复制、修改或删除。
+// 扫描结果会缓存，监听到文件变化或文件夹可用性变化后才重新扫描。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::config::{read_config_value, update_config};
+use crate::data_dir::background_write_guard;
+use crate::fs_helper::get_appdata_dir;
+use crate::importer::{collect_images, ImportOptions};
+use crate::library::sync_library;
+
+/// 文件变化后的合并等待间隔
+const DEBOUNCE_SECS: u64 = 2;
+/// 检查文件夹是否可用（如网络盘断开 / 重新挂载）的间隔
+const AVAILABILITY_CHECK_SECS: u64 = 30;
+
+/// 文件监听器，关联文件夹变化时重建
+static WATCHER: Mutex<Option<RecommendedWatcher>> = Mutex::new(None);
+/// 监听到文件变化，等待刷新索引
+static DIRTY: AtomicBool = AtomicBool::new(false);
+/// 上次检查时可用的文件夹
+static AVAILABLE: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// 配置中的关联文件夹，修改时更新，并在定期检查时重新读取
+static FOLDERS: Mutex<Option<Vec<LinkedFolder>>> = Mutex::new(None);
+/// 各关联文件夹（按路径）中的图片
+static IMAGES: Mutex<Option<HashMap<String, Vec<String>>>> = Mutex::new(None);
+/// 缓存失效次数，扫描期间失效的结果不写入缓存
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkedFolder {
+    pub path: String,
+    #[serde(default = "default_recursive")]
+    pub recursive: bool,
+}
+
+fn default_recursive() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkedFolderStatus {
+    pub path: String,
+    pub recursive: bool,
+    /// 文件夹当前是否可访问
+    pub available: bool,
+    pub count: usize,
+}
+
+pub fn read_linked_folders() -> Vec<LinkedFolder> {
+    if let Some(folders) = FOLDERS.lock().ok().and_then(|folders| folders.clone()) {
+        return folders;
+    }
+
+    let folders: Vec<LinkedFolder> = read_config_value()
+        .get("linked_folders")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    if let Ok(mut cached) = FOLDERS.lock() {
+        *cached = Some(folders.clone());
+    }
+    folders
+}
+
+fn save_linked_folders(folders: &[LinkedFolder]) -> Result<(), String> {
+    update_config(serde_json::json!({ "linked_folders": folders }))?;
+    if let Ok(mut cached) = FOLDERS.lock() {
+        *cached = Some(folders.to_vec());
+    }
+    invalidate_images();
+    Ok(())
+}
+
+/// 清空图片缓存，下次读取时重新扫描
+fn invalidate_images() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Ok(mut images) = IMAGES.lock() {
+        *images = None;
+    }
+}
+
+fn is_available(folder: &LinkedFolder) -> bool {
+    Path::new(&folder.path).is_dir()
+}
+
+/// 文件夹中的图片，优先使用缓存
+fn folder_images(folder: &LinkedFolder) -> Vec<String> {
+    let cached = IMAGES
+        .lock()
+        .ok()
+        .and_then(|images| images.as_ref()?.get(&folder.path).cloned());
+    if let Some(cached) = cached {
+        return cached;
+    }
+
+    let generation = GENERATION.load(Ordering::SeqCst);
+    let images: Vec<String> = collect_images(
+        Path::new(&folder.path),
+        folder.recursive,
+        &ImportOptions::default(),
+        None,
+        None,
+    )
+    .into_iter()
+    .map(|file| file.to_string_lossy().to_string())
+    .collect();
+
+    if let Ok(mut cache) = IMAGES.lock() {
+        if GENERATION.load(Ordering::SeqCst) == generation {
+            cache
+                .get_or_insert_with(HashMap::new)
+                .insert(folder.path.clone(), images.clone());
+        }
+    }
+    images
+}
+
+/// 所有可用关联文件夹中的图片，不可用的文件夹直接跳过
+pub fn linked_folder_images() -> Vec<String> {
+    read_linked_folders()
+        .iter()
+        .filter(|folder| is_available(folder))
+        .flat_map(folder_images)
+        .collect()
+}
+
+/// 路径是否位于关联文件夹中（此类文件只读）
+pub fn is_linked_path(path: &str) -> bool {
+    let path = Path::new(path);
+    read_linked_folders()
+        .iter()
+        .any(|folder| path.starts_with(&folder.path))
+}
+
+/// 路径是否位于当前不可用的关联文件夹中，索引应保留其条目
+pub fn is_in_unavailable_folder(path: &str) -> bool {
+    let path = Path::new(path);
+    read_linked_folders()
+        .iter()
+        .any(|folder| path.starts_with(&folder.path) && !is_available(folder))
+}
+
+/// 按当前可用的关联文件夹重建文件监听
+fn rewatch() {
+    let mut watcher = match notify::recommended_watcher(|event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            if event.kind.is_create() || event.kind.is_remove() || event.kind.is_modify() {
+                DIRTY.store(true, Ordering::Relaxed);
+            }
+        }
+    }) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("Linked folders: failed to create watcher: {}", e);
+            return;
+        }
+    };
+
+    for folder in read_linked_folders().iter().filter(|f| is_available(f)) {
+        let mode = if folder.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        if let Err(e) = watcher.watch(Path::new(&folder.path), mode) {
+            eprintln!("Linked folders: failed to watch {}: {}", folder.path, e);
+        }
+    }
+
+    if let Ok(mut current) = WATCHER.lock() {
+        *current = Some(watcher);
+    }
+}
+
+/// 刷新索引并通知前端，返回最新的文件夹状态
+///
+/// 同步索引会计算新图片的哈希，放到阻塞线程池中执行，不占用主线程和异步运行时。
+async fn refresh(app: &tauri::AppHandle) -> Result<Vec<LinkedFolderStatus>, String> {
+    let folders = tauri::async_runtime::spawn_blocking(|| {
+        if let Err(e) = sync_library() {
+            eprintln!("Linked folders: failed to sync library: {}", e);
+        }
+        linked_folder_statuses()
+    })
+    .await
+    .map_err(|e| format!("Failed to refresh linked folders: {}", e))?;
+    let _ = app.emit("linked-folders-changed", &folders);
+    Ok(folders)
+}
+
+fn available_paths() -> Vec<String> {
+    read_linked_folders()
+        .into_iter()
+        .filter(is_available)
+        .map(|folder| folder.path)
+        .collect()
+}
+
+/// 列出关联文件夹及其状态
+///
+/// 检查网络盘是否可用可能很慢，放到阻塞线程池中执行，不阻塞界面。
+#[tauri::command]
+pub async fn list_linked_folders() -> Result<Vec<LinkedFolderStatus>, String> {
+    tauri::async_runtime::spawn_blocking(linked_folder_statuses)
+        .await
+        .map_err(|e| format!("Failed to list linked folders: {}", e))
+}
+
+fn linked_folder_statuses() -> Vec<LinkedFolderStatus> {
+    read_linked_folders()
+        .into_iter()
+        .map(|folder| {
+            let available = is_available(&folder);
+            let count = if available {
+                folder_images(&folder).len()
+            } else {
+                0
+            };
+            LinkedFolderStatus {
+                path: folder.path,
+                recursive: folder.recursive,
+                available,
+                count,
+            }
+        })
+        .collect()
+}
+
+/// 添加关联文件夹
+#[tauri::command]
+pub async fn add_linked_folder(
+    app: tauri::AppHandle,
+    path: String,
+    recursive: Option<bool>,
+) -> Result<Vec<LinkedFolderStatus>, String> {
+    let folder = PathBuf::from(&path)
+        .canonicalize()
+        .map_err(|e| format!("Folder not found: {} ({})", path, e))?;
+    if !folder.is_dir() {
+        return Err(format!("Not a folder: {}", path));
+    }
+
+    let base_dir = get_appdata_dir()?;
+    if folder.starts_with(&base_dir) || base_dir.starts_with(&folder) {
+        return Err("Cannot link the oPaper data directory".to_string());
+    }
+
+    let folder = folder.to_string_lossy().to_string();
+    let mut folders = read_linked_folders();
+    if folders
+        .iter()
+        .any(|f| Path::new(&folder).starts_with(&f.path) || Path::new(&f.path).starts_with(&folder))
+    {
+        return Err(format!(
+            "Folder overlaps an existing linked folder: {}",
+            folder
+        ));
+    }
+
+    folders.push(LinkedFolder {
+        path: folder,
+        recursive: recursive.unwrap_or(true),
+    });
+    save_linked_folders(&folders)?;
+
+    rewatch();
+    refresh(&app).await
+}
+
+/// 移除关联文件夹（不会删除其中的文件）
+#[tauri::command]
+pub async fn remove_linked_folder(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<Vec<LinkedFolderStatus>, String> {
+    let mut folders = read_linked_folders();
+    let before = folders.len();
+    folders.retain(|folder| folder.path != path);
+    if folders.len() == before {
+        return Err(format!("Linked folder not found: {}", path));
+    }
+    save_linked_folders(&folders)?;
+
+    rewatch();
+    refresh(&app).await
+}
+
+/// 启动关联文件夹监听：文件变化时刷新索引，并定期检查文件夹是否可用
+pub fn start_linked_folder_watcher(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        rewatch();
+        if let Ok(mut available) = AVAILABLE.lock() {
+            *available = available_paths();
+        }
+
+        let mut elapsed = 0;
+        loop {
+            tokio::time::sleep(Duration::from_secs(DEBOUNCE_SECS)).await;
+            elapsed += DEBOUNCE_SECS;
+            let _writing = background_write_guard().await;
+
+            let mut changed = DIRTY.swap(false, Ordering::Relaxed);
+            if changed {
+                invalidate_images();
+            }
+
+            if elapsed >= AVAILABILITY_CHECK_SECS {
+                elapsed = 0;
+                // 配置可能被整体覆盖，定期重新读取
+                if let Ok(mut folders) = FOLDERS.lock() {
+                    *folders = None;
+                }
+                let current = available_paths();
+                let previous = AVAILABLE
+                    .lock()
+                    .map(|mut available| std::mem::replace(&mut *available, current.clone()))
+                    .unwrap_or_default();
+
+                if previous != current {
+                    println!("Linked folders: availability changed: {:?}", current);
+                    // 重新挂载后需要重新监听
+                    rewatch();
+                    invalidate_images();
+                    changed = true;
+                }
+            }
+
+            if changed {
+                if let Err(e) = refresh(&app).await {
+                    eprintln!("Linked folders: {}", e);
+                }
+            }
+        }
+    });
+}
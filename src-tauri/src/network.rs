@@ -0,0 +1,224 @@
+// 网络设置模块：代理、根证书、User-Agent 与超时，所有模块共用同一个 HTTP 客户端
+//
+// 配置示例（config.json）：
+// "network": {
+//   "proxy": "socks5h://127.0.0.1:1080",   // 支持 http / https / socks5 / socks5h，为空时使用系统代理环境变量
+//   "no_proxy": ["localhost", "*.corp.local", "10.0.0.0/8"],
+//   "ca_certs": ["C:/certs/corp-root.pem"], // 额外信任的根证书（PEM 或 DER）
+//   "user_agent": "",                      // 为空时使用默认值
+//   "timeout_secs": 60,                    // 单个请求的总超时（下载按读取超时计算，不受此限制）
+//   "connect_timeout_secs": 15,
+//   "connectivity_url": ""                 // 连通性检测地址，为空时使用默认地址（见 connectivity）
+// }
+//
+// 设置读取后缓存在内存中，配置写入时失效；设置变化后下次获取客户端时自动重建。
+// 根证书文件缺失或无效时跳过该证书并记录日志，不影响其他请求。
+
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{read_config_value, update_config};
+
+const DEFAULT_USER_AGENT: &str = concat!("oPaper/", env!("CARGO_PKG_VERSION"));
+
+/// 缓存的网络设置，配置写入时清空
+static SETTINGS: Mutex<Option<NetworkSettings>> = Mutex::new(None);
+
+/// 共享客户端及构建它们时使用的设置
+static CLIENT: Mutex<Option<SharedClients>> = Mutex::new(None);
+
+struct SharedClients {
+    settings: NetworkSettings,
+    client: reqwest::Client,
+    /// 不跟随重定向，用于受访问策略限制的请求
+    no_redirect: reqwest::Client,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    pub proxy: Option<String>,
+    pub no_proxy: Vec<String>,
+    pub ca_certs: Vec<String>,
+    pub user_agent: Option<String>,
+    pub timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    pub connectivity_url: Option<String>,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        NetworkSettings {
+            proxy: None,
+            no_proxy: Vec::new(),
+            ca_certs: Vec::new(),
+            user_agent: None,
+            timeout_secs: 60,
+            connect_timeout_secs: 15,
+            connectivity_url: None,
+        }
+    }
+}
+
+pub fn read_network_settings() -> NetworkSettings {
+    if let Ok(cached) = SETTINGS.lock() {
+        if let Some(settings) = cached.as_ref() {
+            return settings.clone();
+        }
+    }
+    let settings: NetworkSettings = read_config_value()
+        .get("network")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    if let Ok(mut cached) = SETTINGS.lock() {
+        *cached = Some(settings.clone());
+    }
+    settings
+}
+
+/// 配置已改变，下次使用时重新读取网络设置
+pub fn invalidate_network_settings() {
+    if let Ok(mut cached) = SETTINGS.lock() {
+        *cached = None;
+    }
+}
+
+fn load_certificate(path: &str) -> Result<reqwest::Certificate, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read certificate {}: {}", path, e))?;
+    // 按内容判断格式，PEM 以 "-----BEGIN" 开头
+    let certificate = if bytes.starts_with(b"-----BEGIN") {
+        reqwest::Certificate::from_pem(&bytes)
+    } else {
+        reqwest::Certificate::from_der(&bytes)
+    };
+    certificate.map_err(|e| format!("Invalid certificate {}: {}", path, e))
+}
+
+/// 按设置创建客户端构建器；`strict` 为 false 时跳过无法加载的根证书（只记录日志）
+fn client_builder(
+    settings: &NetworkSettings,
+    redirect: bool,
+    strict: bool,
+) -> Result<reqwest::ClientBuilder, String> {
+    let user_agent = settings
+        .user_agent
+        .as_deref()
+        .filter(|ua| !ua.trim().is_empty())
+        .unwrap_or(DEFAULT_USER_AGENT);
+
+    let mut builder = reqwest::Client::builder()
+        .user_agent(user_agent)
+        .connect_timeout(Duration::from_secs(settings.connect_timeout_secs.max(1)));
+
+    if let Some(proxy) = settings.proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+        let mut proxy = reqwest::Proxy::all(proxy.trim())
+            .map_err(|e| format!("Invalid proxy {}: {}", proxy, e))?;
+        if !settings.no_proxy.is_empty() {
+            proxy = proxy.no_proxy(reqwest::NoProxy::from_string(&settings.no_proxy.join(",")));
+        }
+        // 指定代理时不再读取系统代理环境变量
+        builder = builder.no_proxy().proxy(proxy);
+    }
+
+    for path in &settings.ca_certs {
+        match load_certificate(path) {
+            Ok(certificate) => builder = builder.add_root_certificate(certificate),
+            Err(e) if strict => return Err(e),
+            Err(e) => eprintln!("{}, ignoring it", e),
+        }
+    }
+
+    if !redirect {
+        builder = builder.redirect(reqwest::redirect::Policy::none());
+    }
+
+    Ok(builder)
+}
+
+fn build_client(
+    settings: &NetworkSettings,
+    redirect: bool,
+    strict: bool,
+) -> Result<reqwest::Client, String> {
+    client_builder(settings, redirect, strict)?
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+fn build_clients(settings: NetworkSettings, strict: bool) -> Result<SharedClients, String> {
+    Ok(SharedClients {
+        client: build_client(&settings, true, strict)?,
+        no_redirect: build_client(&settings, false, strict)?,
+        settings,
+    })
+}
+
+fn shared_client(redirect: bool) -> Result<reqwest::Client, String> {
+    let settings = read_network_settings();
+    let mut clients = CLIENT
+        .lock()
+        .map_err(|_| "HTTP client is locked".to_string())?;
+    if clients
+        .as_ref()
+        .is_none_or(|clients| clients.settings != settings)
+    {
+        // 证书文件可能被移动或删除，不能因此让所有请求失败
+        *clients = Some(build_clients(settings, false)?);
+    }
+    let clients = clients.as_ref().ok_or("HTTP client is unavailable")?;
+    Ok(if redirect {
+        clients.client.clone()
+    } else {
+        clients.no_redirect.clone()
+    })
+}
+
+/// 共享 HTTP 客户端（复用连接池），网络设置变化后重建
+pub fn http_client() -> Result<reqwest::Client, String> {
+    shared_client(true)
+}
+
+/// 不跟随重定向的共享客户端，重定向目标需要重新经过访问策略检查
+pub fn http_client_without_redirects() -> Result<reqwest::Client, String> {
+    shared_client(false)
+}
+
+/// 不跟随重定向、将 `host` 固定解析到 `addrs` 的客户端
+///
+/// 用于已按解析结果检查过的受限请求，避免发送时再次解析得到不同的地址。
+/// 每次新建，不共用连接池。
+pub fn pinned_client(host: &str, addrs: &[SocketAddr]) -> Result<reqwest::Client, String> {
+    client_builder(&read_network_settings(), false, false)?
+        .resolve_to_addrs(host, addrs)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// 单个请求的总超时
+pub fn request_timeout() -> Duration {
+    Duration::from_secs(read_network_settings().timeout_secs.max(1))
+}
+
+/// 读取网络设置
+#[tauri::command]
+pub fn get_network_settings() -> NetworkSettings {
+    read_network_settings()
+}
+
+/// 保存网络设置；先用新设置构建客户端，代理地址或证书无效时不保存
+#[tauri::command]
+pub fn set_network_settings(settings: NetworkSettings) -> Result<NetworkSettings, String> {
+    let clients = build_clients(settings.clone(), true)?;
+    let value = serde_json::to_value(&settings)
+        .map_err(|e| format!("Failed to serialize network settings: {}", e))?;
+    update_config(serde_json::json!({ "network": value }))?;
+
+    if let Ok(mut cached) = CLIENT.lock() {
+        *cached = Some(clients);
+    }
+    Ok(settings)
+}
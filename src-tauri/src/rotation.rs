@@ -0,0 +1,253 @@
+// 壁纸轮播模块：静态壁纸页「开启循环」并选择本地来源时，由后台按间隔从壁纸库（或保存的搜索）中切换壁纸
+//
+// 配置示例（config.json，loop / loop_mode 与静态壁纸页共用）：
+// "loop": true,
+// "loop_mode": "local",           // local 由后台轮播；cloud 由静态壁纸页从云端获取
+// "loop_interval_minutes": 30,
+// "loop_search": "风景",          // 保存的搜索名称，优先于 loop_query
+// "loop_query": "tag:自然",       // 直接使用查询语句
+// "loop_shuffle": true,
+// "loop_weather": true            // 优先选择标签与当前天气相符的壁纸
+// 两者都为空时轮播全部静态壁纸，搜索结果中也只轮播静态壁纸。
+// 按天气选择时使用 rain / snow / clear / cloudy（或 雨 / 雪 / 晴 / 多云）标签，
+// 没有相符的壁纸时使用全部候选；天气状况变化后立即切换。
+// 云端来源离线时，静态壁纸页改为从本地壁纸中切换。
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rand::seq::SliceRandom;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::config::read_config_value;
+use crate::data_dir::background_write_guard;
+use crate::library::{load_library, LibraryItem};
+use crate::library_search::{saved_search_items, search_items};
+use crate::wallpaper_switch::{apply_wallpaper_target, WallpaperTarget};
+use crate::weather::current_condition;
+
+/// 后台检查间隔
+const CHECK_INTERVAL_SECS: u64 = 30;
+
+/// 轮播配置，读取 config.json 顶层的 loop 相关字段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RotationConfig {
+    #[serde(rename = "loop")]
+    pub enabled: bool,
+    /// local / cloud
+    #[serde(rename = "loop_mode")]
+    pub mode: String,
+    #[serde(rename = "loop_interval_minutes")]
+    pub interval_minutes: u64,
+    #[serde(rename = "loop_search")]
+    pub search: Option<String>,
+    #[serde(rename = "loop_query")]
+    pub query: Option<String>,
+    #[serde(rename = "loop_shuffle")]
+    pub shuffle: bool,
+    #[serde(rename = "loop_weather")]
+    pub weather: bool,
+}
+
+impl Default for RotationConfig {
+    fn default() -> Self {
+        RotationConfig {
+            enabled: false,
+            mode: "local".to_string(),
+            interval_minutes: 30,
+            search: None,
+            query: None,
+            shuffle: true,
+            weather: false,
+        }
+    }
+}
+
+struct RotationState {
+    last_switch: Option<Instant>,
+    /// 顺序轮播时下一张的位置
+    position: usize,
+    current_path: Option<String>,
+}
+
+static STATE: Mutex<RotationState> = Mutex::new(RotationState {
+    last_switch: None,
+    position: 0,
+    current_path: None,
+});
+
+/// 逐个读取 loop 相关字段，某个字段类型错误时只有该字段使用默认值
+pub fn read_rotation_config() -> RotationConfig {
+    let value = read_config_value();
+    let defaults = RotationConfig::default();
+    RotationConfig {
+        enabled: config_field(&value, "loop", defaults.enabled),
+        mode: config_field(&value, "loop_mode", defaults.mode),
+        interval_minutes: config_field(&value, "loop_interval_minutes", defaults.interval_minutes),
+        search: config_field(&value, "loop_search", defaults.search),
+        query: config_field(&value, "loop_query", defaults.query),
+        shuffle: config_field(&value, "loop_shuffle", defaults.shuffle),
+        weather: config_field(&value, "loop_weather", defaults.weather),
+    }
+}
+
+fn config_field<T: DeserializeOwned>(config: &serde_json::Value, key: &str, default: T) -> T {
+    match config.get(key) {
+        None => default,
+        Some(value) => serde_json::from_value(value.clone()).unwrap_or_else(|e| {
+            eprintln!("Ignoring invalid config field {}: {}", key, e);
+            default
+        }),
+    }
+}
+
+/// 轮播候选壁纸
+pub fn rotation_candidates(config: &RotationConfig) -> Result<Vec<LibraryItem>, String> {
+    let mut items = match (&config.search, &config.query) {
+        (Some(search), _) if !search.is_empty() => saved_search_items(search)?,
+        (_, Some(query)) if !query.is_empty() => search_items(query)?,
+        _ => load_library()?.items,
+    };
+
+    // 只轮播静态壁纸，跳过暂时不可用（关联文件夹断开）的壁纸
+    items.retain(|item| item.kind == "static" && item.available);
+
+    if config.weather {
+        if let Some(condition) = current_condition() {
+            let matched: Vec<LibraryItem> = items
+                .iter()
+                .filter(|item| item.tags.iter().any(|tag| condition.matches_tag(tag)))
+                .cloned()
+                .collect();
+            if !matched.is_empty() {
+                items = matched;
+            }
+        }
+    }
+
+    // 顺序轮播需要稳定的顺序
+    items.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(items)
+}
+
+/// 从候选中选出下一张（尽量不与当前壁纸重复）
+fn pick_next(config: &RotationConfig, candidates: &[LibraryItem]) -> Option<LibraryItem> {
+    let mut state = STATE.lock().ok()?;
+
+    let picked = if config.shuffle {
+        let others: Vec<&LibraryItem> = candidates
+            .iter()
+            .filter(|item| Some(&item.path) != state.current_path.as_ref())
+            .collect();
+        let pool = if others.is_empty() {
+            candidates.iter().collect()
+        } else {
+            others
+        };
+        pool.choose(&mut rand::thread_rng())
+            .map(|item| (*item).clone())
+    } else {
+        let item = candidates
+            .get(state.position % candidates.len().max(1))
+            .cloned();
+        state.position = (state.position + 1) % candidates.len().max(1);
+        item
+    };
+
+    if let Some(item) = &picked {
+        state.current_path = Some(item.path.clone());
+    }
+    picked
+}
+
+/// 切换到下一张壁纸
+async fn rotate(app: &tauri::AppHandle, config: &RotationConfig) -> Result<LibraryItem, String> {
+    let candidates = rotation_candidates(config)?;
+    let item = pick_next(config, &candidates)
+        .ok_or_else(|| "No wallpaper available for rotation".to_string())?;
+
+    let target = WallpaperTarget {
+        kind: item.kind.clone(),
+        path: item.path.clone(),
+    };
+    apply_wallpaper_target(app, &target).await?;
+
+    if let Ok(mut state) = STATE.lock() {
+        state.last_switch = Some(Instant::now());
+    }
+    let _ = app.emit("rotation-changed", item.clone());
+    println!("Rotation: switched wallpaper to {}", item.path);
+
+    Ok(item)
+}
+
+/// 天气状况变化：按天气轮播时在下一次检查时切换
+pub fn weather_changed() {
+    if !read_rotation_config().weather {
+        return;
+    }
+    if let Ok(mut state) = STATE.lock() {
+        state.last_switch = None;
+    }
+}
+
+/// 立即切换到下一张轮播壁纸
+#[tauri::command]
+pub async fn rotate_wallpaper_now(app: tauri::AppHandle) -> Result<LibraryItem, String> {
+    rotate(&app, &read_rotation_config()).await
+}
+
+/// 预览当前轮播配置下的候选壁纸
+#[tauri::command]
+pub fn get_rotation_candidates() -> Result<Vec<LibraryItem>, String> {
+    rotation_candidates(&read_rotation_config())
+}
+
+/// 启动后台轮播任务
+pub fn start_rotation_loop(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let writing = background_write_guard().await;
+            let config = read_rotation_config();
+            // 日出日落定时启用时由定时任务负责切换
+            let schedule_enabled = read_config_value()
+                .pointer("/schedule/enabled")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+
+            // 云端来源由静态壁纸页负责；切换到着色器 / HTML 壁纸后暂停
+            let static_mode = read_config_value()
+                .get("mode")
+                .and_then(|v| v.as_str())
+                .is_none_or(|mode| mode == "static");
+
+            if config.enabled && config.mode == "local" && static_mode && !schedule_enabled {
+                let interval = Duration::from_secs(config.interval_minutes.max(1) * 60);
+                let due = STATE
+                    .lock()
+                    .map(|state| {
+                        state
+                            .last_switch
+                            .is_none_or(|last| last.elapsed() >= interval)
+                    })
+                    .unwrap_or(false);
+
+                if due {
+                    if let Err(e) = rotate(&app, &config).await {
+                        eprintln!("Rotation: failed to switch wallpaper: {}", e);
+                        // 失败后等待下一个间隔再重试，避免频繁报错
+                        if let Ok(mut state) = STATE.lock() {
+                            state.last_switch = Some(Instant::now());
+                        }
+                    }
+                }
+            }
+            drop(writing);
+
+            tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+        }
+    });
+}
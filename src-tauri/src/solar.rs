@@ -0,0 +1,177 @@
+// 太阳位置计算模块（离线日出日落算法）
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 日出日落时太阳中心的高度角（含大气折射与太阳视半径）
+const SUNRISE_ALTITUDE: f64 = -0.833;
+/// 民用晨昏蒙影的太阳高度角
+const CIVIL_TWILIGHT_ALTITUDE: f64 = -6.0;
+
+/// J2000.0 历元对应的儒略日
+const J2000: f64 = 2451545.0;
+/// Unix 纪元（1970-01-01T00:00:00Z）对应的儒略日
+const UNIX_EPOCH_JD: f64 = 2440587.5;
+
+/// 一天中的太阳时段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SolarPhase {
+    /// 黎明：民用晨光始 ~ 日出
+    Dawn,
+    /// 白天：日出 ~ 日落
+    Day,
+    /// 黄昏：日落 ~ 民用昏影终
+    Dusk,
+    /// 夜晚
+    Night,
+}
+
+impl SolarPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SolarPhase::Dawn => "dawn",
+            SolarPhase::Day => "day",
+            SolarPhase::Dusk => "dusk",
+            SolarPhase::Night => "night",
+        }
+    }
+}
+
+/// 某一天的太阳事件时间（本地时区）
+///
+/// 极昼或极夜时对应的事件不存在，字段为 `None`。
+#[derive(Debug, Clone)]
+pub struct SolarTimes {
+    pub date: String,
+    pub dawn: Option<DateTime<Local>>,
+    pub sunrise: Option<DateTime<Local>>,
+    pub solar_noon: DateTime<Local>,
+    pub sunset: Option<DateTime<Local>>,
+    pub dusk: Option<DateTime<Local>>,
+    pub polar_day: bool,
+    pub polar_night: bool,
+}
+
+impl SolarTimes {
+    /// 计算给定时刻所处的太阳时段
+    pub fn phase_at(&self, time: DateTime<Local>) -> SolarPhase {
+        match (self.sunrise, self.sunset) {
+            (Some(sunrise), Some(sunset)) => {
+                if time >= sunrise && time < sunset {
+                    return SolarPhase::Day;
+                }
+                if time < sunrise {
+                    match self.dawn {
+                        Some(dawn) if time >= dawn => SolarPhase::Dawn,
+                        // 太阳整夜都不低于 -6°（白夜），日出前都算黎明
+                        None => SolarPhase::Dawn,
+                        _ => SolarPhase::Night,
+                    }
+                } else {
+                    match self.dusk {
+                        Some(dusk) if time < dusk => SolarPhase::Dusk,
+                        None => SolarPhase::Dusk,
+                        _ => SolarPhase::Night,
+                    }
+                }
+            }
+            _ if self.polar_day => SolarPhase::Day,
+            _ => match (self.dawn, self.dusk) {
+                // 极夜但仍有民用晨昏蒙影
+                (Some(dawn), Some(dusk)) if time >= dawn && time < dusk => {
+                    if time < self.solar_noon {
+                        SolarPhase::Dawn
+                    } else {
+                        SolarPhase::Dusk
+                    }
+                }
+                _ => SolarPhase::Night,
+            },
+        }
+    }
+}
+
+/// 计算指定日期、经纬度的日出日落及晨昏蒙影时间
+///
+/// 使用 NOAA 简化的日出方程，精度约为一分钟，足够用于壁纸切换。
+/// `longitude` 东经为正，`latitude` 北纬为正。
+pub fn solar_times(date: NaiveDate, latitude: f64, longitude: f64) -> SolarTimes {
+    // 以当天正午（UTC）为参考的 J2000 日数
+    let noon = date
+        .and_hms_opt(12, 0, 0)
+        .expect("12:00:00 is always a valid time")
+        .and_utc();
+    let n = (datetime_to_julian(noon) - J2000 + 0.0008).round();
+
+    // 平太阳时
+    let mean_solar_time = n - longitude / 360.0;
+
+    // 太阳平近点角
+    let mean_anomaly = (357.5291 + 0.98560028 * mean_solar_time).rem_euclid(360.0);
+    let m = mean_anomaly.to_radians();
+
+    // 中心差
+    let center = 1.9148 * m.sin() + 0.0200 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+
+    // 黄经
+    let ecliptic_longitude = (mean_anomaly + center + 180.0 + 102.9372).rem_euclid(360.0);
+    let lambda = ecliptic_longitude.to_radians();
+
+    // 太阳过中天的儒略日
+    let transit = J2000 + mean_solar_time + 0.0053 * m.sin() - 0.0069 * (2.0 * lambda).sin();
+
+    // 太阳赤纬
+    let declination = (lambda.sin() * 23.4397_f64.to_radians().sin()).asin();
+
+    let phi = latitude.to_radians();
+    let hour_angle = |altitude: f64| -> Option<f64> {
+        let cos_omega = (altitude.to_radians().sin() - phi.sin() * declination.sin())
+            / (phi.cos() * declination.cos());
+        if (-1.0..=1.0).contains(&cos_omega) {
+            Some(cos_omega.acos().to_degrees())
+        } else {
+            None
+        }
+    };
+
+    let event = |omega: Option<f64>, rising: bool| -> Option<DateTime<Local>> {
+        omega.map(|omega| {
+            let offset = omega / 360.0;
+            let jd = if rising {
+                transit - offset
+            } else {
+                transit + offset
+            };
+            julian_to_datetime(jd).with_timezone(&Local)
+        })
+    };
+
+    let sunrise_omega = hour_angle(SUNRISE_ALTITUDE);
+    let civil_omega = hour_angle(CIVIL_TWILIGHT_ALTITUDE);
+
+    // 正午太阳高度角
+    let noon_altitude = 90.0 - (latitude - declination.to_degrees()).abs();
+
+    SolarTimes {
+        date: date.format("%Y-%m-%d").to_string(),
+        dawn: event(civil_omega, true),
+        sunrise: event(sunrise_omega, true),
+        solar_noon: julian_to_datetime(transit).with_timezone(&Local),
+        sunset: event(sunrise_omega, false),
+        dusk: event(civil_omega, false),
+        polar_day: sunrise_omega.is_none() && noon_altitude > SUNRISE_ALTITUDE,
+        polar_night: sunrise_omega.is_none() && noon_altitude <= SUNRISE_ALTITUDE,
+    }
+}
+
+fn datetime_to_julian(time: DateTime<Utc>) -> f64 {
+    time.timestamp() as f64 / 86400.0 + UNIX_EPOCH_JD
+}
+
+fn julian_to_datetime(jd: f64) -> DateTime<Utc> {
+    let seconds = ((jd - UNIX_EPOCH_JD) * 86400.0).round() as i64;
+    Utc.timestamp_opt(seconds, 0)
+        .single()
+        .unwrap_or_else(Utc::now)
+}
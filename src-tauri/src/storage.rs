@@ -0,0 +1,287 @@
+// 存储管理模块：统计各区域占用、清理临时文件、按配额淘汰缓存
+//
+// 配置示例（config.json）：
+// "storage": {
+//   "temp_max_age_hours": 72,   // temp 中超过该时长且不是当前壁纸的文件会被删除
+//   "quota_mb": 2048            // 总占用上限，超出时按最近最少使用淘汰 cache 中的文件，为空表示不限制
+// }
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::read_config_value;
+use crate::data_dir::background_write_guard;
+use crate::fs_helper::{get_appdata_dir, read_file, write_file};
+
+/// 当前壁纸相关文件（相对 appdata 目录）
+const ACTIVE_FILE: &str = "active_wallpaper.json";
+/// 自动清理间隔
+const GC_INTERVAL_SECS: u64 = 60 * 60;
+
+/// 统计的区域及其目录（相对 appdata 目录）
+const AREAS: [(&str, &str); 6] = [
+    ("static", "wallpaper_static"),
+    ("shader", "wallpaper_shader"),
+    ("html", "wallpaper_html"),
+    ("temp", "temp"),
+    ("cache", "cache"),
+    ("trash", "trash"),
+];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    pub temp_max_age_hours: u64,
+    pub quota_mb: Option<u64>,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            temp_max_age_hours: 72,
+            quota_mb: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AreaUsage {
+    pub area: String,
+    pub path: String,
+    pub bytes: u64,
+    pub files: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageUsage {
+    pub areas: Vec<AreaUsage>,
+    pub total_bytes: u64,
+    pub quota_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GcReport {
+    pub temp_removed: usize,
+    pub cache_evicted: usize,
+    pub freed_bytes: u64,
+    pub usage: StorageUsage,
+}
+
+fn read_storage_config() -> StorageConfig {
+    read_config_value()
+        .get("storage")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// 记录当前壁纸用到的文件（原图、处理后的图片、转码后的图片），清理时跳过
+pub fn mark_active_wallpaper(paths: &[&str]) {
+    let mut paths: Vec<&str> = paths.to_vec();
+    paths.dedup();
+    match serde_json::to_string(&paths) {
+        Ok(content) => {
+            if let Err(e) = write_file(ACTIVE_FILE.to_string(), content) {
+                eprintln!("Failed to record active wallpaper: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Failed to record active wallpaper: {}", e),
+    }
+}
+
+fn read_active_files() -> Vec<String> {
+    read_file(ACTIVE_FILE.to_string())
+        .ok()
+        .and_then(|content| serde_json::from_str::<Vec<String>>(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 当前静态壁纸的原图路径
+pub fn active_wallpaper_source() -> Option<String> {
+    read_active_files().into_iter().next()
+}
+
+/// 当前壁纸相关文件，包括动态壁纸配置中的路径
+fn active_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = read_active_files().into_iter().map(PathBuf::from).collect();
+
+    let config = read_config_value();
+    for key in ["htmlPath", "shaderPath"] {
+        if let Some(path) = config.get(key).and_then(|v| v.as_str()) {
+            if !path.is_empty() {
+                paths.push(PathBuf::from(path));
+            }
+        }
+    }
+
+    paths
+}
+
+/// 更新文件的修改时间，缓存命中时调用，作为淘汰依据
+pub fn touch(path: &Path) {
+    if let Ok(file) = fs::File::options().write(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// 递归列出目录下的所有文件及其元数据
+fn walk_files(dir: &Path, out: &mut Vec<(PathBuf, fs::Metadata)>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => walk_files(&path, out),
+            Ok(metadata) => out.push((path, metadata)),
+            Err(_) => {}
+        }
+    }
+}
+
+fn last_used(metadata: &fs::Metadata) -> SystemTime {
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    let accessed = metadata.accessed().unwrap_or(SystemTime::UNIX_EPOCH);
+    modified.max(accessed)
+}
+
+fn compute_usage(quota_mb: Option<u64>) -> Result<StorageUsage, String> {
+    let base_dir = get_appdata_dir()?;
+
+    let areas: Vec<AreaUsage> = AREAS
+        .iter()
+        .map(|(area, dir)| {
+            let path = base_dir.join(dir);
+            let mut files = Vec::new();
+            walk_files(&path, &mut files);
+            AreaUsage {
+                area: area.to_string(),
+                path: path.to_string_lossy().to_string(),
+                bytes: files.iter().map(|(_, m)| m.len()).sum(),
+                files: files.len(),
+            }
+        })
+        .collect();
+
+    Ok(StorageUsage {
+        total_bytes: areas.iter().map(|a| a.bytes).sum(),
+        areas,
+        quota_bytes: quota_mb.map(|mb| mb * 1024 * 1024),
+    })
+}
+
+/// 删除 temp 中过期且不是当前壁纸的文件
+fn clean_temp(max_age: Duration, active: &[PathBuf]) -> Result<(usize, u64), String> {
+    let mut files = Vec::new();
+    walk_files(&get_appdata_dir()?.join("temp"), &mut files);
+
+    let now = SystemTime::now();
+    let mut removed = 0;
+    let mut freed = 0;
+    for (path, metadata) in files {
+        let age = now
+            .duration_since(last_used(&metadata))
+            .unwrap_or(Duration::ZERO);
+        if age < max_age || active.contains(&path) {
+            continue;
+        }
+        if fs::remove_file(&path).is_ok() {
+            removed += 1;
+            freed += metadata.len();
+        }
+    }
+
+    Ok((removed, freed))
+}
+
+/// 总占用超出配额时，按最近最少使用淘汰 cache 中的文件
+fn evict_cache(excess: u64, active: &[PathBuf]) -> Result<(usize, u64), String> {
+    let mut files = Vec::new();
+    walk_files(&get_appdata_dir()?.join("cache"), &mut files);
+    files.sort_by_key(|(_, metadata)| last_used(metadata));
+
+    let mut evicted = 0;
+    let mut freed = 0;
+    for (path, metadata) in files {
+        if freed >= excess {
+            break;
+        }
+        if active.contains(&path) {
+            continue;
+        }
+        if fs::remove_file(&path).is_ok() {
+            evicted += 1;
+            freed += metadata.len();
+        }
+    }
+
+    Ok((evicted, freed))
+}
+
+fn run_gc() -> Result<GcReport, String> {
+    let config = read_storage_config();
+    let active = active_paths();
+
+    let (temp_removed, temp_freed) = clean_temp(
+        Duration::from_secs(config.temp_max_age_hours * 60 * 60),
+        &active,
+    )?;
+
+    let mut cache_evicted = 0;
+    let mut cache_freed = 0;
+    let usage = compute_usage(config.quota_mb)?;
+    if let Some(quota) = usage.quota_bytes {
+        if usage.total_bytes > quota {
+            (cache_evicted, cache_freed) = evict_cache(usage.total_bytes - quota, &active)?;
+        }
+    }
+
+    Ok(GcReport {
+        temp_removed,
+        cache_evicted,
+        freed_bytes: temp_freed + cache_freed,
+        usage: if cache_evicted > 0 {
+            compute_usage(config.quota_mb)?
+        } else {
+            usage
+        },
+    })
+}
+
+/// 获取各区域的磁盘占用
+#[tauri::command]
+pub fn get_storage_usage() -> Result<StorageUsage, String> {
+    compute_usage(read_storage_config().quota_mb)
+}
+
+/// 立即清理临时文件并执行配额淘汰
+#[tauri::command]
+pub async fn run_storage_gc() -> Result<GcReport, String> {
+    tokio::task::spawn_blocking(run_gc)
+        .await
+        .map_err(|e| format!("Storage task failed: {}", e))?
+}
+
+/// 启动定期存储清理任务
+pub fn start_storage_gc_loop() {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let writing = background_write_guard().await;
+            match tokio::task::spawn_blocking(run_gc).await {
+                Ok(Ok(report)) if report.temp_removed + report.cache_evicted > 0 => println!(
+                    "Storage: removed {} temp files, evicted {} cache files, freed {} bytes",
+                    report.temp_removed, report.cache_evicted, report.freed_bytes
+                ),
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => eprintln!("Storage: cleanup failed: {}", e),
+                Err(e) => eprintln!("Storage: cleanup task failed: {}", e),
+            }
+            drop(writing);
+
+            tokio::time::sleep(Duration::from_secs(GC_INTERVAL_SECS)).await;
+        }
+    });
+}
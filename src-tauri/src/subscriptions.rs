@@ -0,0 +1,439 @@
+// 每日图片订阅模块：每天从在线来源下载当天的图片到壁纸库，可自动设为壁纸
+//
+// 配置示例（config.json）：
+// "subscriptions": [
+//   {
+//     "id": "bing",              // 订阅标识，为空时使用 source
+//     "source": "bing",          // 在线来源 id，见 wallpaper_source
+//     "enabled": true,
+//     "auto_apply": true,        // 下载后设为壁纸
+//     "keep_days": 7,            // 只保留最近 N 天的图片，0 表示全部保留
+//     "query": { "url": "..." }  // 传给来源的参数（可选）
+//   }
+// ]
+//
+// 每小时检查一次，当天尚未成功检查的订阅会立即执行，因此启动时会补上错过的一天。
+// 下载记录保存在 subscriptions.json 中。
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::config::read_config_value;
+use crate::connectivity::{ensure_online, is_online};
+use crate::data_dir::background_write_guard;
+use crate::dedup::import_static_file;
+use crate::fs_helper::{get_appdata_dir, read_file, write_file};
+use crate::library::{
+    item_id, load_library, remember_download_source, sync_library, update_library_item,
+    Attribution, LibraryItem,
+};
+use crate::storage::active_wallpaper_source;
+use crate::trash::move_to_trash;
+use crate::wallpaper_source::{
+    create_source, fetch_source, read_source_settings, SourceItem, SourceQuery,
+};
+use crate::wallpaper_static::{apply_static_wallpaper, download_image};
+
+/// 下载记录（相对 appdata 目录）
+const STATE_FILE: &str = "subscriptions.json";
+/// 后台检查间隔
+const CHECK_INTERVAL_SECS: u64 = 60 * 60;
+
+/// 同一时间只执行一轮检查
+static CHECKING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubscriptionConfig {
+    pub id: String,
+    pub source: String,
+    pub enabled: bool,
+    pub auto_apply: bool,
+    pub keep_days: usize,
+    pub query: SourceQuery,
+}
+
+impl Default for SubscriptionConfig {
+    fn default() -> Self {
+        SubscriptionConfig {
+            id: String::new(),
+            source: String::new(),
+            enabled: true,
+            auto_apply: false,
+            keep_days: 7,
+            query: SourceQuery::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyDownload {
+    /// 本地日期 YYYY-MM-DD
+    pub date: String,
+    /// 来源中的条目 id，用于判断是否已下载
+    pub key: String,
+    pub title: String,
+    /// 保存到库中的路径；与库中已有图片重复时为空（不参与清理）
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubscriptionState {
+    /// 最近一次成功检查的日期
+    pub last_check: Option<String>,
+    pub last_error: Option<String>,
+    pub downloads: Vec<DailyDownload>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionStatus {
+    pub config: SubscriptionConfig,
+    pub state: SubscriptionState,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SubscriptionEvent {
+    id: String,
+    item: LibraryItem,
+    applied: bool,
+}
+
+pub fn read_subscriptions() -> Vec<SubscriptionConfig> {
+    let mut subscriptions: Vec<SubscriptionConfig> = read_config_value()
+        .get("subscriptions")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    for subscription in subscriptions.iter_mut() {
+        if subscription.id.is_empty() {
+            subscription.id = subscription.source.clone();
+        }
+    }
+    subscriptions
+}
+
+fn load_states() -> HashMap<String, SubscriptionState> {
+    read_file(STATE_FILE.to_string())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_states(states: &HashMap<String, SubscriptionState>) -> Result<(), String> {
+    let content = serde_json::to_string(states)
+        .map_err(|e| format!("Failed to serialize subscriptions: {}", e))?;
+    write_file(STATE_FILE.to_string(), content)?;
+    Ok(())
+}
+
+fn update_state<F: FnOnce(&mut SubscriptionState)>(id: &str, update: F) {
+    let mut states = load_states();
+    update(states.entry(id.to_string()).or_default());
+    if let Err(e) = save_states(&states) {
+        eprintln!("Subscription: failed to save state: {}", e);
+    }
+}
+
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// 文件名中只保留安全字符
+fn file_prefix(id: &str) -> String {
+    let prefix: String = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if prefix.is_empty() {
+        "daily".to_string()
+    } else {
+        prefix
+    }
+}
+
+/// 下载图片并导入库，写入署名信息；返回库中的路径及是否为新文件
+async fn download_to_library(
+    app: &tauri::AppHandle,
+    subscription: &SubscriptionConfig,
+    source_item: &SourceItem,
+    date: &str,
+) -> Result<(String, bool), String> {
+    let file_stem = format!("{}_{}", file_prefix(&subscription.id), date);
+    let temp_path = download_image(app, &source_item.url, &file_stem).await?;
+    remember_download_source(&temp_path, &source_item.url);
+
+    let resource_dir = get_appdata_dir()?.join("wallpaper_static");
+    std::fs::create_dir_all(&resource_dir)
+        .map_err(|e| format!("Failed to create resource directory: {}", e))?;
+    let outcome = import_static_file(&temp_path, &resource_dir);
+    let _ = std::fs::remove_file(&temp_path);
+    let outcome = outcome?;
+
+    let attribution = Attribution {
+        provider: source_item.source.clone(),
+        title: source_item.title.clone(),
+        author: source_item.author.clone(),
+        license: source_item.license.clone(),
+        page_url: source_item.page_url.clone(),
+    };
+    // 与库中已有图片重复时不覆盖用户的标题与已有署名
+    update_library_item(&item_id("static", Path::new(&outcome.path)), |item| {
+        if outcome.duplicate && item.attribution.is_some() {
+            return;
+        }
+        if !outcome.duplicate && !source_item.title.is_empty() {
+            item.title = source_item.title.clone();
+        }
+        item.attribution = Some(attribution);
+    })?;
+
+    Ok((outcome.path, !outcome.duplicate))
+}
+
+/// 只保留最近 keep_days 天（含当天）下载的图片，收藏的图片与当前壁纸不会被清理
+///
+/// 按日期而不是按条数清理：来源某天没有图片或下载了同一天的多张图片都不影响保留的天数。
+fn prune(subscription: &SubscriptionConfig) {
+    if subscription.keep_days == 0 {
+        return;
+    }
+
+    let mut states = load_states();
+    let state = match states.get_mut(&subscription.id) {
+        Some(state) => state,
+        None => return,
+    };
+    // 日期格式为 YYYY-MM-DD，可直接按字符串比较
+    let cutoff = (chrono::Local::now().date_naive()
+        - chrono::Duration::days(subscription.keep_days as i64 - 1))
+    .format("%Y-%m-%d")
+    .to_string();
+    let (expired, kept): (Vec<DailyDownload>, Vec<DailyDownload>) = state
+        .downloads
+        .drain(..)
+        .partition(|download| download.date < cutoff);
+    state.downloads = kept;
+    if expired.is_empty() {
+        return;
+    }
+
+    let favorites: Vec<String> = load_library()
+        .map(|index| {
+            index
+                .items
+                .into_iter()
+                .filter(|item| item.favorite)
+                .map(|item| item.path)
+                .collect()
+        })
+        .unwrap_or_default();
+    let active = active_wallpaper_source();
+
+    for download in expired {
+        let path = match download.path {
+            Some(path) => path,
+            None => continue,
+        };
+        if favorites.contains(&path) || active.as_ref() == Some(&path) {
+            continue;
+        }
+        if Path::new(&path).exists() {
+            match move_to_trash("static", Path::new(&path)) {
+                Ok(_) => println!("Subscription: removed expired image {}", path),
+                Err(e) => eprintln!("Subscription: failed to remove {}: {}", path, e),
+            }
+        }
+    }
+
+    if let Err(e) = save_states(&states) {
+        eprintln!("Subscription: failed to save state: {}", e);
+    }
+}
+
+/// 执行单个订阅：获取当天的图片，尚未下载时导入库并按配置设为壁纸
+async fn run_subscription(
+    app: &tauri::AppHandle,
+    subscription: &SubscriptionConfig,
+) -> Result<(), String> {
+    let date = today();
+    let source = create_source(
+        &subscription.source,
+        read_source_settings(&subscription.source),
+    )?;
+    let mut query = subscription.query.clone();
+    query.count = Some(1);
+
+    let items = fetch_source(source.as_ref(), &query).await?;
+    // 当天没有图片（如 APOD 当天是视频）也算检查成功，避免每小时重试
+    let source_item = match items.into_iter().next() {
+        Some(item) => item,
+        None => {
+            println!(
+                "Subscription {}: no image from {} today",
+                subscription.id, subscription.source
+            );
+            update_state(&subscription.id, |state| {
+                state.last_check = Some(date);
+                state.last_error = None;
+            });
+            prune(subscription);
+            return Ok(());
+        }
+    };
+
+    let already = load_states()
+        .get(&subscription.id)
+        .map(|state| state.downloads.iter().any(|d| d.key == source_item.id))
+        .unwrap_or(false);
+    if already {
+        update_state(&subscription.id, |state| {
+            state.last_check = Some(date);
+            state.last_error = None;
+        });
+        return Ok(());
+    }
+
+    let (path, created) = download_to_library(app, subscription, &source_item, &date).await?;
+    update_state(&subscription.id, |state| {
+        state.last_check = Some(date.clone());
+        state.last_error = None;
+        state.downloads.push(DailyDownload {
+            date: date.clone(),
+            key: source_item.id.clone(),
+            title: source_item.title.clone(),
+            path: created.then(|| path.clone()),
+        });
+    });
+    println!(
+        "Subscription {}: downloaded {} to {}",
+        subscription.id, source_item.url, path
+    );
+
+    let mut applied = false;
+    if subscription.auto_apply {
+        match apply_static_wallpaper(app, &path).await {
+            Ok(()) => applied = true,
+            Err(e) => eprintln!("Subscription {}: failed to apply: {}", subscription.id, e),
+        }
+    }
+
+    prune(subscription);
+
+    let item = sync_library()?
+        .items
+        .into_iter()
+        .find(|item| item.path == path);
+    if let Some(item) = item {
+        let _ = app.emit(
+            "subscription-updated",
+            SubscriptionEvent {
+                id: subscription.id.clone(),
+                item,
+                applied,
+            },
+        );
+    }
+
+    Ok(())
+}
+
+/// 检查订阅，`force` 为 false 时跳过当天已成功检查的订阅
+async fn check_subscriptions(app: &tauri::AppHandle, id: Option<&str>, force: bool) {
+    // 离线时跳过，恢复在线后补做
+    if !is_online() {
+        return;
+    }
+    if CHECKING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let date = today();
+    let states = load_states();
+    for subscription in read_subscriptions() {
+        if let Some(id) = id {
+            if subscription.id != id {
+                continue;
+            }
+        } else if !subscription.enabled {
+            continue;
+        }
+
+        let checked = states
+            .get(&subscription.id)
+            .and_then(|state| state.last_check.as_ref())
+            == Some(&date);
+        if checked && !force {
+            continue;
+        }
+
+        if let Err(e) = run_subscription(app, &subscription).await {
+            eprintln!("Subscription {}: {}", subscription.id, e);
+            // 不记录 last_check，下次检查时重试
+            update_state(&subscription.id, |state| state.last_error = Some(e));
+        }
+    }
+
+    CHECKING.store(false, Ordering::SeqCst);
+}
+
+/// 恢复在线后补做当天未完成的订阅
+pub async fn catch_up_subscriptions(app: &tauri::AppHandle) {
+    check_subscriptions(app, None, false).await;
+}
+
+fn subscription_statuses() -> Vec<SubscriptionStatus> {
+    let mut states = load_states();
+    read_subscriptions()
+        .into_iter()
+        .map(|config| SubscriptionStatus {
+            state: states.remove(&config.id).unwrap_or_default(),
+            config,
+        })
+        .collect()
+}
+
+/// 列出订阅及其下载记录
+#[tauri::command]
+pub fn list_subscriptions() -> Vec<SubscriptionStatus> {
+    subscription_statuses()
+}
+
+/// 立即检查订阅（`id` 为空时检查全部已启用的订阅），忽略当天是否已检查
+#[tauri::command]
+pub async fn check_subscriptions_now(
+    app: tauri::AppHandle,
+    id: Option<String>,
+) -> Result<Vec<SubscriptionStatus>, String> {
+    ensure_online()?;
+    if CHECKING.load(Ordering::SeqCst) {
+        return Err("Subscriptions are being checked".to_string());
+    }
+    // 与后台检查一样，迁移数据目录期间等待迁移完成
+    let writing = background_write_guard().await;
+    check_subscriptions(&app, id.as_deref(), true).await;
+    drop(writing);
+    Ok(subscription_statuses())
+}
+
+/// 启动后台订阅检查任务（启动时立即检查一次）
+pub fn start_subscription_loop(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let writing = background_write_guard().await;
+            check_subscriptions(&app, None, false).await;
+            drop(writing);
+            tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+        }
+    });
+}
@@ -0,0 +1,93 @@
+// 测试用本地 HTTP 替身：按路径返回录制的响应，并记录收到的请求，供各来源的测试替换 base_url
+
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// 一条路由：(请求路径（不含查询串）, Content-Type, 响应内容)
+pub type Route = (&'static str, &'static str, String);
+
+pub struct StandIn {
+    pub base_url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl StandIn {
+    /// 在随机端口启动替身，未匹配的路径返回 404
+    pub async fn start(routes: Vec<Route>) -> StandIn {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind stand-in");
+        let base_url = format!("http://{}", listener.local_addr().expect("local addr"));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let routes = Arc::new(routes);
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let routes = routes.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let _ = respond(stream, &routes, &recorded).await;
+                });
+            }
+        });
+
+        StandIn { base_url, requests }
+    }
+
+    /// 已收到的请求头（含请求行）
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// 最近一次请求的目标（路径与查询串）
+    pub fn last_target(&self) -> String {
+        self.requests()
+            .last()
+            .and_then(|head| head.split_whitespace().nth(1).map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+async fn respond(
+    mut stream: TcpStream,
+    routes: &[Route],
+    recorded: &Mutex<Vec<String>>,
+) -> std::io::Result<()> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 4096];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+    let head = String::from_utf8_lossy(&head).to_string();
+    let target = head.split_whitespace().nth(1).unwrap_or("/").to_string();
+    let path = target.split('?').next().unwrap_or("/");
+    recorded.lock().unwrap().push(head.clone());
+
+    let response = match routes.iter().find(|(route, _, _)| *route == path) {
+        Some((_, content_type, body)) => format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            content_type,
+            body.len(),
+            body
+        ),
+        None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            .to_string(),
+    };
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// 不使用系统代理的客户端，保证请求发到本地替身
+pub fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .no_proxy()
+        .build()
+        .expect("build client")
+}
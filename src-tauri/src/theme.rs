@@ -0,0 +1,282 @@
+// 跟随系统浅色 / 深色主题切换壁纸模块
+
+#[cfg(not(target_os = "windows"))]
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::config::read_config_value;
+use crate::data_dir::background_write_guard;
+use crate::library::{find_item_id_by_path, load_library, update_library_item, LibraryItem};
+use crate::wallpaper_switch::{apply_wallpaper_target, WallpaperTarget};
+
+/// 系统主题轮询间隔
+const POLL_INTERVAL_SECS: u64 = 5;
+
+/// 最近一次检测到的系统主题
+static CURRENT_THEME: Mutex<Option<Theme>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+    Any,
+}
+
+impl Theme {
+    fn parse(value: &str) -> Result<Theme, String> {
+        match value {
+            "light" => Ok(Theme::Light),
+            "dark" => Ok(Theme::Dark),
+            "any" => Ok(Theme::Any),
+            _ => Err(format!("Invalid theme: {}", value)),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::Any => "any",
+        }
+    }
+}
+
+/// 带主题标签的壁纸
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeTag {
+    #[serde(flatten)]
+    pub wallpaper: WallpaperTarget,
+    pub theme: Theme,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SystemThemeChanged {
+    theme: Theme,
+}
+
+/// 读取所有标记了浅色 / 深色主题的壁纸
+#[tauri::command]
+pub fn read_wallpaper_themes() -> Result<Vec<ThemeTag>, String> {
+    Ok(load_library()?
+        .items
+        .into_iter()
+        .filter_map(|item| {
+            let theme = Theme::parse(&item.theme).ok()?;
+            (theme != Theme::Any).then_some(ThemeTag {
+                wallpaper: WallpaperTarget {
+                    kind: item.kind,
+                    path: item.path,
+                },
+                theme,
+            })
+        })
+        .collect())
+}
+
+/// 为壁纸设置主题标签（light / dark / any）
+#[tauri::command]
+pub fn set_wallpaper_theme(kind: String, path: String, theme: String) -> Result<(), String> {
+    let theme = Theme::parse(&theme)?;
+    if !matches!(kind.as_str(), "static" | "shader" | "html") {
+        return Err(format!("Unsupported wallpaper type: {}", kind));
+    }
+
+    let id = find_item_id_by_path(&path)?
+        .ok_or_else(|| format!("Wallpaper not found in library: {}", path))?;
+    update_library_item(&id, |item| item.theme = theme.as_str().to_string())?;
+
+    Ok(())
+}
+
+/// 获取当前系统主题
+#[tauri::command]
+pub fn get_system_theme() -> Result<Theme, String> {
+    detect_system_theme().ok_or_else(|| "Failed to detect system theme".to_string())
+}
+
+/// 立即按当前系统主题切换壁纸
+#[tauri::command]
+pub async fn apply_theme_wallpaper(app: tauri::AppHandle) -> Result<String, String> {
+    let theme = get_system_theme()?;
+    let target = pick_wallpaper_for_theme(theme)
+        .ok_or_else(|| "No wallpaper tagged for current theme".to_string())?;
+
+    apply_wallpaper_target(&app, &target).await?;
+
+    Ok(format!("Wallpaper switched to: {}", target.path))
+}
+
+/// 选出与主题匹配的壁纸：优先同主题，其次 any
+fn pick_wallpaper_for_theme(theme: Theme) -> Option<WallpaperTarget> {
+    let items: Vec<LibraryItem> = load_library()
+        .ok()?
+        .items
+        .into_iter()
+        .filter(|item| item.available)
+        .collect();
+
+    let mut candidates: Vec<&LibraryItem> = items
+        .iter()
+        .filter(|item| item.theme == theme.as_str())
+        .collect();
+    if candidates.is_empty() {
+        candidates = items
+            .iter()
+            .filter(|item| item.theme == Theme::Any.as_str())
+            .collect();
+    }
+    if candidates.is_empty() {
+        return None;
+    }
+
+    // 多个候选时按时间轮换，避免每次切换都是同一张
+    let index = chrono::Utc::now().timestamp() as usize % candidates.len();
+    let item = candidates[index];
+    Some(WallpaperTarget {
+        kind: item.kind.clone(),
+        path: item.path.clone(),
+    })
+}
+
+/// 是否跟随系统主题切换壁纸
+fn follow_system_theme() -> bool {
+    read_config_value()
+        .get("follow_system_theme")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// 启动系统主题监听任务（仅在开启 follow_system_theme 时检测系统主题）
+pub fn start_theme_watcher(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let writing = background_write_guard().await;
+            if !follow_system_theme() {
+                // 关闭期间不检测；重新开启后首次检测只记录，不立即切换
+                if let Ok(mut current) = CURRENT_THEME.lock() {
+                    *current = None;
+                }
+            } else if let Some(theme) = detect_system_theme() {
+                let previous = CURRENT_THEME
+                    .lock()
+                    .map(|mut current| current.replace(theme))
+                    .unwrap_or(None);
+
+                // 首次检测只记录，之后主题变化时才切换
+                if previous.is_some() && previous != Some(theme) {
+                    println!("Theme: system theme changed to {:?}", theme);
+                    let _ = app.emit("system-theme-changed", SystemThemeChanged { theme });
+
+                    if let Some(target) = pick_wallpaper_for_theme(theme) {
+                        if let Err(e) = apply_wallpaper_target(&app, &target).await {
+                            eprintln!("Theme: failed to switch wallpaper: {}", e);
+                        }
+                    }
+                }
+            }
+            drop(writing);
+
+            tokio::time::sleep(Duration::from_secs(POLL_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn detect_system_theme() -> Option<Theme> {
+    // GNOME 42+ 使用 color-scheme
+    let output = Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "color-scheme"])
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        let value = String::from_utf8_lossy(&output.stdout);
+        if value.contains("prefer-dark") {
+            return Some(Theme::Dark);
+        }
+        if value.contains("prefer-light") {
+            return Some(Theme::Light);
+        }
+    }
+
+    // 旧版 GNOME 以及其他桌面：根据 GTK 主题名判断
+    let output = Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "gtk-theme"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout).to_lowercase();
+    if value.contains("dark") {
+        Some(Theme::Dark)
+    } else {
+        Some(Theme::Light)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn detect_system_theme() -> Option<Theme> {
+    // 浅色模式下该键不存在，命令返回非 0
+    let output = Command::new("defaults")
+        .args(["read", "-g", "AppleInterfaceStyle"])
+        .output()
+        .ok()?;
+
+    let value = String::from_utf8_lossy(&output.stdout);
+    if output.status.success() && value.trim().eq_ignore_ascii_case("dark") {
+        Some(Theme::Dark)
+    } else {
+        Some(Theme::Light)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn detect_system_theme() -> Option<Theme> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::um::winreg::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    // 直接读取注册表，避免轮询时反复弹出 reg.exe 控制台窗口
+    let to_wide = |s: &str| -> Vec<u16> {
+        OsStr::new(s)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect()
+    };
+    let sub_key = to_wide("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize");
+    let value_name = to_wide("AppsUseLightTheme");
+
+    let mut data: DWORD = 1;
+    let mut size = std::mem::size_of::<DWORD>() as DWORD;
+
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            sub_key.as_ptr(),
+            value_name.as_ptr(),
+            RRF_RT_REG_DWORD,
+            std::ptr::null_mut(),
+            &mut data as *mut DWORD as *mut _,
+            &mut size,
+        )
+    };
+
+    if status != 0 {
+        return None;
+    }
+
+    if data == 0 {
+        Some(Theme::Dark)
+    } else {
+        Some(Theme::Light)
+    }
+}
@@ -0,0 +1,175 @@
+// 静态壁纸缩略图缓存模块
+
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use image::codecs::jpeg::JpegEncoder;
+use image::RgbImage;
+use serde::Serialize;
+use tauri::Emitter;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::fs_helper::get_appdata_dir;
+use crate::image_format::{decodable_path, file_cache_key, temp_sibling};
+use crate::image_pipeline::load_image;
+use crate::storage::touch;
+use crate::wallpaper_static::static_wallpaper_files;
+
+/// 默认缩略图边长
+const DEFAULT_THUMBNAIL_SIZE: u32 = 384;
+/// 缩略图 JPEG 质量
+const THUMBNAIL_QUALITY: u8 = 80;
+/// 同时解码生成缩略图的最大数量
+const MAX_WORKERS: usize = 4;
+
+/// 单张与批量生成共用，避免一次排入大量全尺寸解码
+static WORKERS: Semaphore = Semaphore::const_new(MAX_WORKERS);
+
+#[derive(Debug, Clone, Serialize)]
+struct ThumbnailProgress {
+    done: usize,
+    total: usize,
+    path: String,
+    thumbnail: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThumbnailSummary {
+    pub total: usize,
+    pub generated: usize,
+    pub failed: usize,
+}
+
+fn normalize_size(size: Option<u32>) -> u32 {
+    size.unwrap_or(DEFAULT_THUMBNAIL_SIZE).clamp(32, 1024)
+}
+
+/// 缩略图缓存路径：以源文件路径、大小、修改时间和边长作为键，源文件变化后自动失效
+fn thumbnail_path(source: &Path, size: u32) -> Result<PathBuf, String> {
+    let key = file_cache_key(source, &size.to_le_bytes())?;
+
+    let thumbs_dir = get_appdata_dir()?.join("cache").join("thumbs");
+    fs::create_dir_all(&thumbs_dir)
+        .map_err(|e| format!("Failed to create thumbnail directory: {}", e))?;
+
+    Ok(thumbs_dir.join(format!("{}_{}.jpg", key, size)))
+}
+
+/// 生成（或复用缓存的）缩略图，返回缩略图路径
+pub fn ensure_thumbnail(path: &str, size: u32) -> Result<String, String> {
+    let source = PathBuf::from(path);
+    if !source.exists() {
+        return Err(format!("File not found: {}", path));
+    }
+
+    let target = thumbnail_path(&source, size)?;
+    if target.exists() {
+        touch(&target);
+        return Ok(target.to_string_lossy().to_string());
+    }
+
+    let decodable = decodable_path(path)?;
+    let image = load_image(Path::new(&decodable), true)?;
+    let thumbnail = image.thumbnail(size, size).to_rgb8();
+
+    // 先写入临时文件再重命名，避免并发读取到不完整的缩略图
+    let temp = temp_sibling(&target);
+    if let Err(e) = write_thumbnail(&thumbnail, &temp).and_then(|_| {
+        fs::rename(&temp, &target).map_err(|e| format!("Failed to save thumbnail: {}", e))
+    }) {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// 编码并写入缩略图，写入错误（包括最后的缓冲区刷新）都会返回
+fn write_thumbnail(thumbnail: &RgbImage, path: &Path) -> Result<(), String> {
+    let file = fs::File::create(path).map_err(|e| format!("Failed to create thumbnail: {}", e))?;
+    let mut writer = BufWriter::new(file);
+    JpegEncoder::new_with_quality(&mut writer, THUMBNAIL_QUALITY)
+        .encode_image(thumbnail)
+        .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+    writer
+        .into_inner()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to write thumbnail: {}", e))
+}
+
+/// 获取单张壁纸的缩略图路径，`size` 默认 384
+#[tauri::command]
+pub async fn get_wallpaper_thumbnail(path: String, size: Option<u32>) -> Result<String, String> {
+    let size = normalize_size(size);
+    let _permit = WORKERS
+        .acquire()
+        .await
+        .map_err(|e| format!("Thumbnail task failed: {}", e))?;
+    tokio::task::spawn_blocking(move || ensure_thumbnail(&path, size))
+        .await
+        .map_err(|e| format!("Thumbnail task failed: {}", e))?
+}
+
+/// 批量预生成静态壁纸库的缩略图，通过 `thumbnail-progress` 事件报告进度
+#[tauri::command]
+pub async fn generate_wallpaper_thumbnails(
+    app: tauri::AppHandle,
+    size: Option<u32>,
+) -> Result<ThumbnailSummary, String> {
+    let size = normalize_size(size);
+    let files = tokio::task::spawn_blocking(static_wallpaper_files)
+        .await
+        .map_err(|e| format!("Thumbnail task failed: {}", e))??;
+    let total = files.len();
+
+    let mut tasks = JoinSet::new();
+    for file in files {
+        tasks.spawn(async move {
+            let _permit = WORKERS.acquire().await;
+            let path = file.clone();
+            let result = tokio::task::spawn_blocking(move || ensure_thumbnail(&path, size))
+                .await
+                .unwrap_or_else(|e| Err(format!("Thumbnail task failed: {}", e)));
+            (file, result)
+        });
+    }
+
+    let mut summary = ThumbnailSummary {
+        total,
+        generated: 0,
+        failed: 0,
+    };
+
+    let mut done = 0;
+    while let Some(joined) = tasks.join_next().await {
+        let (path, result) = joined.map_err(|e| format!("Thumbnail task failed: {}", e))?;
+        done += 1;
+
+        let (thumbnail, error) = match result {
+            Ok(thumbnail) => {
+                summary.generated += 1;
+                (Some(thumbnail), None)
+            }
+            Err(e) => {
+                summary.failed += 1;
+                (None, Some(e))
+            }
+        };
+
+        let _ = app.emit(
+            "thumbnail-progress",
+            ThumbnailProgress {
+                done,
+                total,
+                path,
+                thumbnail,
+                error,
+            },
+        );
+    }
+
+    Ok(summary)
+}
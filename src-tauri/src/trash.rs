@@ -0,0 +1,245 @@
+// 回收站模块：删除的壁纸先移入 oPaper/trash，可恢复，超过保留期后自动清除
+//
+// 每个被删除的项目保存在 trash/<id>/ 下，包含原文件（或文件夹）与 trash.json 元数据。
+// 保留天数由配置项 trash_retention_days 控制（默认 30，0 表示永久保留）。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::read_config_value;
+use crate::data_dir::{background_write_guard, try_write_guard};
+use crate::dedup::unique_destination;
+use crate::fs_helper::get_appdata_dir;
+use crate::library::{
+    item_id, load_library, mark_library_stale, sync_library, update_library_item, LibraryItem,
+};
+
+const META_FILE: &str = "trash.json";
+const DEFAULT_RETENTION_DAYS: u64 = 30;
+/// 自动清理检查间隔
+const PURGE_INTERVAL_SECS: u64 = 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    /// static / shader / html
+    pub kind: String,
+    /// 原文件名或文件夹名
+    pub name: String,
+    pub original_path: String,
+    /// 删除时间（Unix 秒）
+    pub deleted_at: i64,
+    pub size: u64,
+    /// 删除前的库条目，恢复时找回标签、评分等用户数据
+    pub library_item: Option<LibraryItem>,
+}
+
+fn trash_dir() -> Result<PathBuf, String> {
+    let dir = get_appdata_dir()?.join("trash");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create trash directory: {}", e))?;
+    Ok(dir)
+}
+
+fn path_size(path: &Path) -> u64 {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|e| path_size(&e.path())).sum())
+            .unwrap_or(0),
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    }
+}
+
+/// 回收站项目目录，拒绝可能越出回收站的 ID
+fn entry_dir(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(format!("Invalid trash entry: {}", id));
+    }
+    Ok(trash_dir()?.join(id))
+}
+
+fn read_entry(dir: &Path) -> Option<TrashEntry> {
+    let content = fs::read_to_string(dir.join(META_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 将文件或文件夹移入回收站
+pub fn move_to_trash(kind: &str, path: &Path) -> Result<TrashEntry, String> {
+    let _writing = try_write_guard()?;
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| "Invalid file name".to_string())?;
+    let original_path = path.to_string_lossy().to_string();
+
+    let library_item = load_library().ok().and_then(|index| {
+        index
+            .items
+            .into_iter()
+            .find(|item| item.path == original_path)
+    });
+
+    let now = chrono::Utc::now();
+    let id = format!("{}-{}", now.timestamp_millis(), item_id(kind, path));
+    let entry_dir = trash_dir()?.join(&id);
+    fs::create_dir_all(&entry_dir).map_err(|e| format!("Failed to create trash entry: {}", e))?;
+
+    let entry = TrashEntry {
+        id,
+        kind: kind.to_string(),
+        name: name.clone(),
+        original_path,
+        deleted_at: now.timestamp(),
+        size: path_size(path),
+        library_item,
+    };
+
+    // 先写入元数据再移动文件，任一步失败都不会留下无法恢复的文件
+    let content = serde_json::to_string_pretty(&entry)
+        .map_err(|e| format!("Failed to serialize trash entry: {}", e))?;
+    fs::write(entry_dir.join(META_FILE), content).map_err(|e| {
+        let _ = fs::remove_dir_all(&entry_dir);
+        format!("Failed to write trash entry: {}", e)
+    })?;
+
+    fs::rename(path, entry_dir.join(&name)).map_err(|e| {
+        let _ = fs::remove_dir_all(&entry_dir);
+        format!("Failed to move to trash: {}", e)
+    })?;
+
+    // 刷新索引，移除已删除的条目
+    if let Err(e) = sync_library() {
+        eprintln!("Failed to sync library: {}", e);
+    }
+
+    Ok(entry)
+}
+
+/// 清除超过保留期的项目，返回清除数量
+fn purge_expired() -> Result<usize, String> {
+    let days = read_config_value()
+        .get("trash_retention_days")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    if days == 0 {
+        return Ok(0);
+    }
+
+    let cutoff = chrono::Utc::now().timestamp() - (days * 24 * 60 * 60) as i64;
+    let expired: Vec<String> = list_trash()?
+        .into_iter()
+        .filter(|entry| entry.deleted_at < cutoff)
+        .map(|entry| entry.id)
+        .collect();
+
+    if expired.is_empty() {
+        return Ok(0);
+    }
+    empty_trash(Some(expired))
+}
+
+/// 列出回收站中的项目（最近删除的在前）
+#[tauri::command]
+pub fn list_trash() -> Result<Vec<TrashEntry>, String> {
+    let entries =
+        fs::read_dir(trash_dir()?).map_err(|e| format!("Failed to read trash directory: {}", e))?;
+
+    let mut items: Vec<TrashEntry> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| read_entry(&entry.path()))
+        .collect();
+    items.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+
+    Ok(items)
+}
+
+/// 从回收站恢复到原位置（原位置被占用时自动改名），返回恢复后的路径
+#[tauri::command]
+pub fn restore_from_trash(id: String) -> Result<String, String> {
+    let _writing = try_write_guard()?;
+    let entry_dir = entry_dir(&id)?;
+    let entry = read_entry(&entry_dir).ok_or_else(|| format!("Trash entry not found: {}", id))?;
+
+    let original = PathBuf::from(&entry.original_path);
+    let parent = original
+        .parent()
+        .ok_or_else(|| "Invalid original path".to_string())?;
+    fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create resource directory: {}", e))?;
+
+    let target = unique_destination(parent, &entry.name);
+    fs::rename(entry_dir.join(&entry.name), &target)
+        .map_err(|e| format!("Failed to restore from trash: {}", e))?;
+    let _ = fs::remove_dir_all(&entry_dir);
+    mark_library_stale();
+
+    // 找回标签、评分等用户数据
+    if let Some(previous) = entry.library_item {
+        let id = item_id(&entry.kind, &target);
+        let result = update_library_item(&id, |item| {
+            item.added_at = previous.added_at;
+            item.source = previous.source;
+            item.source_url = previous.source_url;
+            item.theme = previous.theme;
+            item.tags = previous.tags;
+            item.favorite = previous.favorite;
+            item.rating = previous.rating;
+            item.notes = previous.notes;
+            item.attribution = previous.attribution;
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to restore library metadata: {}", e);
+        }
+    }
+
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// 永久删除回收站中的项目，`ids` 为空时清空回收站，返回删除数量
+#[tauri::command]
+pub fn empty_trash(ids: Option<Vec<String>>) -> Result<usize, String> {
+    let _writing = try_write_guard()?;
+    let ids = match ids {
+        Some(ids) => ids,
+        None => list_trash()?.into_iter().map(|entry| entry.id).collect(),
+    };
+
+    let mut removed = 0;
+    for id in ids {
+        let entry_dir = match entry_dir(&id) {
+            Ok(dir) if dir.is_dir() => dir,
+            _ => continue,
+        };
+        match fs::remove_dir_all(&entry_dir) {
+            Ok(_) => removed += 1,
+            Err(e) => eprintln!("Failed to remove trash entry {}: {}", id, e),
+        }
+    }
+
+    Ok(removed)
+}
+
+/// 启动回收站自动清理任务
+pub fn start_trash_purge_loop() {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let writing = background_write_guard().await;
+            match purge_expired() {
+                Ok(0) => {}
+                Ok(count) => println!("Trash: purged {} expired items", count),
+                Err(e) => eprintln!("Trash: failed to purge expired items: {}", e),
+            }
+            drop(writing);
+
+            tokio::time::sleep(Duration::from_secs(PURGE_INTERVAL_SECS)).await;
+        }
+    });
+}
@@ -2,15 +2,17 @@ use std::fs;
 
 use base64::Engine;
 
+use crate::data_dir::try_write_guard;
 use crate::fs_helper::{get_appdata_dir, read_folder_folders};
+use crate::library::mark_library_stale;
+use crate::trash::move_to_trash;
+use crate::wallpaper_package::{export_package, import_package, PackageImportResult, PackageInfo};
 
 /// 删除 HTML 壁纸文件夹
 #[tauri::command]
 pub fn delete_wallpaper_html(folder: String) -> Result<(), String> {
     // 获取 appdata 目录下的 oPaper 路径
-    let base_dir = dirs::data_dir()
-        .ok_or("Failed to get data directory")?
-        .join("oPaper");
+    let base_dir = get_appdata_dir()?;
 
     // 构建 wallpaper_html 目录路径
     let wallpaper_dir = base_dir.join("wallpaper_html").join(&folder);
@@ -19,7 +21,8 @@ pub fn delete_wallpaper_html(folder: String) -> Result<(), String> {
         return Err(format!("Folder not found: {}", folder));
     }
 
-    fs::remove_dir_all(&wallpaper_dir).map_err(|e| format!("Failed to delete folder: {}", e))?;
+    // 移入回收站，可恢复
+    move_to_trash("html", &wallpaper_dir)?;
 
     Ok(())
 }
@@ -28,9 +31,7 @@ pub fn delete_wallpaper_html(folder: String) -> Result<(), String> {
 #[tauri::command]
 pub fn read_wallpaper_html() -> Result<Vec<String>, String> {
     // 获取 appdata 目录下的 oPaper 路径
-    let base_dir = dirs::data_dir()
-        .ok_or("Failed to get data directory")?
-        .join("oPaper");
+    let base_dir = get_appdata_dir()?;
 
     // 构建 wallpaper_html 目录路径
     let wallpaper_dir = base_dir.join("wallpaper_html");
@@ -52,9 +53,7 @@ pub fn read_wallpaper_html() -> Result<Vec<String>, String> {
 #[tauri::command]
 pub fn read_wallpaper_html_file(folder_path: String) -> Result<String, String> {
     // 获取 appdata 目录下的 oPaper 路径
-    let base_dir = dirs::data_dir()
-        .ok_or("Failed to get data directory")?
-        .join("oPaper");
+    let base_dir = get_appdata_dir()?;
 
     // 构建完整的文件路径
     let file_path = base_dir.join(&folder_path).join("index.html");
@@ -69,16 +68,16 @@ pub fn read_wallpaper_html_file(folder_path: String) -> Result<String, String> {
 /// 写入 HTML 文件内容
 #[tauri::command]
 pub fn write_wallpaper_html_file(folder_path: String, html: String) -> Result<(), String> {
+    let _writing = try_write_guard()?;
     // 获取 appdata 目录下的 oPaper 路径
-    let base_dir = dirs::data_dir()
-        .ok_or("Failed to get data directory")?
-        .join("oPaper");
+    let base_dir = get_appdata_dir()?;
 
     // 构建完整的文件路径
     let file_path = base_dir.join(&folder_path).join("index.html");
 
     // 写入文件内容
     fs::write(&file_path, html).map_err(|e| format!("Failed to write HTML file: {}", e))?;
+    mark_library_stale();
 
     Ok(())
 }
@@ -86,6 +85,7 @@ pub fn write_wallpaper_html_file(folder_path: String, html: String) -> Result<()
 /// 写入 HTML 文件内容
 #[tauri::command]
 pub fn save_temp_html(content: String) -> Result<String, String> {
+    let _writing = try_write_guard()?;
     // 获取 appdata 目录下的 oPaper 路径
     let base_dir = get_appdata_dir()?;
 
@@ -112,10 +112,9 @@ pub fn save_wallpaper_html(
     html: String,
     thumbnail: Option<String>,
 ) -> Result<String, String> {
+    let _writing = try_write_guard()?;
     // 获取 appdata 目录下的 oPaper 路径
-    let base_dir = dirs::data_dir()
-        .ok_or("Failed to get data directory")?
-        .join("oPaper");
+    let base_dir = get_appdata_dir()?;
 
     // 创建 wallpaper_html 目录
     let path = base_dir.join("wallpaper_html").join(&folder_name);
@@ -150,6 +149,7 @@ pub fn save_wallpaper_html(
                 .map_err(|e| format!("Failed to write thumbnail: {}", e))?;
         }
     }
+    mark_library_stale();
 
     Ok(path.to_string_lossy().to_string())
 }
@@ -178,3 +178,22 @@ fn create_default_thumbnail() -> Vec<u8> {
     ];
     png_data
 }
+
+/// 将 HTML 壁纸导出为 `.opaper` 包
+#[tauri::command]
+pub async fn export_wallpaper_html_package(
+    id: String,
+    path: String,
+    info: Option<PackageInfo>,
+) -> Result<String, String> {
+    export_package("html", id, path, info).await
+}
+
+/// 导入 HTML 壁纸的 `.opaper` 包，`approve_network` 为 true 时确认包中需要确认的网络权限
+#[tauri::command]
+pub async fn import_wallpaper_html_package(
+    path: String,
+    approve_network: Option<bool>,
+) -> Result<PackageImportResult, String> {
+    import_package("html", path, approve_network.unwrap_or(false)).await
+}
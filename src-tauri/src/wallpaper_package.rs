@@ -0,0 +1,348 @@
+// 壁纸包模块：将单个着色器 / HTML 壁纸打包为 `.opaper` 文件分享
+//
+// `.opaper` 为 zip 文件，根目录包含 manifest.json 与壁纸文件夹中的全部文件：
+// {
+//   "type": "shader",            // shader / html
+//   "title": "Ocean",
+//   "author": "...",
+//   "license": "CC-BY-4.0",
+//   "version": "1.0.0",
+//   "thumbnail": "thumbnail.png",
+//   "files": ["shader.glsl", "thumbnail.png"],
+//   "network": { "allow": ["api.example.com"] }  // HTML 壁纸的请求访问策略（可选），见 fetch_policy
+// }
+//
+// 导入后 manifest.json 保存在壁纸文件夹中，导出时沿用其中的 network 字段。
+// network 中的局域网访问与较高的请求频率需要用户确认：导入结果列出这些权限，
+// 传入 approve_network 时一并确认，之后也可以通过 approve_wallpaper_network 确认。
+//
+// 着色器与 HTML 壁纸的打包格式和校验完全相同，实现集中在本模块，
+// wallpaper_shader / wallpaper_html 中的命令只指定壁纸类型后调用这里。
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::archive::{
+    add_bytes, add_file, create_archive, entry_name, extract_to, join_relative, open_archive,
+    read_string, validate_archive,
+};
+use crate::data_dir::try_write_guard;
+use crate::fetch_policy::{
+    elevated_permissions, read_wallpaper_policy, set_wallpaper_approval, FetchPolicy,
+};
+use crate::fs_helper::get_appdata_dir;
+use crate::library::{load_library, register_import};
+
+const MANIFEST_FILE: &str = "manifest.json";
+const MAX_MANIFEST_BYTES: u64 = 1024 * 1024;
+const MAX_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+const MAX_TOTAL_BYTES: u64 = 256 * 1024 * 1024;
+const MAX_FILES: usize = 1000;
+
+/// 临时解压目录序号，同一毫秒内的多次导入也不会使用相同的目录
+static STAGING_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageManifest {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub title: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub license: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    pub files: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<FetchPolicy>,
+}
+
+/// 导出时可填写的包信息
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PackageInfo {
+    pub title: Option<String>,
+    pub author: String,
+    pub license: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageImportResult {
+    pub kind: String,
+    /// 导入后的壁纸文件夹
+    pub path: String,
+    pub manifest: PackageManifest,
+    /// network 中需要用户确认的权限
+    pub network_permissions: Vec<String>,
+    /// 上述权限已确认
+    pub network_approved: bool,
+}
+
+/// 壁纸类型对应的目录与入口文件
+fn kind_layout(kind: &str) -> Result<(&'static str, &'static str), String> {
+    match kind {
+        "shader" => Ok(("wallpaper_shader", "shader.glsl")),
+        "html" => Ok(("wallpaper_html", "index.html")),
+        _ => Err(format!("Unsupported package type: {}", kind)),
+    }
+}
+
+/// 文件夹内所有文件的相对路径（`/` 分隔）
+fn list_files(dir: &Path, prefix: &str, out: &mut Vec<String>) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let relative = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        if entry.path().is_dir() {
+            list_files(&entry.path(), &relative, out)?;
+        } else {
+            out.push(relative);
+        }
+    }
+    Ok(())
+}
+
+/// 与前端保存壁纸时一致，以标题作为文件夹名，去掉文件系统不允许的字符
+fn sanitize_folder_name(title: &str) -> String {
+    let name: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let name = name.trim().trim_matches('.').trim().to_string();
+    if name.is_empty() {
+        "wallpaper".to_string()
+    } else {
+        name
+    }
+}
+
+/// 不与已有壁纸冲突的文件夹：`title`、`title (1)`、`title (2)`……
+fn unique_folder(parent: &Path, name: &str) -> PathBuf {
+    let candidate = parent.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    (1..)
+        .map(|n| parent.join(format!("{} ({})", name, n)))
+        .find(|candidate| !candidate.exists())
+        .expect("unbounded range always yields a free name")
+}
+
+fn validate_manifest(manifest: &PackageManifest, entries: &HashSet<String>) -> Result<(), String> {
+    let (_, entry_file) = kind_layout(&manifest.kind)?;
+
+    if manifest.title.trim().is_empty() {
+        return Err("Package title is empty".to_string());
+    }
+    if manifest.files.is_empty() || manifest.files.len() > MAX_FILES {
+        return Err("Invalid package file list".to_string());
+    }
+    if !manifest.files.iter().any(|file| file == entry_file) {
+        return Err(format!("Package is missing {}", entry_file));
+    }
+    for file in &manifest.files {
+        if file == MANIFEST_FILE || !entries.contains(file) {
+            return Err(format!("Invalid package file: {}", file));
+        }
+    }
+    if let Some(thumbnail) = &manifest.thumbnail {
+        if !manifest.files.contains(thumbnail) {
+            return Err(format!("Package thumbnail not found: {}", thumbnail));
+        }
+    }
+
+    Ok(())
+}
+
+/// 将 `kind` 类型（shader / html）的壁纸导出为 `.opaper` 包
+pub async fn export_package(
+    kind: &'static str,
+    id: String,
+    path: String,
+    info: Option<PackageInfo>,
+) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || export_package_blocking(kind, &id, &path, info))
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+fn export_package_blocking(
+    kind: &str,
+    id: &str,
+    path: &str,
+    info: Option<PackageInfo>,
+) -> Result<String, String> {
+    let item = load_library()?
+        .items
+        .into_iter()
+        .find(|item| item.id == id && item.kind == kind)
+        .ok_or_else(|| format!("Library item not found: {}", id))?;
+    kind_layout(&item.kind)?;
+    let info = info.unwrap_or_default();
+
+    let folder = PathBuf::from(&item.path);
+    let mut files = Vec::new();
+    list_files(&folder, "", &mut files)?;
+    // 文件夹中的 manifest.json 是导入时保存的，不作为壁纸文件导出
+    files.retain(|file| file != MANIFEST_FILE);
+    files.sort();
+
+    let thumbnail = files.iter().find(|f| *f == "thumbnail.png").cloned();
+    let manifest = PackageManifest {
+        kind: item.kind.clone(),
+        title: info.title.unwrap_or(item.title),
+        author: info.author,
+        license: info.license,
+        version: info.version,
+        thumbnail,
+        files,
+        network: read_wallpaper_policy(&folder),
+    };
+
+    let mut target = PathBuf::from(path);
+    if target.extension().is_none() {
+        target.set_extension("opaper");
+    }
+
+    let mut zip = create_archive(&target)?;
+    let content = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    add_bytes(&mut zip, MANIFEST_FILE, content.as_bytes())?;
+    for file in &manifest.files {
+        add_file(&mut zip, &join_relative(&folder, file), file)?;
+    }
+    zip.finish()
+        .map_err(|e| format!("Failed to finish package: {}", e))?;
+
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// 导入 `kind` 类型的 `.opaper` 包，同名壁纸已存在时以 `标题 (n)` 命名
+///
+/// `approve_network` 为 true 时确认包中需要确认的网络权限（应在用户同意后传入）。
+pub async fn import_package(
+    kind: &'static str,
+    path: String,
+    approve_network: bool,
+) -> Result<PackageImportResult, String> {
+    tokio::task::spawn_blocking(move || import_package_blocking(kind, &path, approve_network))
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+fn import_package_blocking(
+    kind: &str,
+    path: &str,
+    approve_network: bool,
+) -> Result<PackageImportResult, String> {
+    let _writing = try_write_guard()?;
+    let mut archive = open_archive(Path::new(path))?;
+    validate_archive(&mut archive, MAX_ENTRY_BYTES, MAX_TOTAL_BYTES)?;
+
+    let manifest: PackageManifest = serde_json::from_str(&read_string(
+        &mut archive,
+        MANIFEST_FILE,
+        MAX_MANIFEST_BYTES,
+    )?)
+    .map_err(|e| format!("Invalid manifest: {}", e))?;
+    if manifest.kind != kind {
+        return Err(format!("Package is not a {} wallpaper", kind));
+    }
+
+    let names: Vec<Option<String>> = (0..archive.len())
+        .map(|index| entry_name(&mut archive, index))
+        .collect();
+    let entries: HashSet<String> = names.iter().flatten().cloned().collect();
+    validate_manifest(&manifest, &entries)?;
+
+    let base_dir = get_appdata_dir()?;
+    let (dir, _) = kind_layout(&manifest.kind)?;
+
+    // 先解压到临时目录，完整成功后再移入壁纸目录
+    let staging = base_dir.join("temp").join(format!(
+        "package_{}_{}",
+        chrono::Utc::now().timestamp_millis(),
+        STAGING_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = (|| {
+        // 条目声明的大小不可信，按实际解压的字节数限制总量
+        let mut remaining = MAX_TOTAL_BYTES;
+        for (index, name) in names.iter().enumerate() {
+            match name {
+                Some(name) if manifest.files.contains(name) => {
+                    extract_to(
+                        &mut archive,
+                        index,
+                        &join_relative(&staging, name),
+                        MAX_ENTRY_BYTES,
+                        &mut remaining,
+                    )?;
+                }
+                _ => {}
+            }
+        }
+        let content = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+        fs::write(staging.join(MANIFEST_FILE), content)
+            .map_err(|e| format!("Failed to write manifest: {}", e))?;
+
+        let parent = base_dir.join(dir);
+        fs::create_dir_all(&parent)
+            .map_err(|e| format!("Failed to create resource directory: {}", e))?;
+        let target = unique_folder(&parent, &sanitize_folder_name(&manifest.title));
+        fs::rename(&staging, &target).map_err(|e| format!("Failed to install package: {}", e))?;
+        Ok::<PathBuf, String>(target)
+    })();
+
+    let target = match result {
+        Ok(target) => target,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+
+    let network_permissions = manifest
+        .network
+        .as_ref()
+        .map(elevated_permissions)
+        .unwrap_or_default();
+    let network_approved = approve_network && !network_permissions.is_empty();
+    // 同名文件夹可能留有旧的确认记录，导入时总是按本次选择更新
+    if manifest.network.is_some() {
+        if let Err(e) = set_wallpaper_approval(&target, network_approved) {
+            eprintln!("Failed to save network approval: {}", e);
+        }
+    }
+
+    let target = target.to_string_lossy().to_string();
+    // 登记来源，失败不影响导入
+    if let Err(e) = register_import(&manifest.kind, path, &target) {
+        eprintln!("Failed to register library item: {}", e);
+    }
+
+    Ok(PackageImportResult {
+        kind: manifest.kind.clone(),
+        path: target,
+        manifest,
+        network_permissions,
+        network_approved,
+    })
+}
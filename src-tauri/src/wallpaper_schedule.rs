@@ -0,0 +1,249 @@
+// 日出日落定时切换壁纸模块
+//
+// 配置示例（config.json）：
+// "schedule": {
+//   "enabled": true,
+//   "latitude": 31.23,
+//   "longitude": 121.47,
+//   "wallpapers": {
+//     "dawn":  { "type": "static", "path": "/.../wallpaper_static/dawn.jpg" },
+//     "day":   { "type": "shader", "path": "/.../wallpaper_shader/s_xxx" },
+//     "dusk":  { "type": "static", "path": "/.../wallpaper_static/dusk.jpg" },
+//     "night": { "type": "html",   "path": "/.../wallpaper_html/h_xxx" }
+//   }
+// }
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::config::read_config_value;
+use crate::data_dir::background_write_guard;
+use crate::solar::{solar_times, SolarPhase, SolarTimes};
+use crate::wallpaper_switch::{apply_wallpaper_target, WallpaperTarget};
+
+/// 定时检查间隔
+const CHECK_INTERVAL_SECS: u64 = 60;
+
+/// 上一次已应用的时段，用于只在时段切换时更换壁纸
+static LAST_PHASE: Mutex<Option<SolarPhase>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    pub enabled: bool,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub wallpapers: HashMap<String, WallpaperTarget>,
+}
+
+impl ScheduleConfig {
+    /// 获取时段对应的壁纸；黎明未配置时使用白天，黄昏未配置时使用夜晚
+    pub fn target_for(&self, phase: SolarPhase) -> Option<&WallpaperTarget> {
+        self.wallpapers.get(phase.as_str()).or_else(|| match phase {
+            SolarPhase::Dawn => self.wallpapers.get(SolarPhase::Day.as_str()),
+            SolarPhase::Dusk => self.wallpapers.get(SolarPhase::Night.as_str()),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleTransition {
+    pub time: String,
+    pub phase: SolarPhase,
+    pub wallpaper: Option<WallpaperTarget>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SchedulePreview {
+    pub date: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub dawn: Option<String>,
+    pub sunrise: Option<String>,
+    pub solar_noon: String,
+    pub sunset: Option<String>,
+    pub dusk: Option<String>,
+    pub polar_day: bool,
+    pub polar_night: bool,
+    /// 当天按时间排序的切换点（第一个为当天 00:00 所处的时段）
+    pub transitions: Vec<ScheduleTransition>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SchedulePhaseChanged {
+    phase: SolarPhase,
+    wallpaper: WallpaperTarget,
+}
+
+fn read_schedule_config() -> ScheduleConfig {
+    read_config_value()
+        .get("schedule")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+fn validate_coordinates(latitude: f64, longitude: f64) -> Result<(), String> {
+    if !(-90.0..=90.0).contains(&latitude) {
+        return Err(format!("Invalid latitude: {}", latitude));
+    }
+    if !(-180.0..=180.0).contains(&longitude) {
+        return Err(format!("Invalid longitude: {}", longitude));
+    }
+    Ok(())
+}
+
+fn build_transitions(
+    date: NaiveDate,
+    times: &SolarTimes,
+    config: &ScheduleConfig,
+) -> Vec<ScheduleTransition> {
+    let midnight = Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .unwrap_or_else(Local::now);
+
+    let mut events: Vec<(DateTime<Local>, SolarPhase)> = vec![(midnight, times.phase_at(midnight))];
+    let candidates = [
+        (times.dawn, SolarPhase::Dawn),
+        (times.sunrise, SolarPhase::Day),
+        (times.sunset, SolarPhase::Dusk),
+        (times.dusk, SolarPhase::Night),
+    ];
+    for (time, phase) in candidates {
+        if let Some(time) = time {
+            if time.date_naive() == date {
+                events.push((time, phase));
+            }
+        }
+    }
+    events.sort_by_key(|(time, _)| *time);
+    // 合并相邻的相同时段（如 00:00 已处于黎明）
+    events.dedup_by(|b, a| a.1 == b.1);
+
+    events
+        .into_iter()
+        .map(|(time, phase)| ScheduleTransition {
+            time: time.to_rfc3339(),
+            phase,
+            wallpaper: config.target_for(phase).cloned(),
+        })
+        .collect()
+}
+
+/// 预览指定日期的日出日落时间与壁纸切换计划
+///
+/// `date` 格式为 `YYYY-MM-DD`，为空时使用今天；经纬度为空时使用配置中的值。
+#[tauri::command]
+pub fn preview_solar_schedule(
+    date: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+) -> Result<SchedulePreview, String> {
+    let config = read_schedule_config();
+    let latitude = latitude.unwrap_or(config.latitude);
+    let longitude = longitude.unwrap_or(config.longitude);
+    validate_coordinates(latitude, longitude)?;
+
+    let date = match date {
+        Some(date) if !date.is_empty() => NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date: {}", e))?,
+        _ => Local::now().date_naive(),
+    };
+
+    let times = solar_times(date, latitude, longitude);
+    let format = |time: Option<DateTime<Local>>| time.map(|t| t.to_rfc3339());
+
+    Ok(SchedulePreview {
+        date: times.date.clone(),
+        latitude,
+        longitude,
+        dawn: format(times.dawn),
+        sunrise: format(times.sunrise),
+        solar_noon: times.solar_noon.to_rfc3339(),
+        sunset: format(times.sunset),
+        dusk: format(times.dusk),
+        polar_day: times.polar_day,
+        polar_night: times.polar_night,
+        transitions: build_transitions(date, &times, &config),
+    })
+}
+
+/// 立即按当前时段应用壁纸（修改定时配置后调用）
+#[tauri::command]
+pub async fn apply_solar_schedule(app: tauri::AppHandle) -> Result<String, String> {
+    let config = read_schedule_config();
+    validate_coordinates(config.latitude, config.longitude)?;
+
+    if let Ok(mut last) = LAST_PHASE.lock() {
+        *last = None;
+    }
+
+    match check_schedule(&app, &config).await? {
+        Some(phase) => Ok(format!("Schedule applied: {}", phase.as_str())),
+        None => Err("No wallpaper assigned for current phase".to_string()),
+    }
+}
+
+/// 检查当前时段，时段变化时切换壁纸，返回本次应用的时段
+async fn check_schedule(
+    app: &tauri::AppHandle,
+    config: &ScheduleConfig,
+) -> Result<Option<SolarPhase>, String> {
+    let now = Local::now();
+    let phase = solar_times(now.date_naive(), config.latitude, config.longitude).phase_at(now);
+
+    let last = LAST_PHASE.lock().map(|last| *last).unwrap_or(None);
+    if last == Some(phase) {
+        return Ok(None);
+    }
+
+    let target = match config.target_for(phase) {
+        Some(target) => target.clone(),
+        None => return Ok(None),
+    };
+
+    apply_wallpaper_target(app, &target).await?;
+
+    if let Ok(mut last) = LAST_PHASE.lock() {
+        *last = Some(phase);
+    }
+
+    let _ = app.emit(
+        "schedule-phase-changed",
+        SchedulePhaseChanged {
+            phase,
+            wallpaper: target,
+        },
+    );
+    println!("Schedule: switched wallpaper for phase {}", phase.as_str());
+
+    Ok(Some(phase))
+}
+
+/// 启动后台定时任务
+pub fn start_schedule_loop(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let writing = background_write_guard().await;
+            let config = read_schedule_config();
+            if config.enabled {
+                if validate_coordinates(config.latitude, config.longitude).is_ok() {
+                    if let Err(e) = check_schedule(&app, &config).await {
+                        eprintln!("Schedule: failed to switch wallpaper: {}", e);
+                    }
+                }
+            } else if let Ok(mut last) = LAST_PHASE.lock() {
+                *last = None;
+            }
+            drop(writing);
+
+            tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+        }
+    });
+}
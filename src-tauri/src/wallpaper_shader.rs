@@ -1,7 +1,11 @@
 use base64::Engine;
 use std::fs;
 
+use crate::data_dir::try_write_guard;
 use crate::fs_helper::{get_appdata_dir, read_folder_folders};
+use crate::library::mark_library_stale;
+use crate::trash::move_to_trash;
+use crate::wallpaper_package::{export_package, import_package, PackageImportResult, PackageInfo};
 
 #[tauri::command]
 pub fn delete_wallpaper_shader(folder: String) -> Result<(), String> {
@@ -15,7 +19,8 @@ pub fn delete_wallpaper_shader(folder: String) -> Result<(), String> {
         return Err(format!("Folder not found: {}", folder));
     }
 
-    fs::remove_dir_all(&wallpaper_dir).map_err(|e| format!("Failed to delete folder: {}", e))?;
+    // 移入回收站，可恢复
+    move_to_trash("shader", &wallpaper_dir)?;
 
     Ok(())
 }
@@ -40,6 +45,7 @@ pub fn save_wallpaper_shader(
     glsl: String,
     thumbnail: String,
 ) -> Result<String, String> {
+    let _writing = try_write_guard()?;
     // 获取 appdata 目录下的 oPaper 路径
     let base_dir = get_appdata_dir()?;
 
@@ -64,9 +70,29 @@ pub fn save_wallpaper_shader(
         .map_err(|e| format!("Failed to decode base64: {}", e))?;
     fs::write(&thumbnail_path, thumbnail_bytes)
         .map_err(|e| format!("Failed to save thumbnail: {}", e))?;
+    mark_library_stale();
 
     glsl_path
         .to_str()
         .ok_or_else(|| "Invalid path".to_string())
         .map(|s| s.to_string())
 }
+
+/// 将着色器壁纸导出为 `.opaper` 包
+#[tauri::command]
+pub async fn export_wallpaper_shader_package(
+    id: String,
+    path: String,
+    info: Option<PackageInfo>,
+) -> Result<String, String> {
+    export_package("shader", id, path, info).await
+}
+
+/// 导入着色器壁纸的 `.opaper` 包，`approve_network` 为 true 时确认包中需要确认的网络权限
+#[tauri::command]
+pub async fn import_wallpaper_shader_package(
+    path: String,
+    approve_network: Option<bool>,
+) -> Result<PackageImportResult, String> {
+    import_package("shader", path, approve_network.unwrap_or(false)).await
+}
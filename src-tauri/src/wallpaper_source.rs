@@ -0,0 +1,1542 @@
+// 在线壁纸来源模块：统一各图片服务的接口，返回规范化的壁纸条目
+//
+// 每个来源只负责构造请求与解析响应，网络请求由 fetch_source 统一发送，
+// 基础地址可通过配置替换（例如指向本地镜像或录制的响应）。
+//
+// 配置示例（config.json）：
+// "sources": {
+//   "unsplash": { "api_key": "{{secret:unsplash}}" },   // 可引用 save_credential 保存的凭据
+//   "pexels": { "api_key": "..." },
+//   "wallhaven": { "api_key": "..." },                  // 可选，不填时只能获取 SFW 图片
+//   "nasa": { "api_key": "DEMO_KEY" },
+//   "rss": { "url": "https://example.com/feed.xml" },
+//   "bing": { "base_url": "http://127.0.0.1:8080", "market": "zh-CN" }
+// }
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::read_config_value;
+use crate::connectivity::{ensure_online, report_network_error};
+use crate::credentials::resolve_secret_value;
+use crate::network::{http_client, request_timeout};
+
+const DEFAULT_COUNT: u32 = 8;
+const MEDIA_NS: &str = "http://search.yahoo.com/mrss/";
+
+/// 规范化的壁纸条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceItem {
+    pub source: String,
+    pub id: String,
+    /// 原图地址，可直接传给 set_static_wallpaper_from_url
+    pub url: String,
+    pub thumbnail: Option<String>,
+    /// 图片详情页
+    pub page_url: Option<String>,
+    pub title: String,
+    pub author: String,
+    pub license: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// 获取条目时的参数，各来源只使用自己支持的部分
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceQuery {
+    /// 搜索关键字（Unsplash、Pexels、Wallhaven）
+    pub query: Option<String>,
+    pub count: Option<u32>,
+    /// 日期 YYYY-MM-DD（Wikimedia、NASA APOD），为空时使用当天
+    pub date: Option<String>,
+    /// 订阅地址（RSS/Atom），为空时使用配置中的地址
+    pub url: Option<String>,
+}
+
+/// 来源配置（config.json 中 sources.<id>）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SourceSettings {
+    pub api_key: Option<String>,
+    pub base_url: Option<String>,
+    /// Bing 地区，如 zh-CN
+    pub market: Option<String>,
+    /// RSS/Atom 订阅地址
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceInfo {
+    pub id: String,
+    pub name: String,
+    pub requires_key: bool,
+    /// 需要的 API Key 或订阅地址已配置
+    pub configured: bool,
+}
+
+pub trait WallpaperSource: Send + Sync {
+    fn id(&self) -> &'static str;
+
+    /// 构造请求
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        query: &SourceQuery,
+    ) -> Result<reqwest::RequestBuilder, String>;
+
+    /// 解析响应内容
+    fn parse(&self, body: &str) -> Result<Vec<SourceItem>, String>;
+}
+
+/// 所有来源：(id, 名称, 是否需要 API Key)
+const SOURCES: [(&str, &str, bool); 7] = [
+    ("bing", "Bing 每日图片", false),
+    ("wikimedia", "Wikimedia Commons 每日图片", false),
+    ("nasa", "NASA APOD", false),
+    ("unsplash", "Unsplash", true),
+    ("pexels", "Pexels", true),
+    ("wallhaven", "Wallhaven", false),
+    ("rss", "RSS/Atom 订阅", false),
+];
+
+pub(crate) fn trim_base(base_url: Option<String>, default: &str) -> String {
+    base_url
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| default.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+fn require_key(api_key: Option<String>, name: &str) -> Result<String, String> {
+    api_key
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| format!("{} API key is not configured", name))
+}
+
+/// 替换 API Key 中引用的凭据，按基础地址的主机检查凭据允许的主机
+pub(crate) fn resolve_key(api_key: &str, base_url: &str) -> Result<String, String> {
+    let host = reqwest::Url::parse(base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default();
+    resolve_secret_value(api_key, &host)
+}
+
+fn count_of(query: &SourceQuery, max: u32) -> u32 {
+    query.count.unwrap_or(DEFAULT_COUNT).clamp(1, max)
+}
+
+fn date_of(query: &SourceQuery) -> Result<chrono::NaiveDate, String> {
+    match &query.date {
+        Some(date) if !date.is_empty() => chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date {}: {}", date, e)),
+        _ => Ok(chrono::Local::now().date_naive()),
+    }
+}
+
+pub(crate) fn parse_json<T: serde::de::DeserializeOwned>(
+    body: &str,
+    name: &str,
+) -> Result<T, String> {
+    serde_json::from_str(body).map_err(|e| format!("Failed to parse {} response: {}", name, e))
+}
+
+// ---------------------------------------------------------------------------
+// Bing 每日图片
+
+#[derive(Deserialize)]
+struct BingResponse {
+    #[serde(default)]
+    images: Vec<BingImage>,
+}
+
+#[derive(Deserialize)]
+struct BingImage {
+    #[serde(default)]
+    urlbase: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    copyright: String,
+    #[serde(default)]
+    copyrightlink: String,
+    #[serde(default)]
+    startdate: String,
+}
+
+pub struct BingSource {
+    base_url: String,
+    market: String,
+}
+
+impl BingSource {
+    pub const DEFAULT_BASE_URL: &'static str = "https://www.bing.com";
+
+    pub fn new(base_url: Option<String>, market: Option<String>) -> Self {
+        BingSource {
+            base_url: trim_base(base_url, Self::DEFAULT_BASE_URL),
+            market: market.unwrap_or_else(|| "zh-CN".to_string()),
+        }
+    }
+}
+
+/// 从 "标题 (© 作者)" 中拆出标题与作者
+fn split_copyright(copyright: &str) -> (String, String) {
+    match copyright.rfind(" (") {
+        Some(index) if copyright.ends_with(')') => {
+            let title = copyright[..index].trim().to_string();
+            let author = copyright[index + 2..copyright.len() - 1]
+                .trim_start_matches('©')
+                .trim()
+                .to_string();
+            (title, author)
+        }
+        _ => (copyright.trim().to_string(), String::new()),
+    }
+}
+
+impl WallpaperSource for BingSource {
+    fn id(&self) -> &'static str {
+        "bing"
+    }
+
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        query: &SourceQuery,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        Ok(client
+            .get(format!("{}/HPImageArchive.aspx", self.base_url))
+            .query(&[
+                ("format", "js".to_string()),
+                ("idx", "0".to_string()),
+                ("n", count_of(query, 8).to_string()),
+                ("mkt", self.market.clone()),
+            ]))
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<SourceItem>, String> {
+        let response: BingResponse = parse_json(body, "Bing")?;
+        Ok(response
+            .images
+            .into_iter()
+            .filter(|image| !image.urlbase.is_empty() || !image.url.is_empty())
+            .map(|image| {
+                let (caption, author) = split_copyright(&image.copyright);
+                let (url, width, height) = if image.urlbase.is_empty() {
+                    (format!("{}{}", self.base_url, image.url), None, None)
+                } else {
+                    (
+                        format!("{}{}_UHD.jpg", self.base_url, image.urlbase),
+                        Some(3840),
+                        Some(2160),
+                    )
+                };
+                SourceItem {
+                    source: self.id().to_string(),
+                    id: if image.startdate.is_empty() {
+                        image.urlbase.clone()
+                    } else {
+                        image.startdate.clone()
+                    },
+                    thumbnail: (!image.urlbase.is_empty())
+                        .then(|| format!("{}{}_400x240.jpg", self.base_url, image.urlbase)),
+                    page_url: (!image.copyrightlink.is_empty()).then_some(image.copyrightlink),
+                    title: if image.title.is_empty() {
+                        caption
+                    } else {
+                        image.title
+                    },
+                    author,
+                    license: "Bing".to_string(),
+                    url,
+                    width,
+                    height,
+                }
+            })
+            .collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Wikimedia Commons 每日图片（Wikimedia feed API）
+
+#[derive(Deserialize)]
+struct WikimediaFeed {
+    image: Option<WikimediaImage>,
+}
+
+#[derive(Deserialize)]
+struct WikimediaImage {
+    #[serde(default)]
+    title: String,
+    image: WikimediaFile,
+    thumbnail: Option<WikimediaFile>,
+    #[serde(default)]
+    file_page: String,
+    artist: Option<WikimediaText>,
+    credit: Option<WikimediaText>,
+    license: Option<WikimediaLicense>,
+    description: Option<WikimediaText>,
+}
+
+#[derive(Deserialize)]
+struct WikimediaFile {
+    source: String,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct WikimediaText {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct WikimediaLicense {
+    #[serde(default, rename = "type")]
+    kind: String,
+}
+
+pub struct WikimediaSource {
+    base_url: String,
+}
+
+impl WikimediaSource {
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.wikimedia.org";
+
+    pub fn new(base_url: Option<String>) -> Self {
+        WikimediaSource {
+            base_url: trim_base(base_url, Self::DEFAULT_BASE_URL),
+        }
+    }
+}
+
+impl WallpaperSource for WikimediaSource {
+    fn id(&self) -> &'static str {
+        "wikimedia"
+    }
+
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        query: &SourceQuery,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let date = date_of(query)?;
+        Ok(client.get(format!(
+            "{}/feed/v1/wikipedia/en/featured/{}",
+            self.base_url,
+            date.format("%Y/%m/%d")
+        )))
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<SourceItem>, String> {
+        let feed: WikimediaFeed = parse_json(body, "Wikimedia")?;
+        let image = match feed.image {
+            Some(image) => image,
+            None => return Ok(Vec::new()),
+        };
+
+        let title = image
+            .description
+            .map(|d| d.text)
+            .filter(|text| !text.is_empty())
+            .unwrap_or_else(|| {
+                image
+                    .title
+                    .trim_start_matches("File:")
+                    .rsplit_once('.')
+                    .map(|(name, _)| name.to_string())
+                    .unwrap_or_else(|| image.title.clone())
+            });
+        let author = image
+            .artist
+            .or(image.credit)
+            .map(|a| a.text.trim().to_string())
+            .unwrap_or_default();
+
+        Ok(vec![SourceItem {
+            source: self.id().to_string(),
+            id: image.title.clone(),
+            url: image.image.source,
+            thumbnail: image.thumbnail.map(|t| t.source),
+            page_url: (!image.file_page.is_empty()).then_some(image.file_page),
+            title,
+            author,
+            license: image.license.map(|l| l.kind).unwrap_or_default(),
+            width: image.image.width,
+            height: image.image.height,
+        }])
+    }
+}
+
+// ---------------------------------------------------------------------------
+// NASA 每日天文图片
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ApodResponse {
+    Many(Vec<ApodEntry>),
+    One(ApodEntry),
+}
+
+#[derive(Deserialize)]
+struct ApodEntry {
+    #[serde(default)]
+    date: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    url: String,
+    hdurl: Option<String>,
+    #[serde(default)]
+    media_type: String,
+    copyright: Option<String>,
+}
+
+pub struct NasaApodSource {
+    base_url: String,
+    api_key: String,
+}
+
+impl NasaApodSource {
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.nasa.gov";
+
+    pub fn new(base_url: Option<String>, api_key: Option<String>) -> Self {
+        NasaApodSource {
+            base_url: trim_base(base_url, Self::DEFAULT_BASE_URL),
+            api_key: api_key
+                .filter(|key| !key.is_empty())
+                .unwrap_or_else(|| "DEMO_KEY".to_string()),
+        }
+    }
+}
+
+impl WallpaperSource for NasaApodSource {
+    fn id(&self) -> &'static str {
+        "nasa"
+    }
+
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        query: &SourceQuery,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let mut params = vec![("api_key", resolve_key(&self.api_key, &self.base_url)?)];
+        match (&query.date, query.count) {
+            (Some(date), _) if !date.is_empty() => {
+                params.push(("date", date_of(query)?.format("%Y-%m-%d").to_string()))
+            }
+            (_, Some(count)) if count > 1 => params.push(("count", count.min(100).to_string())),
+            _ => {}
+        }
+        Ok(client
+            .get(format!("{}/planetary/apod", self.base_url))
+            .query(&params))
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<SourceItem>, String> {
+        let entries = match parse_json(body, "NASA APOD")? {
+            ApodResponse::Many(entries) => entries,
+            ApodResponse::One(entry) => vec![entry],
+        };
+        Ok(entries
+            .into_iter()
+            // 视频条目无法作为静态壁纸
+            .filter(|entry| entry.media_type == "image" && !entry.url.is_empty())
+            .map(|entry| {
+                let (author, license) = match entry.copyright {
+                    Some(copyright) => (
+                        copyright.replace('\n', " ").trim().to_string(),
+                        "Copyrighted".to_string(),
+                    ),
+                    None => ("NASA".to_string(), "Public domain".to_string()),
+                };
+                SourceItem {
+                    source: self.id().to_string(),
+                    page_url: Some(format!(
+                        "https://apod.nasa.gov/apod/ap{}.html",
+                        entry.date.replace('-', "").get(2..).unwrap_or_default()
+                    )),
+                    id: entry.date,
+                    thumbnail: Some(entry.url.clone()),
+                    url: entry.hdurl.unwrap_or(entry.url),
+                    title: entry.title,
+                    author,
+                    license,
+                    width: None,
+                    height: None,
+                }
+            })
+            .collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Unsplash
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum UnsplashResponse {
+    Random(Vec<UnsplashPhoto>),
+    Search { results: Vec<UnsplashPhoto> },
+}
+
+#[derive(Deserialize)]
+struct UnsplashPhoto {
+    id: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    description: Option<String>,
+    alt_description: Option<String>,
+    urls: UnsplashUrls,
+    user: UnsplashUser,
+    links: Option<UnsplashLinks>,
+}
+
+#[derive(Deserialize)]
+struct UnsplashUrls {
+    #[serde(default)]
+    full: String,
+    #[serde(default)]
+    raw: String,
+    small: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UnsplashUser {
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct UnsplashLinks {
+    html: Option<String>,
+}
+
+pub struct UnsplashSource {
+    base_url: String,
+    api_key: String,
+}
+
+impl UnsplashSource {
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.unsplash.com";
+
+    pub fn new(base_url: Option<String>, api_key: Option<String>) -> Result<Self, String> {
+        Ok(UnsplashSource {
+            base_url: trim_base(base_url, Self::DEFAULT_BASE_URL),
+            api_key: require_key(api_key, "Unsplash")?,
+        })
+    }
+}
+
+impl WallpaperSource for UnsplashSource {
+    fn id(&self) -> &'static str {
+        "unsplash"
+    }
+
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        query: &SourceQuery,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let mut params = vec![
+            ("count", count_of(query, 30).to_string()),
+            ("orientation", "landscape".to_string()),
+        ];
+        if let Some(keyword) = query.query.as_ref().filter(|q| !q.is_empty()) {
+            params.push(("query", keyword.clone()));
+        }
+        let key = resolve_key(&self.api_key, &self.base_url)?;
+        Ok(client
+            .get(format!("{}/photos/random", self.base_url))
+            .query(&params)
+            .header("Authorization", format!("Client-ID {}", key))
+            .header("Accept-Version", "v1"))
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<SourceItem>, String> {
+        let photos = match parse_json(body, "Unsplash")? {
+            UnsplashResponse::Random(photos) => photos,
+            UnsplashResponse::Search { results } => results,
+        };
+        Ok(photos
+            .into_iter()
+            .map(|photo| SourceItem {
+                source: self.id().to_string(),
+                url: if photo.urls.full.is_empty() {
+                    photo.urls.raw
+                } else {
+                    photo.urls.full
+                },
+                thumbnail: photo.urls.small,
+                page_url: photo.links.and_then(|l| l.html),
+                title: photo
+                    .description
+                    .or(photo.alt_description)
+                    .unwrap_or_default(),
+                author: photo.user.name,
+                license: "Unsplash License".to_string(),
+                width: photo.width,
+                height: photo.height,
+                id: photo.id,
+            })
+            .collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pexels
+
+#[derive(Deserialize)]
+struct PexelsResponse {
+    #[serde(default)]
+    photos: Vec<PexelsPhoto>,
+}
+
+#[derive(Deserialize)]
+struct PexelsPhoto {
+    id: u64,
+    width: Option<u32>,
+    height: Option<u32>,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    photographer: String,
+    #[serde(default)]
+    alt: String,
+    src: PexelsSrc,
+}
+
+#[derive(Deserialize)]
+struct PexelsSrc {
+    original: String,
+    medium: Option<String>,
+}
+
+pub struct PexelsSource {
+    base_url: String,
+    api_key: String,
+}
+
+impl PexelsSource {
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.pexels.com";
+
+    pub fn new(base_url: Option<String>, api_key: Option<String>) -> Result<Self, String> {
+        Ok(PexelsSource {
+            base_url: trim_base(base_url, Self::DEFAULT_BASE_URL),
+            api_key: require_key(api_key, "Pexels")?,
+        })
+    }
+}
+
+impl WallpaperSource for PexelsSource {
+    fn id(&self) -> &'static str {
+        "pexels"
+    }
+
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        query: &SourceQuery,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let per_page = count_of(query, 80).to_string();
+        let request = match query.query.as_ref().filter(|q| !q.is_empty()) {
+            Some(keyword) => client.get(format!("{}/v1/search", self.base_url)).query(&[
+                ("query", keyword.as_str()),
+                ("per_page", &per_page),
+                ("orientation", "landscape"),
+            ]),
+            None => client
+                .get(format!("{}/v1/curated", self.base_url))
+                .query(&[("per_page", &per_page)]),
+        };
+        Ok(request.header("Authorization", resolve_key(&self.api_key, &self.base_url)?))
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<SourceItem>, String> {
+        let response: PexelsResponse = parse_json(body, "Pexels")?;
+        Ok(response
+            .photos
+            .into_iter()
+            .map(|photo| SourceItem {
+                source: self.id().to_string(),
+                id: photo.id.to_string(),
+                url: photo.src.original,
+                thumbnail: photo.src.medium,
+                page_url: (!photo.url.is_empty()).then_some(photo.url),
+                title: photo.alt,
+                author: photo.photographer,
+                license: "Pexels License".to_string(),
+                width: photo.width,
+                height: photo.height,
+            })
+            .collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Wallhaven
+
+#[derive(Deserialize)]
+struct WallhavenResponse {
+    #[serde(default)]
+    data: Vec<WallhavenWallpaper>,
+}
+
+#[derive(Deserialize)]
+struct WallhavenWallpaper {
+    id: String,
+    #[serde(default)]
+    url: String,
+    path: String,
+    dimension_x: Option<u32>,
+    dimension_y: Option<u32>,
+    thumbs: Option<WallhavenThumbs>,
+    uploader: Option<WallhavenUploader>,
+}
+
+#[derive(Deserialize)]
+struct WallhavenThumbs {
+    large: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WallhavenUploader {
+    #[serde(default)]
+    username: String,
+}
+
+pub struct WallhavenSource {
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl WallhavenSource {
+    pub const DEFAULT_BASE_URL: &'static str = "https://wallhaven.cc";
+
+    pub fn new(base_url: Option<String>, api_key: Option<String>) -> Self {
+        WallhavenSource {
+            base_url: trim_base(base_url, Self::DEFAULT_BASE_URL),
+            api_key: api_key.filter(|key| !key.is_empty()),
+        }
+    }
+}
+
+impl WallpaperSource for WallhavenSource {
+    fn id(&self) -> &'static str {
+        "wallhaven"
+    }
+
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        query: &SourceQuery,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let mut params = vec![
+            ("sorting", "random".to_string()),
+            ("categories", "110".to_string()),
+            ("atleast", "1920x1080".to_string()),
+        ];
+        if let Some(keyword) = query.query.as_ref().filter(|q| !q.is_empty()) {
+            params.push(("q", keyword.clone()));
+        }
+        if let Some(key) = &self.api_key {
+            params.push(("apikey", resolve_key(key, &self.base_url)?));
+        }
+        Ok(client
+            .get(format!("{}/api/v1/search", self.base_url))
+            .query(&params))
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<SourceItem>, String> {
+        let response: WallhavenResponse = parse_json(body, "Wallhaven")?;
+        Ok(response
+            .data
+            .into_iter()
+            .map(|wallpaper| SourceItem {
+                source: self.id().to_string(),
+                title: format!("Wallhaven {}", wallpaper.id),
+                url: wallpaper.path,
+                thumbnail: wallpaper.thumbs.and_then(|t| t.large),
+                page_url: (!wallpaper.url.is_empty()).then_some(wallpaper.url),
+                author: wallpaper.uploader.map(|u| u.username).unwrap_or_default(),
+                license: String::new(),
+                width: wallpaper.dimension_x,
+                height: wallpaper.dimension_y,
+                id: wallpaper.id,
+            })
+            .collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RSS / Atom 媒体订阅
+
+pub struct FeedSource {
+    url: Option<String>,
+}
+
+impl FeedSource {
+    pub fn new(url: Option<String>) -> Self {
+        FeedSource {
+            url: url.filter(|url| !url.is_empty()),
+        }
+    }
+}
+
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn child_text(node: roxmltree::Node, name: &str) -> Option<String> {
+    child(node, name)
+        .and_then(|n| n.text())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+fn media_children<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> + 'a {
+    // media:content 也可能包在 media:group 中
+    node.descendants().filter(move |n| {
+        n.is_element() && n.tag_name().name() == name && n.tag_name().namespace() == Some(MEDIA_NS)
+    })
+}
+
+fn is_image_type(mime: Option<&str>) -> bool {
+    mime.is_some_and(|mime| mime.starts_with("image/"))
+}
+
+fn parse_dimension(node: roxmltree::Node, name: &str) -> Option<u32> {
+    node.attribute(name).and_then(|v| v.parse().ok())
+}
+
+/// 条目中的图片：media:content、enclosure（RSS）或 rel="enclosure" 的 link（Atom）
+fn feed_image(entry: roxmltree::Node) -> Option<(String, Option<u32>, Option<u32>)> {
+    let media = media_children(entry, "content")
+        .filter(|n| {
+            n.attribute("medium") == Some("image")
+                || (n.attribute("medium").is_none() && is_image_type(n.attribute("type")))
+        })
+        .filter_map(|n| {
+            let url = n.attribute("url")?;
+            Some((
+                url.to_string(),
+                parse_dimension(n, "width"),
+                parse_dimension(n, "height"),
+            ))
+        })
+        // 同一条目有多种尺寸时取最大的
+        .max_by_key(|(_, width, height)| width.unwrap_or(0) as u64 * height.unwrap_or(0) as u64);
+    if media.is_some() {
+        return media;
+    }
+
+    entry
+        .children()
+        .filter(|n| n.is_element())
+        .find_map(|n| match n.tag_name().name() {
+            "enclosure" if is_image_type(n.attribute("type")) => {
+                n.attribute("url").map(|url| (url.to_string(), None, None))
+            }
+            "link"
+                if n.attribute("rel") == Some("enclosure")
+                    && is_image_type(n.attribute("type")) =>
+            {
+                n.attribute("href").map(|url| (url.to_string(), None, None))
+            }
+            _ => None,
+        })
+}
+
+fn feed_link(entry: roxmltree::Node) -> Option<String> {
+    entry
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().name() == "link")
+        .find_map(|n| match n.attribute("href") {
+            // Atom
+            Some(href) if matches!(n.attribute("rel"), None | Some("alternate")) => {
+                Some(href.to_string())
+            }
+            Some(_) => None,
+            // RSS
+            None => n
+                .text()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty()),
+        })
+}
+
+fn feed_author(entry: roxmltree::Node) -> Option<String> {
+    media_children(entry, "credit")
+        .find_map(|n| n.text().map(|t| t.trim().to_string()))
+        .or_else(|| child_text(entry, "creator"))
+        .or_else(|| child(entry, "author").and_then(|author| child_text(author, "name")))
+        .or_else(|| child_text(entry, "author"))
+}
+
+fn feed_license(entry: roxmltree::Node) -> Option<String> {
+    media_children(entry, "license")
+        .find_map(|n| {
+            n.text()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .or_else(|| n.attribute("href").map(|href| href.to_string()))
+        })
+        .or_else(|| child_text(entry, "license"))
+        .or_else(|| child_text(entry, "rights"))
+        .or_else(|| media_children(entry, "copyright").find_map(|n| n.text().map(str::to_string)))
+}
+
+impl WallpaperSource for FeedSource {
+    fn id(&self) -> &'static str {
+        "rss"
+    }
+
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        query: &SourceQuery,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let url = query
+            .url
+            .as_ref()
+            .filter(|url| !url.is_empty())
+            .or(self.url.as_ref())
+            .ok_or_else(|| "Feed URL is not configured".to_string())?;
+        Ok(client.get(url).header(
+            "Accept",
+            "application/rss+xml, application/atom+xml, application/xml;q=0.9, */*;q=0.8",
+        ))
+    }
+
+    fn parse(&self, body: &str) -> Result<Vec<SourceItem>, String> {
+        let document =
+            roxmltree::Document::parse(body).map_err(|e| format!("Failed to parse feed: {}", e))?;
+        let root = document.root_element();
+        // 频道级别的版权信息作为条目的默认许可
+        let channel = child(root, "channel").unwrap_or(root);
+        let channel_license =
+            child_text(channel, "copyright").or_else(|| child_text(root, "rights"));
+
+        Ok(root
+            .descendants()
+            .filter(|n| n.is_element() && matches!(n.tag_name().name(), "item" | "entry"))
+            .filter_map(|entry| {
+                let (url, width, height) = feed_image(entry)?;
+                let page_url = feed_link(entry);
+                Some(SourceItem {
+                    source: self.id().to_string(),
+                    id: child_text(entry, "guid")
+                        .or_else(|| child_text(entry, "id"))
+                        .or_else(|| page_url.clone())
+                        .unwrap_or_else(|| url.clone()),
+                    thumbnail: media_children(entry, "thumbnail")
+                        .find_map(|n| n.attribute("url").map(str::to_string)),
+                    title: child_text(entry, "title").unwrap_or_default(),
+                    author: feed_author(entry).unwrap_or_default(),
+                    license: feed_license(entry)
+                        .or_else(|| channel_license.clone())
+                        .unwrap_or_default(),
+                    page_url,
+                    url,
+                    width,
+                    height,
+                })
+            })
+            .collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+
+/// 读取来源配置（config.json 中 sources.<id>）
+pub fn read_source_settings(id: &str) -> SourceSettings {
+    read_config_value()
+        .get("sources")
+        .and_then(|sources| sources.get(id))
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// 按 id 与配置创建来源
+pub fn create_source(
+    id: &str,
+    settings: SourceSettings,
+) -> Result<Box<dyn WallpaperSource>, String> {
+    let SourceSettings {
+        api_key,
+        base_url,
+        market,
+        url,
+    } = settings;
+    Ok(match id {
+        "bing" => Box::new(BingSource::new(base_url, market)),
+        "wikimedia" => Box::new(WikimediaSource::new(base_url)),
+        "nasa" => Box::new(NasaApodSource::new(base_url, api_key)),
+        "unsplash" => Box::new(UnsplashSource::new(base_url, api_key)?),
+        "pexels" => Box::new(PexelsSource::new(base_url, api_key)?),
+        "wallhaven" => Box::new(WallhavenSource::new(base_url, api_key)),
+        "rss" => Box::new(FeedSource::new(url.or(base_url))),
+        _ => return Err(format!("Unknown wallpaper source: {}", id)),
+    })
+}
+
+/// 请求来源并返回规范化条目
+pub async fn fetch_source(
+    source: &dyn WallpaperSource,
+    query: &SourceQuery,
+) -> Result<Vec<SourceItem>, String> {
+    ensure_online()?;
+    let client = http_client()?;
+    let response = source
+        .request(&client, query)?
+        .timeout(request_timeout())
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_connect() || e.is_timeout() {
+                report_network_error();
+            }
+            format!("Failed to request {}: {}", source.id(), e)
+        })?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to request {}: HTTP {}",
+            source.id(),
+            response.status()
+        ));
+    }
+
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read {} response: {}", source.id(), e))?;
+
+    let mut items = source.parse(&body)?;
+    if let Some(count) = query.count {
+        items.truncate(count as usize);
+    }
+    Ok(items)
+}
+
+/// 列出可用的在线来源
+#[tauri::command]
+pub fn list_wallpaper_sources() -> Vec<SourceInfo> {
+    SOURCES
+        .iter()
+        .map(|(id, name, requires_key)| {
+            let settings = read_source_settings(id);
+            let configured = match *id {
+                "rss" => settings.url.is_some_and(|url| !url.is_empty()),
+                _ if *requires_key => settings.api_key.is_some_and(|key| !key.is_empty()),
+                _ => true,
+            };
+            SourceInfo {
+                id: id.to_string(),
+                name: name.to_string(),
+                requires_key: *requires_key,
+                configured,
+            }
+        })
+        .collect()
+}
+
+/// 从在线来源获取壁纸条目
+#[tauri::command]
+pub async fn fetch_wallpaper_source(
+    source: String,
+    query: Option<SourceQuery>,
+) -> Result<Vec<SourceItem>, String> {
+    let provider = create_source(&source, read_source_settings(&source))?;
+    fetch_source(provider.as_ref(), &query.unwrap_or_default()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{client, StandIn};
+
+    const JSON: &str = "application/json";
+    const XML: &str = "application/xml";
+
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/sources/",
+                $name
+            ))
+            .to_string()
+        };
+    }
+
+    /// 与 fetch_source 相同地构造请求并解析，但不经过共享客户端与连通性检查
+    async fn fetch(
+        source: &dyn WallpaperSource,
+        query: &SourceQuery,
+    ) -> Result<Vec<SourceItem>, String> {
+        let response = source
+            .request(&client(), query)?
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?;
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        source.parse(&body)
+    }
+
+    #[tokio::test]
+    async fn bing_normalizes_images() {
+        let server =
+            StandIn::start(vec![("/HPImageArchive.aspx", JSON, fixture!("bing.json"))]).await;
+        let source = BingSource::new(Some(format!("{}/", server.base_url)), Some("en-US".into()));
+        let query = SourceQuery {
+            count: Some(2),
+            ..Default::default()
+        };
+
+        let items = fetch(&source, &query).await.unwrap();
+        let target = server.last_target();
+        assert!(target.contains("format=js"), "{}", target);
+        assert!(target.contains("n=2"), "{}", target);
+        assert!(target.contains("mkt=en-US"), "{}", target);
+
+        assert_eq!(items.len(), 2);
+        let owl = &items[0];
+        assert_eq!(owl.source, "bing");
+        assert_eq!(owl.id, "20240115");
+        assert_eq!(
+            owl.url,
+            format!(
+                "{}/th?id=OHR.SnowyOwl_EN-US1234567890_UHD.jpg",
+                server.base_url
+            )
+        );
+        assert_eq!(
+            owl.thumbnail.as_deref(),
+            Some(
+                format!(
+                    "{}/th?id=OHR.SnowyOwl_EN-US1234567890_400x240.jpg",
+                    server.base_url
+                )
+                .as_str()
+            )
+        );
+        assert_eq!(owl.title, "Winter's silent hunter");
+        assert_eq!(owl.author, "Jim Cumming/Getty Images");
+        assert_eq!(owl.license, "Bing");
+        assert_eq!(
+            owl.page_url.as_deref(),
+            Some("https://www.bing.com/search?q=snowy+owl&form=hpcapt")
+        );
+        assert_eq!((owl.width, owl.height), (Some(3840), Some(2160)));
+
+        // 没有 urlbase 时使用 url，标题取自版权说明
+        let lake = &items[1];
+        assert_eq!(
+            lake.url,
+            format!(
+                "{}/th?id=OHR.FrozenLake_EN-US0987654321_1920x1080.jpg",
+                server.base_url
+            )
+        );
+        assert_eq!(lake.title, "Frozen lake at dawn");
+        assert_eq!(lake.author, "");
+        assert_eq!(lake.thumbnail, None);
+        assert_eq!(lake.page_url, None);
+        assert_eq!((lake.width, lake.height), (None, None));
+    }
+
+    #[tokio::test]
+    async fn wikimedia_reads_featured_image() {
+        let server = StandIn::start(vec![(
+            "/feed/v1/wikipedia/en/featured/2024/01/15",
+            JSON,
+            fixture!("wikimedia.json"),
+        )])
+        .await;
+        let source = WikimediaSource::new(Some(server.base_url.clone()));
+        let query = SourceQuery {
+            date: Some("2024-01-15".into()),
+            ..Default::default()
+        };
+
+        let items = fetch(&source, &query).await.unwrap();
+        assert_eq!(items.len(), 1);
+        let item = &items[0];
+        assert_eq!(item.source, "wikimedia");
+        assert_eq!(item.id, "File:Lake Louise at dawn.jpg");
+        assert_eq!(
+            item.url,
+            "https://upload.wikimedia.org/wikipedia/commons/a/ab/Lake_Louise_at_dawn.jpg"
+        );
+        assert!(item
+            .thumbnail
+            .as_deref()
+            .unwrap()
+            .ends_with("640px-Lake_Louise_at_dawn.jpg"));
+        assert_eq!(
+            item.page_url.as_deref(),
+            Some("https://commons.wikimedia.org/wiki/File:Lake_Louise_at_dawn.jpg")
+        );
+        assert_eq!(item.title, "Lake Louise at dawn, Banff National Park");
+        assert_eq!(item.author, "Example Photographer");
+        assert_eq!(item.license, "CC BY-SA 4.0");
+        assert_eq!((item.width, item.height), (Some(6000), Some(4000)));
+    }
+
+    #[tokio::test]
+    async fn wikimedia_without_image_is_empty() {
+        let server = StandIn::start(vec![(
+            "/feed/v1/wikipedia/en/featured/2024/01/16",
+            JSON,
+            r#"{"tfa": {"title": "Only_an_article"}}"#.to_string(),
+        )])
+        .await;
+        let source = WikimediaSource::new(Some(server.base_url.clone()));
+        let query = SourceQuery {
+            date: Some("2024-01-16".into()),
+            ..Default::default()
+        };
+
+        assert!(fetch(&source, &query).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn wikimedia_rejects_invalid_date() {
+        let source = WikimediaSource::new(None);
+        let query = SourceQuery {
+            date: Some("2024-13-01".into()),
+            ..Default::default()
+        };
+
+        let error = fetch(&source, &query).await.unwrap_err();
+        assert!(error.starts_with("Invalid date 2024-13-01"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn apod_skips_videos() {
+        let server = StandIn::start(vec![("/planetary/apod", JSON, fixture!("apod.json"))]).await;
+        let source = NasaApodSource::new(Some(server.base_url.clone()), None);
+        let query = SourceQuery {
+            count: Some(3),
+            ..Default::default()
+        };
+
+        let items = fetch(&source, &query).await.unwrap();
+        let target = server.last_target();
+        assert!(target.contains("api_key=DEMO_KEY"), "{}", target);
+        assert!(target.contains("count=3"), "{}", target);
+
+        // 2024-01-14 为视频，不作为壁纸
+        let ids: Vec<&str> = items.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, ["2024-01-13", "2024-01-15"]);
+
+        let galaxy = &items[0];
+        assert_eq!(
+            galaxy.url,
+            "https://apod.nasa.gov/apod/image/2401/M101_hd.jpg"
+        );
+        assert_eq!(
+            galaxy.thumbnail.as_deref(),
+            Some("https://apod.nasa.gov/apod/image/2401/M101_1024.jpg")
+        );
+        assert_eq!(
+            galaxy.page_url.as_deref(),
+            Some("https://apod.nasa.gov/apod/ap240113.html")
+        );
+        assert_eq!(galaxy.title, "M101: The Pinwheel Galaxy");
+        assert_eq!(galaxy.author, "NASA");
+        assert_eq!(galaxy.license, "Public domain");
+
+        // 没有 hdurl 时使用 url，有版权时标注作者
+        let nebula = &items[1];
+        assert_eq!(
+            nebula.url,
+            "https://apod.nasa.gov/apod/image/2401/Orion_1024.jpg"
+        );
+        assert_eq!(nebula.author, "Jane Astro");
+        assert_eq!(nebula.license, "Copyrighted");
+    }
+
+    #[tokio::test]
+    async fn apod_video_day_is_empty() {
+        let server = StandIn::start(vec![(
+            "/planetary/apod",
+            JSON,
+            r#"{"date": "2024-01-14", "media_type": "video", "title": "Aurora",
+                "url": "https://www.youtube.com/embed/abcdefghijk"}"#
+                .to_string(),
+        )])
+        .await;
+        let source = NasaApodSource::new(Some(server.base_url.clone()), Some("key".into()));
+        let query = SourceQuery {
+            date: Some("2024-01-14".into()),
+            ..Default::default()
+        };
+
+        assert!(fetch(&source, &query).await.unwrap().is_empty());
+        let target = server.last_target();
+        assert!(target.contains("api_key=key"), "{}", target);
+        assert!(target.contains("date=2024-01-14"), "{}", target);
+    }
+
+    #[tokio::test]
+    async fn unsplash_sends_key_and_normalizes_photos() {
+        let server =
+            StandIn::start(vec![("/photos/random", JSON, fixture!("unsplash.json"))]).await;
+        let source =
+            UnsplashSource::new(Some(server.base_url.clone()), Some("access-key".into())).unwrap();
+        let query = SourceQuery {
+            query: Some("coffee".into()),
+            count: Some(2),
+            ..Default::default()
+        };
+
+        let items = fetch(&source, &query).await.unwrap();
+        let head = server.requests().pop().unwrap().to_ascii_lowercase();
+        assert!(
+            head.contains("authorization: client-id access-key"),
+            "{}",
+            head
+        );
+        assert!(head.contains("query=coffee"), "{}", head);
+
+        assert_eq!(items.len(), 2);
+        let first = &items[0];
+        assert_eq!(first.source, "unsplash");
+        assert_eq!(first.id, "Dwu85P9SOIk");
+        assert_eq!(
+            first.url,
+            "https://images.unsplash.com/photo-1417325384643-aac51acc9e5d?q=75&fm=jpg"
+        );
+        assert!(first.thumbnail.as_deref().unwrap().contains("w=400"));
+        assert_eq!(
+            first.page_url.as_deref(),
+            Some("https://unsplash.com/photos/Dwu85P9SOIk")
+        );
+        assert_eq!(first.title, "A man drinking a coffee.");
+        assert_eq!(first.author, "Joe Example");
+        assert_eq!(first.license, "Unsplash License");
+        assert_eq!((first.width, first.height), (Some(5245), Some(3497)));
+
+        // full 为空时使用 raw，没有描述时使用 alt_description
+        let second = &items[1];
+        assert_eq!(
+            second.url,
+            "https://images.unsplash.com/photo-1500000000000-raw"
+        );
+        assert_eq!(second.title, "mountain range under cloudy sky");
+        assert_eq!(second.page_url, None);
+    }
+
+    #[test]
+    fn keyed_sources_require_key() {
+        for key in [None, Some(String::new())] {
+            let error = UnsplashSource::new(None, key.clone()).err().unwrap();
+            assert_eq!(error, "Unsplash API key is not configured");
+            let error = PexelsSource::new(None, key.clone()).err().unwrap();
+            assert_eq!(error, "Pexels API key is not configured");
+            let error = create_source("unsplash", SourceSettings::default())
+                .err()
+                .unwrap();
+            assert_eq!(error, "Unsplash API key is not configured");
+        }
+        // 其他来源不需要 API Key
+        for id in ["bing", "wikimedia", "nasa", "wallhaven", "rss"] {
+            assert!(
+                create_source(id, SourceSettings::default()).is_ok(),
+                "{}",
+                id
+            );
+        }
+        assert_eq!(
+            create_source("flickr", SourceSettings::default())
+                .err()
+                .unwrap(),
+            "Unknown wallpaper source: flickr"
+        );
+    }
+
+    #[tokio::test]
+    async fn pexels_searches_with_key() {
+        let server = StandIn::start(vec![("/v1/search", JSON, fixture!("pexels.json"))]).await;
+        let source =
+            PexelsSource::new(Some(server.base_url.clone()), Some("pexels-key".into())).unwrap();
+        let query = SourceQuery {
+            query: Some("nature".into()),
+            count: Some(1),
+            ..Default::default()
+        };
+
+        let items = fetch(&source, &query).await.unwrap();
+        let head = server.requests().pop().unwrap().to_ascii_lowercase();
+        assert!(head.contains("authorization: pexels-key"), "{}", head);
+        assert!(head.contains("per_page=1"), "{}", head);
+
+        assert_eq!(items.len(), 1);
+        let item = &items[0];
+        assert_eq!(item.source, "pexels");
+        assert_eq!(item.id, "2014422");
+        assert_eq!(
+            item.url,
+            "https://images.pexels.com/photos/2014422/pexels-photo-2014422.jpeg"
+        );
+        assert!(item.thumbnail.as_deref().unwrap().contains("h=350"));
+        assert_eq!(
+            item.page_url.as_deref(),
+            Some("https://www.pexels.com/photo/brown-rocks-during-golden-hour-2014422/")
+        );
+        assert_eq!(item.title, "Brown Rocks During Golden Hour");
+        assert_eq!(item.author, "Joey Farina");
+        assert_eq!(item.license, "Pexels License");
+        assert_eq!((item.width, item.height), (Some(3024), Some(3024)));
+    }
+
+    #[tokio::test]
+    async fn pexels_without_query_uses_curated() {
+        let server = StandIn::start(vec![("/v1/curated", JSON, fixture!("pexels.json"))]).await;
+        let source =
+            PexelsSource::new(Some(server.base_url.clone()), Some("pexels-key".into())).unwrap();
+
+        let items = fetch(&source, &SourceQuery::default()).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(server.last_target().starts_with("/v1/curated?"));
+    }
+
+    #[tokio::test]
+    async fn wallhaven_normalizes_wallpapers() {
+        let server =
+            StandIn::start(vec![("/api/v1/search", JSON, fixture!("wallhaven.json"))]).await;
+        let source = WallhavenSource::new(Some(server.base_url.clone()), Some("wh-key".into()));
+        let query = SourceQuery {
+            query: Some("nature".into()),
+            ..Default::default()
+        };
+
+        let items = fetch(&source, &query).await.unwrap();
+        let target = server.last_target();
+        assert!(target.contains("q=nature"), "{}", target);
+        assert!(target.contains("apikey=wh-key"), "{}", target);
+
+        assert_eq!(items.len(), 2);
+        let first = &items[0];
+        assert_eq!(first.source, "wallhaven");
+        assert_eq!(first.id, "94x38z");
+        assert_eq!(first.title, "Wallhaven 94x38z");
+        assert_eq!(
+            first.url,
+            "https://w.wallhaven.cc/full/94/wallhaven-94x38z.jpg"
+        );
+        assert_eq!(
+            first.thumbnail.as_deref(),
+            Some("https://th.wallhaven.cc/lg/94/94x38z.jpg")
+        );
+        assert_eq!(
+            first.page_url.as_deref(),
+            Some("https://wallhaven.cc/w/94x38z")
+        );
+        assert_eq!(first.author, "");
+        assert_eq!((first.width, first.height), (Some(6742), Some(3534)));
+
+        let second = &items[1];
+        assert_eq!(second.author, "someone");
+        assert_eq!(second.thumbnail, None);
+    }
+
+    #[tokio::test]
+    async fn rss_picks_largest_media_content() {
+        let server = StandIn::start(vec![("/feed.xml", XML, fixture!("feed.rss.xml"))]).await;
+        let source = FeedSource::new(Some(format!("{}/feed.xml", server.base_url)));
+
+        let items = fetch(&source, &SourceQuery::default()).await.unwrap();
+        let head = server.requests().pop().unwrap().to_ascii_lowercase();
+        assert!(head.contains("accept: application/rss+xml"), "{}", head);
+
+        // 音频 enclosure 的条目被跳过
+        assert_eq!(items.len(), 2);
+        let sunrise = &items[0];
+        assert_eq!(sunrise.source, "rss");
+        assert_eq!(sunrise.id, "photo-1001");
+        // 视频尺寸更大但不是图片，取最大的图片
+        assert_eq!(sunrise.url, "https://photos.example.com/img/1001-large.jpg");
+        assert_eq!((sunrise.width, sunrise.height), (Some(3840), Some(2160)));
+        assert_eq!(
+            sunrise.thumbnail.as_deref(),
+            Some("https://photos.example.com/img/1001-thumb.jpg")
+        );
+        assert_eq!(
+            sunrise.page_url.as_deref(),
+            Some("https://photos.example.com/p/mountain-sunrise")
+        );
+        assert_eq!(sunrise.title, "Mountain sunrise");
+        assert_eq!(sunrise.author, "Alice Example");
+        assert_eq!(sunrise.license, "CC0 1.0");
+
+        // enclosure 条目，许可取频道版权，id 取链接
+        let harbour = &items[1];
+        assert_eq!(harbour.url, "https://photos.example.com/img/1002.jpg");
+        assert_eq!(harbour.id, "https://photos.example.com/p/quiet-harbour");
+        assert_eq!(harbour.license, "CC BY 4.0");
+        assert_eq!((harbour.width, harbour.height), (None, None));
+    }
+
+    #[tokio::test]
+    async fn atom_reads_enclosure_links() {
+        let server = StandIn::start(vec![("/atom.xml", XML, fixture!("feed.atom.xml"))]).await;
+        // 查询中的地址优先于配置
+        let source = FeedSource::new(Some(format!("{}/missing.xml", server.base_url)));
+        let query = SourceQuery {
+            url: Some(format!("{}/atom.xml", server.base_url)),
+            ..Default::default()
+        };
+
+        let items = fetch(&source, &query).await.unwrap();
+        assert_eq!(items.len(), 1);
+        let dunes = &items[0];
+        assert_eq!(dunes.id, "urn:uuid:1225c695-cfb8-4ebb-aaaa-80da344efa6a");
+        assert_eq!(dunes.url, "https://atom.example.com/img/dunes.png");
+        assert_eq!(
+            dunes.page_url.as_deref(),
+            Some("https://atom.example.com/dunes")
+        );
+        assert_eq!(dunes.title, "Desert dunes");
+        assert_eq!(dunes.author, "Bob Example");
+        assert_eq!(dunes.license, "All rights reserved");
+    }
+
+    #[tokio::test]
+    async fn feed_requires_url() {
+        let error = fetch(
+            &FeedSource::new(Some(String::new())),
+            &SourceQuery::default(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, "Feed URL is not configured");
+    }
+
+    #[test]
+    fn malformed_responses_are_errors() {
+        let error = BingSource::new(None, None).parse("<html>").unwrap_err();
+        assert!(
+            error.starts_with("Failed to parse Bing response"),
+            "{}",
+            error
+        );
+        let error = FeedSource::new(None).parse("{}").unwrap_err();
+        assert!(error.starts_with("Failed to parse feed"), "{}", error);
+    }
+}
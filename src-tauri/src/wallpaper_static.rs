@@ -1,10 +1,25 @@
 use std::fs;
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tauri::Manager;
 
+use crate::config::read_config_value;
+use crate::connectivity::{check_connectivity, is_online, queue_download, SET_WALLPAPER};
+use crate::data_dir::try_write_guard;
+use crate::dedup::{import_static_file, ImportOutcome};
+use crate::download::{download_to_file, DownloadOptions};
 use crate::fs_helper::{get_appdata_dir, read_folder_files};
+use crate::image_format::{detect_file_format, prepare_for_desktop};
+use crate::image_pipeline::{process_image, PipelineOptions};
+use crate::library::remember_download_source;
+use crate::linked_folders::{is_linked_path, linked_folder_images};
+use crate::storage::mark_active_wallpaper;
 use crate::tool::wait_for_window_closed;
+use crate::trash::move_to_trash;
+
+/// 临时文件序号，同一毫秒内的多次下载也不会使用相同的文件名
+static DOWNLOAD_COUNTER: AtomicU64 = AtomicU64::new(0);
 
 #[tauri::command]
 pub fn delete_wallpaper_static(path: String) -> Result<(), String> {
@@ -14,13 +29,29 @@ pub fn delete_wallpaper_static(path: String) -> Result<(), String> {
         return Err(format!("File not found: {}", path));
     }
 
-    fs::remove_file(&path_buf).map_err(|e| format!("Failed to delete file: {}", e))?;
+    // 关联文件夹为只读来源
+    if is_linked_path(&path) {
+        return Err(format!("Cannot delete file in linked folder: {}", path));
+    }
+
+    // 移入回收站，可恢复
+    move_to_trash("static", &path_buf)?;
 
     Ok(())
 }
 
+/// 读取静态壁纸列表
+///
+/// 关联文件夹可能是响应缓慢的网络盘，检查与扫描放到阻塞线程池中执行，不阻塞界面。
 #[tauri::command]
-pub fn read_wallpaper_static() -> Result<Vec<String>, String> {
+pub async fn read_wallpaper_static() -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(static_wallpaper_files)
+        .await
+        .map_err(|e| format!("Failed to read wallpapers: {}", e))?
+}
+
+/// 静态壁纸目录与可用关联文件夹中的全部图片
+pub fn static_wallpaper_files() -> Result<Vec<String>, String> {
     // 获取 appdata 目录下的 oPaper 路径
     let base_dir = get_appdata_dir()?;
 
@@ -33,12 +64,8 @@ pub fn read_wallpaper_static() -> Result<Vec<String>, String> {
     let mut images = Vec::new();
 
     for file in &files {
-        let ext = PathBuf::from(file)
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("")
-            .to_lowercase();
-        if ext != "jpg" && ext != "jpeg" && ext != "png" && ext != "gif" {
+        // 按文件头识别格式，扩展名不可靠
+        if detect_file_format(&PathBuf::from(file)).is_none() {
             println!("Skipping non-image file: {}", file);
             continue; // 跳过非图片文件
         }
@@ -46,11 +73,16 @@ pub fn read_wallpaper_static() -> Result<Vec<String>, String> {
         images.push(file.clone());
     }
 
+    // 关联文件夹中的图片（已按格式过滤）
+    images.extend(linked_folder_images());
+
     Ok(images)
 }
 
+/// 保存图片到静态壁纸库，返回保存结果（是否为重复内容、是否改名）
 #[tauri::command]
-pub fn copy_wallpaper_to_wallpaper_static(path: String) -> Result<String, String> {
+pub fn copy_wallpaper_to_wallpaper_static(path: String) -> Result<ImportOutcome, String> {
+    let _writing = try_write_guard()?;
     // 获取 appdata 目录下的 oPaper 路径
     let base_dir = get_appdata_dir()?;
 
@@ -59,114 +91,182 @@ pub fn copy_wallpaper_to_wallpaper_static(path: String) -> Result<String, String
     fs::create_dir_all(&resource_dir)
         .map_err(|e| format!("Failed to create resource directory: {}", e))?;
 
-    let pa = PathBuf::from(&path);
-    let file_name = pa
-        .file_name()
-        .and_then(|name| name.to_str())
-        .ok_or_else(|| "Invalid file name".to_string())?;
-
-    let dest_path = resource_dir.join(file_name);
-
-    fs::copy(&path, &dest_path).map_err(|e| format!("Failed to copy image: {}", e))?;
-
-    Ok(dest_path.to_string_lossy().to_string())
+    // 内容相同的图片不重复保存，重名时自动改名
+    import_static_file(&path, &resource_dir)
 }
 
+/// 下载图片并设置为壁纸
+///
+/// `queue` 为 false 时离线直接返回错误，不加入待处理队列（如云端随机图片，恢复在线后已无意义）。
 #[tauri::command]
 pub async fn set_static_wallpaper_from_url(
     app: tauri::AppHandle,
     url: String,
+    queue: Option<bool>,
 ) -> Result<String, String> {
-    // 检查并关闭 background 窗口
-    if let Some(window) = app.get_webview_window("background") {
-        println!("Closing background window before setting static wallpaper");
-        window
-            .close()
-            .map_err(|e| format!("Failed to close background window: {}", e))?;
+    set_wallpaper_from_url(&app, &url, queue.unwrap_or(true)).await
+}
 
-        // 等待窗口完全关闭
-        wait_for_window_closed(&app, "background", 2000)
-            .map_err(|e| format!("Failed to wait for window close: {}", e))?;
+/// 离线时的错误：`queue` 为 true 时请求已加入待处理队列
+fn offline_error(url: &str, queue: bool) -> String {
+    if !queue {
+        return "Network is offline".to_string();
+    }
+    match queue_download(url, SET_WALLPAPER) {
+        Ok(()) => {
+            "Network is offline, the wallpaper will be set when the connection returns".to_string()
+        }
+        Err(e) => format!(
+            "Network is offline and the request could not be queued: {}",
+            e
+        ),
+    }
+}
 
-        println!("Background window closed successfully");
+/// 下载图片并设置为壁纸，离线时（`queue` 为 true）加入待处理队列，恢复在线后再设置
+pub async fn set_wallpaper_from_url(
+    app: &tauri::AppHandle,
+    url: &str,
+    queue: bool,
+) -> Result<String, String> {
+    if !is_online() {
+        return Err(offline_error(url, queue));
     }
+    // 下载与处理期间写入 temp 与缓存目录，迁移数据目录时拒绝
+    let _writing = try_write_guard()?;
 
-    // 下载图片
-    let image_data = download_image(&url).await?;
+    // 检查并关闭 background 窗口
+    close_background_window(app)?;
 
-    // 保存到应用 resource 文件夹
-    let file_name = format!("wallpaper_{}.jpg", chrono::Utc::now().timestamp());
-    // let file_name = "wallpaper_temp.jpg".to_string();
-    let temp_path = save_temp_image(&image_data, file_name)?;
+    // 下载图片（按实际下载到的格式确定扩展名）
+    let file_stem = format!(
+        "wallpaper_{}_{}",
+        chrono::Utc::now().timestamp_millis(),
+        DOWNLOAD_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let temp_path = match download_image(app, url, &file_stem).await {
+        Ok(path) => path,
+        // 下载失败时重新探测，确认离线则加入队列
+        Err(e) => {
+            if check_connectivity(app).await {
+                return Err(e);
+            }
+            return Err(offline_error(url, queue));
+        }
+    };
+    remember_download_source(&temp_path, url);
 
-    // 设置壁纸
-    set_wallpaper(&temp_path)?;
+    // 设置壁纸（启用图片处理时使用处理后的图片）
+    process_and_set_wallpaper(app, &temp_path).await?;
 
     Ok(temp_path)
 }
 
 #[tauri::command]
-pub fn set_static_wallpaper_from_path(
+pub async fn set_static_wallpaper_from_path(
     app: tauri::AppHandle,
     path: String,
 ) -> Result<String, String> {
-    // 检查并关闭 background 窗口
-    if let Some(window) = app.get_webview_window("background") {
-        println!("Closing background window before setting static wallpaper");
-        window
-            .close()
-            .map_err(|e| format!("Failed to close background window: {}", e))?;
+    apply_static_wallpaper(&app, &path).await?;
 
-        // 等待窗口完全关闭
-        wait_for_window_closed(&app, "background", 2000)
-            .map_err(|e| format!("Failed to wait for window close: {}", e))?;
+    Ok(format!("Wallpaper set successfully from: {}", path))
+}
 
-        println!("Background window closed successfully");
-    }
+/// 关闭动态壁纸窗口并将本地图片设置为系统壁纸
+pub async fn apply_static_wallpaper(app: &tauri::AppHandle, path: &str) -> Result<(), String> {
+    close_background_window(app)?;
 
-    let path_buf = PathBuf::from(&path);
+    let path_buf = PathBuf::from(path);
 
     if !path_buf.exists() {
         return Err(format!("File not found: {}", path));
     }
 
-    set_wallpaper(&path)?;
-
-    Ok(format!("Wallpaper set successfully from: {}", path))
+    process_and_set_wallpaper(app, path).await
 }
 
-async fn download_image(url: &str) -> Result<Vec<u8>, String> {
-    let response = reqwest::get(url)
+/// 预览图片处理效果，返回处理后的图片路径（不修改配置，也不设置壁纸）
+#[tauri::command]
+pub async fn preview_image_pipeline(
+    app: tauri::AppHandle,
+    path: String,
+    options: PipelineOptions,
+) -> Result<String, String> {
+    let screen = primary_screen_size(&app);
+    tauri::async_runtime::spawn_blocking(move || process_image(&path, &options, screen))
         .await
-        .map_err(|e| format!("Failed to download image: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "Failed to download image: HTTP {}",
-            response.status()
-        ));
-    }
+        .map_err(|e| format!("Failed to process image: {}", e))?
+}
 
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| format!("Failed to read image data: {}", e))?;
+/// 按配置中的 image_pipeline 处理图片（未启用时使用原图）并设置为壁纸
+///
+/// 解码、处理与转码都很耗 CPU，放到阻塞线程池中执行，不占用异步运行时。
+async fn process_and_set_wallpaper(app: &tauri::AppHandle, path: &str) -> Result<(), String> {
+    let options: PipelineOptions = read_config_value()
+        .get("image_pipeline")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let screen = primary_screen_size(app);
+    let source = path.to_string();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let wallpaper_path = if options.enabled {
+            process_image(&source, &options, screen)?
+        } else {
+            source.clone()
+        };
+        set_wallpaper(&source, &wallpaper_path)
+    })
+    .await
+    .map_err(|e| format!("Failed to set wallpaper: {}", e))?
+}
 
-    Ok(bytes.to_vec())
+/// 主显示器的物理分辨率
+fn primary_screen_size(app: &tauri::AppHandle) -> Option<(u32, u32)> {
+    app.primary_monitor()
+        .ok()
+        .flatten()
+        .map(|monitor| (monitor.size().width, monitor.size().height))
 }
 
-fn save_temp_image(data: &[u8], file_name: String) -> Result<String, String> {
-    // 获取 appdata 目录下的 oPaper 路径
-    let base_dir = get_appdata_dir()?;
+/// 检查并关闭 background 窗口
+fn close_background_window(app: &tauri::AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("background") {
+        println!("Closing background window before setting static wallpaper");
+        window
+            .close()
+            .map_err(|e| format!("Failed to close background window: {}", e))?;
 
-    // 创建 temp 目录
-    let resource_dir = base_dir.join("temp");
-    fs::create_dir_all(&resource_dir)
-        .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+        // 等待窗口完全关闭
+        wait_for_window_closed(app, "background", 2000)
+            .map_err(|e| format!("Failed to wait for window close: {}", e))?;
 
-    let image_path = resource_dir.join(file_name);
+        println!("Background window closed successfully");
+    }
+
+    Ok(())
+}
 
-    fs::write(&image_path, data).map_err(|e| format!("Failed to save image: {}", e))?;
+/// 下载图片到 temp 目录，按实际内容确定扩展名，返回文件路径
+pub async fn download_image(
+    app: &tauri::AppHandle,
+    url: &str,
+    file_stem: &str,
+) -> Result<String, String> {
+    let temp_dir = get_appdata_dir()?.join("temp");
+    let download_path = temp_dir.join(format!("{}.download", file_stem));
+    download_to_file(Some(app), url, &download_path, &DownloadOptions::image()).await?;
+
+    let format = match detect_file_format(&download_path) {
+        Some(format) => format,
+        None => {
+            let _ = fs::remove_file(&download_path);
+            return Err("Downloaded file is not a supported image".to_string());
+        }
+    };
+
+    let image_path = temp_dir.join(format!("{}.{}", file_stem, format.extension()));
+    fs::rename(&download_path, &image_path).map_err(|e| format!("Failed to save image: {}", e))?;
 
     image_path
         .to_str()
@@ -174,7 +274,14 @@ fn save_temp_image(data: &[u8], file_name: String) -> Result<String, String> {
         .map(|s| s.to_string())
 }
 
-fn set_wallpaper(path: &str) -> Result<(), String> {
+/// 将图片设置为系统壁纸，`source` 为处理前的原图
+fn set_wallpaper(source: &str, path: &str) -> Result<(), String> {
+    // 桌面后端不支持的格式先转码到缓存目录
+    let prepared = prepare_for_desktop(path)?;
+    // 当前壁纸用到的文件不参与临时文件清理与缓存淘汰
+    mark_active_wallpaper(&[source, path, &prepared]);
+    let path = prepared.as_str();
+
     #[cfg(target_os = "macos")]
     {
         set_wallpaper_macos(path)
@@ -241,23 +348,29 @@ fn set_wallpaper_windows(path: &str) -> Result<(), String> {
 #[cfg(target_os = "linux")]
 fn set_wallpaper_linux(path: &str) -> Result<(), String> {
     // 尝试使用 gsettings (GNOME)
+    let uri = format!("file://{}", path);
     let output = Command::new("gsettings")
-        .args(&[
-            "set",
-            "org.gnome.desktop.background",
-            "picture-uri",
-            &format!("file://{}", path),
-        ])
+        .args(["set", "org.gnome.desktop.background", "picture-uri", &uri])
         .output();
 
     if let Ok(output) = output {
         if output.status.success() {
+            // GNOME 42+ 深色模式下使用 picture-uri-dark，同步设置以免显示旧壁纸
+            // 旧版本没有该键，失败可以忽略
+            let _ = Command::new("gsettings")
+                .args([
+                    "set",
+                    "org.gnome.desktop.background",
+                    "picture-uri-dark",
+                    &uri,
+                ])
+                .output();
             return Ok(());
         }
     }
 
     // 尝试使用 feh (通用)
-    let output = Command::new("feh").args(&["--bg-scale", path]).output();
+    let output = Command::new("feh").args(["--bg-scale", path]).output();
 
     if let Ok(output) = output {
         if output.status.success() {
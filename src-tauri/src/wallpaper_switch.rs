@@ -0,0 +1,66 @@
+// 壁纸切换模块：由后端任务（定时、主题等）统一应用静态 / 着色器 / HTML 壁纸
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::update_config;
+use crate::wallpaper_animation::create_animation_wallpaper;
+use crate::wallpaper_static::apply_static_wallpaper;
+
+/// 一个可被应用的壁纸
+///
+/// `kind` 取值为 `static` / `shader` / `html`，`path` 为图片、`shader.glsl`、
+/// `index.html` 的完整路径，着色器和 HTML 壁纸也可以直接传入所在文件夹。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WallpaperTarget {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub path: String,
+}
+
+/// 应用壁纸，并同步更新配置中的 mode / shaderPath / htmlPath
+pub async fn apply_wallpaper_target(
+    app: &tauri::AppHandle,
+    target: &WallpaperTarget,
+) -> Result<(), String> {
+    match target.kind.as_str() {
+        "static" => {
+            update_config(serde_json::json!({ "mode": "static" }))?;
+            apply_static_wallpaper(app, &target.path).await
+        }
+        "shader" => {
+            let shader_path = resolve_entry_file(&target.path, "shader.glsl")?;
+            update_config(serde_json::json!({
+                "mode": "shader",
+                "loop": false,
+                "shaderPath": shader_path,
+            }))?;
+            create_animation_wallpaper(app.clone()).await.map(|_| ())
+        }
+        "html" => {
+            let html_path = resolve_entry_file(&target.path, "index.html")?;
+            update_config(serde_json::json!({
+                "mode": "html",
+                "loop": false,
+                "htmlPath": html_path,
+            }))?;
+            create_animation_wallpaper(app.clone()).await.map(|_| ())
+        }
+        other => Err(format!("Unsupported wallpaper type: {}", other)),
+    }
+}
+
+/// 文件夹路径补全为入口文件路径
+fn resolve_entry_file(path: &str, entry: &str) -> Result<String, String> {
+    let mut path_buf = PathBuf::from(path);
+    if path_buf.is_dir() {
+        path_buf = path_buf.join(entry);
+    }
+
+    if !path_buf.exists() {
+        return Err(format!("File not found: {}", path_buf.display()));
+    }
+
+    Ok(path_buf.to_string_lossy().to_string())
+}
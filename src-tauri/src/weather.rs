@@ -0,0 +1,777 @@
+// 天气模块：为 HTML 壁纸提供当前天气，并供轮播按天气选择壁纸
+//
+// 配置示例（config.json）：
+// "weather": {
+//   "enabled": true,                 // 后台定时刷新
+//   "provider": "open-meteo",        // open-meteo / openweather / mock
+//   "latitude": 31.23,               // 为空时使用 schedule 中的坐标
+//   "longitude": 121.47,
+//   "units": "metric",               // metric（℃、m/s）/ imperial（℉、mph）
+//   "api_key": "{{secret:openweather}}",  // OpenWeather 需要，可引用凭据
+//   "base_url": "http://127.0.0.1:8787",  // 可选，覆盖服务地址
+//   "refresh_minutes": 30
+// }
+//
+// mock 提供者请求本地测试服务 `GET <base_url>/weather?latitude=&longitude=&units=`，
+// 响应即为 { "condition": "rain", "temperature": 12.5, ... }，便于在没有外网时测试。
+//
+// 天气更新后发出 `weather-updated` 事件，内容为 Weather；最近一次结果保存在 weather.json 中。
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::config::read_config_value;
+use crate::connectivity::is_online;
+use crate::data_dir::background_write_guard;
+use crate::fs_helper::{read_file, write_file};
+use crate::network::{http_client, request_timeout};
+use crate::rotation::weather_changed;
+use crate::wallpaper_source::{parse_json, resolve_key, trim_base};
+
+/// 最近一次天气（相对 appdata 目录）
+const STATE_FILE: &str = "weather.json";
+/// 后台检查间隔
+const CHECK_INTERVAL_SECS: u64 = 60;
+/// 刷新失败后的重试间隔
+const RETRY_SECS: u64 = 5 * 60;
+/// 强制刷新的最短间隔，避免壁纸频繁请求
+const MIN_REFRESH_SECS: i64 = 60;
+/// 轮播按天气选择时，天气结果的最长有效时间
+const MAX_CONDITION_AGE_SECS: i64 = 6 * 60 * 60;
+
+/// 最近一次天气
+static CURRENT: Mutex<Option<Weather>> = Mutex::new(None);
+
+/// 天气状况，对应壁纸标签
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeatherCondition {
+    Clear,
+    Cloudy,
+    Rain,
+    Snow,
+}
+
+impl WeatherCondition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WeatherCondition::Clear => "clear",
+            WeatherCondition::Cloudy => "cloudy",
+            WeatherCondition::Rain => "rain",
+            WeatherCondition::Snow => "snow",
+        }
+    }
+
+    /// 对应的壁纸标签
+    pub fn tags(&self) -> &'static [&'static str] {
+        match self {
+            WeatherCondition::Clear => &["clear", "sunny", "晴"],
+            WeatherCondition::Cloudy => &["cloudy", "多云", "阴"],
+            WeatherCondition::Rain => &["rain", "rainy", "雨"],
+            WeatherCondition::Snow => &["snow", "snowy", "雪"],
+        }
+    }
+
+    pub fn matches_tag(&self, tag: &str) -> bool {
+        let tag = tag.trim().to_lowercase();
+        self.tags().iter().any(|t| *t == tag)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Units {
+    Metric,
+    Imperial,
+}
+
+impl Units {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "" | "metric" => Ok(Units::Metric),
+            "imperial" => Ok(Units::Imperial),
+            other => Err(format!("Unsupported units: {}", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Units::Metric => "metric",
+            Units::Imperial => "imperial",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Weather {
+    pub provider: String,
+    pub condition: WeatherCondition,
+    pub description: String,
+    pub temperature: f64,
+    pub feels_like: Option<f64>,
+    /// 相对湿度（%）
+    pub humidity: Option<f64>,
+    pub wind_speed: Option<f64>,
+    pub is_day: Option<bool>,
+    pub units: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// 获取时间（Unix 秒）
+    pub fetched_at: i64,
+    /// 刷新失败时返回的旧结果
+    #[serde(default)]
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeatherConfig {
+    pub enabled: bool,
+    pub provider: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub units: String,
+    pub api_key: Option<String>,
+    pub base_url: Option<String>,
+    pub refresh_minutes: u64,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        WeatherConfig {
+            enabled: false,
+            provider: "open-meteo".to_string(),
+            latitude: None,
+            longitude: None,
+            units: "metric".to_string(),
+            api_key: None,
+            base_url: None,
+            refresh_minutes: 30,
+        }
+    }
+}
+
+pub trait WeatherProvider: Send + Sync {
+    fn id(&self) -> &'static str;
+
+    /// 构造请求
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        location: Location,
+        units: Units,
+    ) -> Result<reqwest::RequestBuilder, String>;
+
+    /// 解析响应内容
+    fn parse(&self, body: &str, location: Location, units: Units) -> Result<Weather, String>;
+}
+
+// ---------------------------------------------------------------------------
+// Open-Meteo
+
+#[derive(Deserialize)]
+struct OpenMeteoResponse {
+    current: OpenMeteoCurrent,
+}
+
+#[derive(Deserialize)]
+struct OpenMeteoCurrent {
+    temperature_2m: f64,
+    apparent_temperature: Option<f64>,
+    relative_humidity_2m: Option<f64>,
+    weather_code: u32,
+    wind_speed_10m: Option<f64>,
+    is_day: Option<u8>,
+}
+
+/// WMO 天气代码
+fn wmo_condition(code: u32) -> (WeatherCondition, &'static str) {
+    match code {
+        0 => (WeatherCondition::Clear, "晴"),
+        1 => (WeatherCondition::Clear, "少云"),
+        2 => (WeatherCondition::Cloudy, "多云"),
+        3 => (WeatherCondition::Cloudy, "阴"),
+        45 | 48 => (WeatherCondition::Cloudy, "雾"),
+        51 | 53 | 55 => (WeatherCondition::Rain, "毛毛雨"),
+        56 | 57 => (WeatherCondition::Rain, "冻毛毛雨"),
+        61 | 63 | 65 => (WeatherCondition::Rain, "雨"),
+        66 | 67 => (WeatherCondition::Rain, "冻雨"),
+        71 | 73 | 75 => (WeatherCondition::Snow, "雪"),
+        77 => (WeatherCondition::Snow, "米雪"),
+        80..=82 => (WeatherCondition::Rain, "阵雨"),
+        85 | 86 => (WeatherCondition::Snow, "阵雪"),
+        95 | 96 | 99 => (WeatherCondition::Rain, "雷雨"),
+        _ => (WeatherCondition::Cloudy, "未知"),
+    }
+}
+
+pub struct OpenMeteoProvider {
+    base_url: String,
+}
+
+impl OpenMeteoProvider {
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.open-meteo.com";
+
+    pub fn new(base_url: Option<String>) -> Self {
+        OpenMeteoProvider {
+            base_url: trim_base(base_url, Self::DEFAULT_BASE_URL),
+        }
+    }
+}
+
+impl WeatherProvider for OpenMeteoProvider {
+    fn id(&self) -> &'static str {
+        "open-meteo"
+    }
+
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        location: Location,
+        units: Units,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let (temperature_unit, wind_speed_unit) = match units {
+            Units::Metric => ("celsius", "ms"),
+            Units::Imperial => ("fahrenheit", "mph"),
+        };
+        Ok(client
+            .get(format!("{}/v1/forecast", self.base_url))
+            .query(&[
+                ("latitude", location.latitude.to_string()),
+                ("longitude", location.longitude.to_string()),
+                (
+                    "current",
+                    "temperature_2m,apparent_temperature,relative_humidity_2m,weather_code,wind_speed_10m,is_day"
+                        .to_string(),
+                ),
+                ("temperature_unit", temperature_unit.to_string()),
+                ("wind_speed_unit", wind_speed_unit.to_string()),
+            ]))
+    }
+
+    fn parse(&self, body: &str, location: Location, units: Units) -> Result<Weather, String> {
+        let response: OpenMeteoResponse = parse_json(body, "Open-Meteo")?;
+        let current = response.current;
+        let (condition, description) = wmo_condition(current.weather_code);
+        Ok(Weather {
+            provider: self.id().to_string(),
+            condition,
+            description: description.to_string(),
+            temperature: current.temperature_2m,
+            feels_like: current.apparent_temperature,
+            humidity: current.relative_humidity_2m,
+            wind_speed: current.wind_speed_10m,
+            is_day: current.is_day.map(|v| v == 1),
+            units: units.as_str().to_string(),
+            latitude: location.latitude,
+            longitude: location.longitude,
+            fetched_at: chrono::Utc::now().timestamp(),
+            stale: false,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// OpenWeather
+
+#[derive(Deserialize)]
+struct OpenWeatherResponse {
+    weather: Vec<OpenWeatherCondition>,
+    main: OpenWeatherMain,
+    wind: Option<OpenWeatherWind>,
+}
+
+#[derive(Deserialize)]
+struct OpenWeatherCondition {
+    id: u32,
+    #[serde(default)]
+    description: String,
+    /// 图标名称，以 d / n 结尾表示白天 / 夜晚
+    #[serde(default)]
+    icon: String,
+}
+
+#[derive(Deserialize)]
+struct OpenWeatherMain {
+    temp: f64,
+    feels_like: Option<f64>,
+    humidity: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct OpenWeatherWind {
+    speed: f64,
+}
+
+/// OpenWeather 天气代码：2xx 雷暴、3xx 毛毛雨、5xx 雨、6xx 雪、7xx 雾霾、800 晴、80x 云
+fn open_weather_condition(id: u32) -> WeatherCondition {
+    match id {
+        200..=599 => WeatherCondition::Rain,
+        600..=699 => WeatherCondition::Snow,
+        800 => WeatherCondition::Clear,
+        _ => WeatherCondition::Cloudy,
+    }
+}
+
+pub struct OpenWeatherProvider {
+    base_url: String,
+    api_key: String,
+}
+
+impl OpenWeatherProvider {
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.openweathermap.org";
+
+    pub fn new(base_url: Option<String>, api_key: Option<String>) -> Result<Self, String> {
+        let base_url = trim_base(base_url, Self::DEFAULT_BASE_URL);
+        let api_key = api_key
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| "OpenWeather API key is not configured".to_string())?;
+        Ok(OpenWeatherProvider {
+            api_key: resolve_key(&api_key, &base_url)?,
+            base_url,
+        })
+    }
+}
+
+impl WeatherProvider for OpenWeatherProvider {
+    fn id(&self) -> &'static str {
+        "openweather"
+    }
+
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        location: Location,
+        units: Units,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        Ok(client
+            .get(format!("{}/data/2.5/weather", self.base_url))
+            .query(&[
+                ("lat", location.latitude.to_string()),
+                ("lon", location.longitude.to_string()),
+                ("units", units.as_str().to_string()),
+                ("lang", "zh_cn".to_string()),
+                ("appid", self.api_key.clone()),
+            ]))
+    }
+
+    fn parse(&self, body: &str, location: Location, units: Units) -> Result<Weather, String> {
+        let response: OpenWeatherResponse = parse_json(body, "OpenWeather")?;
+        let current = response
+            .weather
+            .into_iter()
+            .next()
+            .ok_or_else(|| "OpenWeather response has no weather".to_string())?;
+        Ok(Weather {
+            provider: self.id().to_string(),
+            condition: open_weather_condition(current.id),
+            description: current.description,
+            temperature: response.main.temp,
+            feels_like: response.main.feels_like,
+            humidity: response.main.humidity,
+            wind_speed: response.wind.map(|wind| wind.speed),
+            is_day: match current.icon.chars().last() {
+                Some('d') => Some(true),
+                Some('n') => Some(false),
+                _ => None,
+            },
+            units: units.as_str().to_string(),
+            latitude: location.latitude,
+            longitude: location.longitude,
+            fetched_at: chrono::Utc::now().timestamp(),
+            stale: false,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 本地测试服务
+
+#[derive(Deserialize)]
+struct MockResponse {
+    condition: WeatherCondition,
+    #[serde(default)]
+    description: String,
+    temperature: f64,
+    feels_like: Option<f64>,
+    humidity: Option<f64>,
+    wind_speed: Option<f64>,
+    is_day: Option<bool>,
+}
+
+pub struct MockWeatherProvider {
+    base_url: String,
+}
+
+impl MockWeatherProvider {
+    pub const DEFAULT_BASE_URL: &'static str = "http://127.0.0.1:8787";
+
+    pub fn new(base_url: Option<String>) -> Self {
+        MockWeatherProvider {
+            base_url: trim_base(base_url, Self::DEFAULT_BASE_URL),
+        }
+    }
+}
+
+impl WeatherProvider for MockWeatherProvider {
+    fn id(&self) -> &'static str {
+        "mock"
+    }
+
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        location: Location,
+        units: Units,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        Ok(client.get(format!("{}/weather", self.base_url)).query(&[
+            ("latitude", location.latitude.to_string()),
+            ("longitude", location.longitude.to_string()),
+            ("units", units.as_str().to_string()),
+        ]))
+    }
+
+    fn parse(&self, body: &str, location: Location, units: Units) -> Result<Weather, String> {
+        let response: MockResponse = parse_json(body, "mock weather")?;
+        Ok(Weather {
+            provider: self.id().to_string(),
+            condition: response.condition,
+            description: response.description,
+            temperature: response.temperature,
+            feels_like: response.feels_like,
+            humidity: response.humidity,
+            wind_speed: response.wind_speed,
+            is_day: response.is_day,
+            units: units.as_str().to_string(),
+            latitude: location.latitude,
+            longitude: location.longitude,
+            fetched_at: chrono::Utc::now().timestamp(),
+            stale: false,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+
+pub fn read_weather_config() -> WeatherConfig {
+    read_config_value()
+        .get("weather")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// 天气位置：weather 中的坐标，未设置时使用日出日落定时的坐标
+fn location_of(config: &WeatherConfig) -> Result<Location, String> {
+    let schedule = read_config_value();
+    let coordinate = |own: Option<f64>, key: &str| {
+        own.or_else(|| {
+            schedule
+                .pointer(&format!("/schedule/{}", key))
+                .and_then(|v| v.as_f64())
+        })
+    };
+    checked_location(
+        coordinate(config.latitude, "latitude"),
+        coordinate(config.longitude, "longitude"),
+    )
+}
+
+fn checked_location(latitude: Option<f64>, longitude: Option<f64>) -> Result<Location, String> {
+    match (latitude, longitude) {
+        (Some(latitude), Some(longitude))
+            if (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude) =>
+        {
+            Ok(Location {
+                latitude,
+                longitude,
+            })
+        }
+        (Some(_), Some(_)) => Err("Invalid weather location".to_string()),
+        _ => Err("Weather location is not configured".to_string()),
+    }
+}
+
+/// 按配置创建天气提供者
+pub fn create_provider(config: &WeatherConfig) -> Result<Box<dyn WeatherProvider>, String> {
+    let base_url = config.base_url.clone();
+    Ok(match config.provider.as_str() {
+        "" | "open-meteo" => Box::new(OpenMeteoProvider::new(base_url)),
+        "openweather" => Box::new(OpenWeatherProvider::new(base_url, config.api_key.clone())?),
+        "mock" => Box::new(MockWeatherProvider::new(base_url)),
+        other => return Err(format!("Unknown weather provider: {}", other)),
+    })
+}
+
+/// 请求当前天气
+pub async fn fetch_weather(config: &WeatherConfig) -> Result<Weather, String> {
+    let location = location_of(config)?;
+    fetch_weather_with(&http_client()?, location, config, request_timeout()).await
+}
+
+/// 使用指定的客户端与超时请求 `location` 的天气，不读取配置与网络设置
+async fn fetch_weather_with(
+    client: &reqwest::Client,
+    location: Location,
+    config: &WeatherConfig,
+    timeout: Duration,
+) -> Result<Weather, String> {
+    let units = Units::parse(&config.units)?;
+    let provider = create_provider(config)?;
+
+    let response = provider
+        .request(client, location, units)?
+        .timeout(timeout)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to request {}: {}", provider.id(), e))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to request {}: HTTP {}",
+            provider.id(),
+            response.status()
+        ));
+    }
+
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read {} response: {}", provider.id(), e))?;
+    provider.parse(&body, location, units)
+}
+
+/// 最近一次天气（内存中没有时读取 weather.json）
+fn cached_weather() -> Option<Weather> {
+    let mut current = CURRENT.lock().ok()?;
+    if current.is_none() {
+        *current = read_file(STATE_FILE.to_string())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+    }
+    current.clone()
+}
+
+fn store_weather(weather: &Weather) {
+    if let Ok(mut current) = CURRENT.lock() {
+        *current = Some(weather.clone());
+    }
+    match serde_json::to_string(weather) {
+        Ok(content) => {
+            if let Err(e) = write_file(STATE_FILE.to_string(), content) {
+                eprintln!("Weather: failed to save: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Weather: failed to save: {}", e),
+    }
+}
+
+/// 缓存是否对应当前配置（提供者、单位、位置）
+fn cache_matches(weather: &Weather, config: &WeatherConfig) -> bool {
+    let provider = if config.provider.is_empty() {
+        "open-meteo"
+    } else {
+        config.provider.as_str()
+    };
+    let units = Units::parse(&config.units).map(|units| units.as_str());
+    let near = |a: f64, b: f64| (a - b).abs() < 0.05;
+    weather.provider == provider
+        && units == Ok(weather.units.as_str())
+        && location_of(config).is_ok_and(|location| {
+            near(location.latitude, weather.latitude) && near(location.longitude, weather.longitude)
+        })
+}
+
+fn age_of(weather: &Weather) -> i64 {
+    chrono::Utc::now().timestamp() - weather.fetched_at
+}
+
+/// 当前天气状况，供轮播按天气选择；没有较新的天气时为空
+pub fn current_condition() -> Option<WeatherCondition> {
+    cached_weather()
+        .filter(|weather| age_of(weather) < MAX_CONDITION_AGE_SECS)
+        .map(|weather| weather.condition)
+}
+
+/// 刷新天气并发出事件；天气状况变化时通知轮播
+async fn refresh(app: &tauri::AppHandle, config: &WeatherConfig) -> Result<Weather, String> {
+    let previous = cached_weather().map(|weather| weather.condition);
+    let weather = fetch_weather(config).await?;
+    store_weather(&weather);
+    let _ = app.emit("weather-updated", weather.clone());
+
+    if previous.is_some_and(|previous| previous != weather.condition) {
+        println!(
+            "Weather: condition changed to {}",
+            weather.condition.as_str()
+        );
+        weather_changed();
+    }
+    Ok(weather)
+}
+
+/// 获取当前天气；缓存未过期时直接返回，`force` 为 true 时重新请求（最短间隔一分钟）
+#[tauri::command]
+pub async fn get_weather(app: tauri::AppHandle, force: Option<bool>) -> Result<Weather, String> {
+    let config = read_weather_config();
+    let cached = cached_weather().filter(|weather| cache_matches(weather, &config));
+
+    if let Some(weather) = &cached {
+        let max_age = if force.unwrap_or(false) {
+            MIN_REFRESH_SECS
+        } else {
+            config.refresh_minutes.max(1) as i64 * 60
+        };
+        if age_of(weather) < max_age {
+            return Ok(weather.clone());
+        }
+    }
+
+    match refresh(&app, &config).await {
+        Ok(weather) => Ok(weather),
+        Err(e) => match cached {
+            Some(mut weather) => {
+                eprintln!("Weather: refresh failed, using cached result: {}", e);
+                weather.stale = true;
+                Ok(weather)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// 启动后台天气刷新任务
+pub fn start_weather_loop(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_failure: Option<Instant> = None;
+        loop {
+            let writing = background_write_guard().await;
+            let config = read_weather_config();
+            let retry_due = last_failure
+                .is_none_or(|failed| failed.elapsed() >= Duration::from_secs(RETRY_SECS));
+            // 离线时保留缓存，恢复在线后再刷新
+            if config.enabled && retry_due && is_online() {
+                let due = cached_weather()
+                    .filter(|weather| cache_matches(weather, &config))
+                    .is_none_or(|weather| {
+                        age_of(&weather) >= config.refresh_minutes.max(1) as i64 * 60
+                    });
+                if due {
+                    match refresh(&app, &config).await {
+                        Ok(_) => last_failure = None,
+                        Err(e) => {
+                            eprintln!("Weather: failed to refresh: {}", e);
+                            last_failure = Some(Instant::now());
+                        }
+                    }
+                }
+            }
+            drop(writing);
+
+            tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{client, StandIn};
+
+    const JSON: &str = "application/json";
+    const LOCATION: Location = Location {
+        latitude: 31.23,
+        longitude: 121.47,
+    };
+
+    /// 与 fetch_weather 相同地请求，但不读取配置，也不经过共享客户端（及其代理设置）
+    async fn fetch(config: &WeatherConfig) -> Result<Weather, String> {
+        fetch_weather_with(&client(), LOCATION, config, Duration::from_secs(10)).await
+    }
+
+    fn mock_config(base_url: &str) -> WeatherConfig {
+        WeatherConfig {
+            provider: "mock".to_string(),
+            units: "imperial".to_string(),
+            base_url: Some(format!("{}/", base_url)),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_weather_reads_mock_provider() {
+        let body = r#"{"condition":"rain","description":"小雨","temperature":54.5,"humidity":88,"is_day":false}"#;
+        let server = StandIn::start(vec![("/weather", JSON, body.to_string())]).await;
+
+        let weather = fetch(&mock_config(&server.base_url)).await.unwrap();
+        let target = server.last_target();
+        assert!(target.starts_with("/weather?"), "{}", target);
+        assert!(target.contains("latitude=31.23"), "{}", target);
+        assert!(target.contains("longitude=121.47"), "{}", target);
+        assert!(target.contains("units=imperial"), "{}", target);
+
+        assert_eq!(weather.provider, "mock");
+        assert_eq!(weather.condition, WeatherCondition::Rain);
+        assert_eq!(weather.description, "小雨");
+        assert_eq!(weather.temperature, 54.5);
+        assert_eq!(weather.humidity, Some(88.0));
+        assert_eq!(weather.feels_like, None);
+        assert_eq!(weather.is_day, Some(false));
+        assert_eq!(weather.units, "imperial");
+        assert_eq!((weather.latitude, weather.longitude), (31.23, 121.47));
+        assert!(!weather.stale);
+    }
+
+    #[tokio::test]
+    async fn fetch_weather_reports_http_errors() {
+        let server = StandIn::start(Vec::new()).await;
+
+        let error = fetch(&mock_config(&server.base_url)).await.unwrap_err();
+        assert!(error.contains("mock"), "{}", error);
+        assert!(error.contains("404"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn fetch_weather_rejects_invalid_responses() {
+        let body = r#"{"condition":"fog","temperature":10}"#;
+        let server = StandIn::start(vec![("/weather", JSON, body.to_string())]).await;
+
+        let error = fetch(&mock_config(&server.base_url)).await.unwrap_err();
+        assert!(
+            error.starts_with("Failed to parse mock weather response"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn invalid_location_is_rejected() {
+        assert_eq!(
+            checked_location(Some(91.0), Some(121.47)).unwrap_err(),
+            "Invalid weather location"
+        );
+        assert_eq!(
+            checked_location(Some(31.23), None).unwrap_err(),
+            "Weather location is not configured"
+        );
+        assert!(checked_location(Some(31.23), Some(121.47)).is_ok());
+    }
+
+    #[test]
+    fn open_weather_codes_map_to_conditions() {
+        assert_eq!(open_weather_condition(800), WeatherCondition::Clear);
+        assert_eq!(open_weather_condition(801), WeatherCondition::Cloudy);
+        assert_eq!(open_weather_condition(804), WeatherCondition::Cloudy);
+        assert_eq!(open_weather_condition(211), WeatherCondition::Rain);
+        assert_eq!(open_weather_condition(501), WeatherCondition::Rain);
+        assert_eq!(open_weather_condition(601), WeatherCondition::Snow);
+        assert_eq!(open_weather_condition(741), WeatherCondition::Cloudy);
+    }
+}
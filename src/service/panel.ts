@@ -1,6 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
 import { convertFileSrc } from "@tauri-apps/api/core";
-import { sleep } from "@/utils/util";
+import { mapWithLimit, sleep } from "@/utils/util";
 import Config from "@/service/config";
 
 interface IWallpaper {
@@ -10,6 +10,17 @@ interface IWallpaper {
   url: string;
 }
 
+interface IImportOutcome {
+  path: string;
+  /** 库中已有相同内容的图片，未重复保存 */
+  duplicate: boolean;
+  /** 因重名而改名保存 */
+  renamed: boolean;
+}
+
+/** 同时请求缩略图的数量 */
+const THUMBNAIL_CONCURRENCY = 4;
+
 export default class Panel {
   static async readConfig() {
     return await Config.readConfig();
@@ -24,16 +35,29 @@ export default class Panel {
       const images = await invoke("read_wallpaper_static");
       let wallpapers: IWallpaper[] = [];
       if (images && Array.isArray(images)) {
-        wallpapers = images.map((imagePath, index) => {
-          const fileUrl = convertFileSrc(imagePath);
-          return {
-            id: `local-${imagePath}`,
-            title: imagePath.split("/").pop() || `本地图片 ${index + 1}`,
-            thumbnail: fileUrl,
-            url: imagePath,
-            author: "本地图片",
-          };
-        });
+        // 限制并发，避免一次排入大量缩略图生成任务
+        wallpapers = await mapWithLimit(
+          images as string[],
+          THUMBNAIL_CONCURRENCY,
+          async (imagePath, index) => {
+            // 优先使用缩略图缓存，生成失败时回退到原图
+            let thumbnailPath = imagePath;
+            try {
+              thumbnailPath = await invoke<string>("get_wallpaper_thumbnail", {
+                path: imagePath,
+              });
+            } catch (e) {
+              console.log("get_wallpaper_thumbnail: " + e);
+            }
+            return {
+              id: `local-${imagePath}`,
+              title: imagePath.split("/").pop() || `本地图片 ${index + 1}`,
+              thumbnail: convertFileSrc(thumbnailPath),
+              url: imagePath,
+              author: "本地图片",
+            };
+          },
+        );
       }
       wallpapers = wallpapers.sort((a, b) =>
         b.title.localeCompare(a.title) > 0 ? 1 : -1,
@@ -51,8 +75,10 @@ export default class Panel {
     let pathUrl = "";
     let imageUrl = "";
     try {
+      // 随机图片恢复在线后再设置已无意义，离线时不加入待处理队列
       pathUrl = await invoke("set_static_wallpaper_from_url", {
         url: randomImageUrl,
+        queue: false,
       });
       imageUrl = convertFileSrc(pathUrl);
 
@@ -73,6 +99,19 @@ export default class Panel {
     }
   }
 
+  /** 当前是否在线，读取失败时按在线处理 */
+  static async isOnline(): Promise<boolean> {
+    try {
+      const status = await invoke<{ online: boolean }>(
+        "get_connectivity_status",
+      );
+      return status.online;
+    } catch (e) {
+      console.error("get_connectivity_status:", e);
+      return true;
+    }
+  }
+
   static async setWallpaperFromLocal(path: string, lastConfig = {}) {
     try {
       await invoke("set_config", {
@@ -88,13 +127,27 @@ export default class Panel {
     }
   }
 
-  static async downloadCurrentWallpaper(path: string) {
+  /** 保存到本地库，失败时返回 null */
+  static async downloadCurrentWallpaper(
+    path: string,
+  ): Promise<IImportOutcome | null> {
     try {
-      await invoke("copy_wallpaper_to_wallpaper_static", { path });
-      return true;
+      return await invoke<IImportOutcome>(
+        "copy_wallpaper_to_wallpaper_static",
+        { path },
+      );
     } catch (e) {
       console.error("download_current_wallpaper:", e);
-      return false;
+      return null;
+    }
+  }
+
+  static async listSavedSearches(): Promise<{ name: string; query: string }[]> {
+    try {
+      return await invoke("list_saved_searches");
+    } catch (e) {
+      console.error("list_saved_searches:", e);
+      return [];
     }
   }
 
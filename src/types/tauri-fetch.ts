@@ -1,15 +1,51 @@
 // Tauri fetch 相关类型定义
 
+export interface MultipartField {
+  name: string;
+  /** value / file_path / base64 三选一 */
+  value?: string;
+  file_path?: string;
+  base64?: string;
+  file_name?: string;
+  content_type?: string;
+}
+
+export type FetchResponseType = "text" | "json" | "base64" | "file";
+
 export interface FetchOptions {
   method?: string;
+  /** 请求头，可用 {{secret:名称}} 引用已保存的凭据 */
   headers?: Record<string, string>;
   body?: string;
+  /** 二进制请求体（base64 编码） */
+  body_base64?: string;
+  /** 二进制请求体（字节数组） */
+  body_bytes?: number[];
+  /** application/x-www-form-urlencoded 表单 */
+  form?: Record<string, string>;
+  /** multipart/form-data 表单 */
+  multipart?: MultipartField[];
+  /** 响应类型，默认 text */
+  response_type?: FetchResponseType;
+  /** response_type 为 file 时的保存路径 */
+  save_path?: string;
+  /** 缓存有效期（秒），代替服务器的缓存策略；0 表示不使用缓存 */
+  cache_ttl?: number;
+  /** Cookie 会话名称，指定时发送并保存该会话的 Cookie */
+  session?: string;
 }
 
 export interface FetchResponse {
   status: number;
   headers: Record<string, string>;
+  /** text / json 为文本，base64 为编码后的内容，file 为保存的文件路径 */
   body: string;
+  /** response_type 为 json 时解析后的内容 */
+  json?: any;
+  /** 响应来自本地缓存 */
+  from_cache: boolean;
+  /** 网络不可用时返回的过期缓存 */
+  stale: boolean;
 }
 
 // 导入 Tauri API